@@ -2,21 +2,35 @@ use std::error::Error;
 use std::{
     collections::HashMap,
     fmt::{Display, Formatter},
+    fs::{create_dir_all, read_to_string, write},
     hash::Hash,
+    io::ErrorKind,
+    sync::Arc,
 };
 
 use async_trait::async_trait;
 use tokio::sync::Mutex;
 
 use self::{
-    requests::{make_request, BungieRequest, BungieResponseError},
+    client::{BungieClient, ReqwestClient},
+    requests::{BungieRequest, BungieResponseError},
     responses::{
-        ActivityInfo, BungieProfile, CharacterActivityHistory, ProfileCurrentActivities,
-        ProfileInfo,
+        ActivityInfo, BungieProfile, CharacterActivityHistory, CharacterInfo, CommonSettings,
+        GlobalAlert, MalformedActivity, ProfileInfo, ProfileSnapshot,
+    },
+};
+use crate::{
+    config::{data_path, profiles::Profile},
+    consts::{
+        CHARACTERS_COMPONENT, MALFORMED_ACTIVITIES_FILE, MALFORMED_ACTIVITIES_LIMIT,
+        PROFILES_COMPONENT,
     },
 };
-use crate::config::profiles::Profile;
 
+pub mod client;
+#[cfg(test)]
+pub mod fake;
+pub mod fixtures;
 pub mod requests;
 pub mod responses;
 
@@ -39,55 +53,67 @@ impl Display for ApiError {
 
 impl Error for ApiError {}
 
+impl ApiError {
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ApiError::ResponseDeserializeError(_) => false,
+            ApiError::ResponseError(e) => e.is_retryable(),
+        }
+    }
+}
+
 #[async_trait]
 pub trait Source<K: Hash + Eq + Clone + Send + Sync, V: Clone + Send> {
     async fn get(&mut self, key: &K) -> Result<V, ApiError>
     where
         K: 'async_trait,
     {
-        let cache = self.cache();
-
-        if cache.contains_key(&key) {
-            return Ok(cache.get(&key).unwrap().clone());
+        if let Some(value) = self.cache().get(&key) {
+            return Ok(value.clone());
         }
 
-        let value_fut = Self::get_value(key.clone());
+        let value = self.get_value(key.clone()).await?;
 
-        let value = value_fut.await?;
-
-        cache.insert(key.clone(), value.clone());
+        self.cache().insert(key.clone(), value.clone());
 
         Ok(value)
     }
 
-    async fn get_value(key: K) -> Result<V, ApiError>;
+    async fn get_value(&self, key: K) -> Result<V, ApiError>;
 
     fn cache(&mut self) -> &mut HashMap<K, V>;
 }
 
-#[derive(Default)]
 pub struct ProfileInfoSource {
+    client: Arc<dyn BungieClient>,
     cache: HashMap<Profile, ProfileInfo>,
 }
 
 impl ProfileInfoSource {
-    pub fn set_characters(&mut self, profile: &Profile, characters: Vec<String>) {
-        if let Some(p) = self.cache.get_mut(profile) {
-            p.character_ids = characters;
+    pub fn new(client: Arc<dyn BungieClient>) -> Self {
+        Self {
+            client,
+            cache: HashMap::new(),
         }
     }
+
+    pub fn set_profile_info(&mut self, profile: &Profile, profile_info: ProfileInfo) {
+        self.cache.insert(profile.clone(), profile_info);
+    }
 }
 
 #[async_trait]
 impl Source<Profile, ProfileInfo> for ProfileInfoSource {
-    async fn get_value(profile: Profile) -> Result<ProfileInfo, ApiError> {
-        let res_val = make_request(BungieRequest::GetProfile {
-            membership_type: profile.account_platform,
-            membership_id: &profile.account_id,
-            component: 100,
-        })
-        .await
-        .map_err(|e| ApiError::ResponseError(e))?;
+    async fn get_value(&self, profile: Profile) -> Result<ProfileInfo, ApiError> {
+        let res_val = self
+            .client
+            .request(BungieRequest::GetProfile {
+                membership_type: profile.account_platform,
+                membership_id: &profile.account_id,
+                components: &[PROFILES_COMPONENT],
+            })
+            .await
+            .map_err(|e| ApiError::ResponseError(e))?;
 
         serde_json::from_value(res_val).map_err(|e| ApiError::ResponseDeserializeError(e))
     }
@@ -97,15 +123,31 @@ impl Source<Profile, ProfileInfo> for ProfileInfoSource {
     }
 }
 
-#[derive(Default)]
 pub struct ActivityInfoSource {
+    client: Arc<dyn BungieClient>,
     cache: HashMap<usize, ActivityInfo>,
 }
 
+impl ActivityInfoSource {
+    pub fn new(client: Arc<dyn BungieClient>) -> Self {
+        Self {
+            client,
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Only what was already fetched, without going to the API.
+    pub fn cached(&self, activity_hash: usize) -> Option<&ActivityInfo> {
+        self.cache.get(&activity_hash)
+    }
+}
+
 #[async_trait]
 impl Source<usize, ActivityInfo> for ActivityInfoSource {
-    async fn get_value(activity_hash: usize) -> Result<ActivityInfo, ApiError> {
-        let res_val = make_request(BungieRequest::GetDestinyActivityDefinition { activity_hash })
+    async fn get_value(&self, activity_hash: usize) -> Result<ActivityInfo, ApiError> {
+        let res_val = self
+            .client
+            .request(BungieRequest::GetDestinyActivityDefinition { activity_hash })
             .await
             .map_err(|e| ApiError::ResponseError(e))?;
 
@@ -117,92 +159,169 @@ impl Source<usize, ActivityInfo> for ActivityInfoSource {
     }
 }
 
-#[derive(Default)]
 pub struct Api {
+    client: Arc<dyn BungieClient>,
     pub profile_info_source: Mutex<ProfileInfoSource>,
     pub activity_info_source: Mutex<ActivityInfoSource>,
 }
 
+impl Default for Api {
+    fn default() -> Self {
+        Self::new(Arc::new(ReqwestClient::default()))
+    }
+}
+
 impl Api {
+    pub fn new(client: Arc<dyn BungieClient>) -> Self {
+        Self {
+            profile_info_source: Mutex::new(ProfileInfoSource::new(client.clone())),
+            activity_info_source: Mutex::new(ActivityInfoSource::new(client.clone())),
+            client,
+        }
+    }
+
     pub async fn search_profile(
+        &self,
         display_name: &String,
         display_name_code: usize,
     ) -> Result<Vec<BungieProfile>, ApiError> {
-        let res_val = make_request(BungieRequest::SearchDestinyPlayerByBungieName {
-            display_name: display_name,
-            display_name_code,
-        })
-        .await
-        .map_err(|e| ApiError::ResponseError(e))?;
+        let res_val = self
+            .client
+            .request(BungieRequest::SearchDestinyPlayerByBungieName {
+                display_name: display_name,
+                display_name_code,
+            })
+            .await
+            .map_err(|e| ApiError::ResponseError(e))?;
 
         serde_json::from_value(res_val).map_err(|e| ApiError::ResponseDeserializeError(e))
     }
 
-    pub async fn get_profile_activities(
+    pub async fn get_profile_snapshot(
+        &self,
         profile: &Profile,
-    ) -> Result<ProfileCurrentActivities, ApiError> {
-        let res_val = make_request(BungieRequest::GetProfile {
-            membership_type: profile.account_platform,
-            membership_id: &profile.account_id,
-            component: 204,
-        })
-        .await
-        .map_err(|e| ApiError::ResponseError(e))?;
+        components: &[usize],
+    ) -> Result<ProfileSnapshot, ApiError> {
+        let res_val = self
+            .client
+            .request(BungieRequest::GetProfile {
+                membership_type: profile.account_platform,
+                membership_id: &profile.account_id,
+                components,
+            })
+            .await
+            .map_err(|e| ApiError::ResponseError(e))?;
+
+        serde_json::from_value(res_val).map_err(|e| ApiError::ResponseDeserializeError(e))
+    }
+
+    pub async fn get_characters(&self, profile: &Profile) -> Result<Vec<CharacterInfo>, ApiError> {
+        let snapshot = self
+            .get_profile_snapshot(profile, &[CHARACTERS_COMPONENT])
+            .await?;
+
+        Ok(snapshot.characters.unwrap_or_default())
+    }
+
+    pub async fn get_global_alerts(&self) -> Result<Vec<GlobalAlert>, ApiError> {
+        let res_val = self
+            .client
+            .request(BungieRequest::GetGlobalAlerts)
+            .await
+            .map_err(|e| ApiError::ResponseError(e))?;
+
+        serde_json::from_value(res_val).map_err(|e| ApiError::ResponseDeserializeError(e))
+    }
+
+    pub async fn get_common_settings(&self) -> Result<CommonSettings, ApiError> {
+        let res_val = self
+            .client
+            .request(BungieRequest::GetCommonSettings)
+            .await
+            .map_err(|e| ApiError::ResponseError(e))?;
 
         serde_json::from_value(res_val).map_err(|e| ApiError::ResponseDeserializeError(e))
     }
 
     pub async fn get_activity_history(
+        &self,
         profile: &Profile,
         character_id: &String,
         page: usize,
     ) -> Result<CharacterActivityHistory, ApiError> {
-        let res_val = make_request(BungieRequest::GetActivityHistory {
-            membership_type: profile.account_platform,
-            membership_id: &profile.account_id,
-            character_id: character_id,
-            page,
-        })
-        .await
-        .map_err(|e| ApiError::ResponseError(e))?;
+        let res_val = self
+            .client
+            .request(BungieRequest::GetActivityHistory {
+                membership_type: profile.account_platform,
+                membership_id: &profile.account_id,
+                character_id: character_id,
+                page,
+            })
+            .await
+            .map_err(|e| ApiError::ResponseError(e))?;
 
-        serde_json::from_value(res_val).map_err(|e| ApiError::ResponseDeserializeError(e))
-    }
+        let history: CharacterActivityHistory =
+            serde_json::from_value(res_val).map_err(|e| ApiError::ResponseDeserializeError(e))?;
 
-    pub async fn get_character_classes(
-        profile: &Profile,
-    ) -> Result<HashMap<String, String>, ApiError> {
-        let res_val = make_request(BungieRequest::GetProfile {
-            membership_type: profile.account_platform,
-            membership_id: &profile.account_id,
-            component: 200,
-        })
-        .await
-        .map_err(|e| ApiError::ResponseError(e))?;
-
-        let classes = parse_character_classes(res_val)?;
-        Ok(classes)
+        if !history.malformed.is_empty() {
+            record_malformed_activities(&history.malformed);
+        }
+
+        Ok(history)
     }
 }
 
-fn parse_character_classes(value: serde_json::Value) -> Result<HashMap<String, String>, ApiError> {
-    let mut result = HashMap::new();
-
-    if let Some(characters) = value.get("characters").and_then(|c| c.get("data")) {
-        if let Some(char_map) = characters.as_object() {
-            for (character_id, character_data) in char_map {
-                if let Some(class_type) = character_data.get("classType").and_then(|c| c.as_u64()) {
-                    let class_name = match class_type {
-                        0 => "Titan",
-                        1 => "Hunter", 
-                        2 => "Warlock",
-                        _ => "Unknown",
-                    };
-                    result.insert(character_id.clone(), class_name.to_string());
-                }
+/// Keeps the latest malformed entries around for bug reports. The same pages
+/// get fetched over and over, so entries that are already there are skipped.
+fn record_malformed_activities(entries: &[MalformedActivity]) {
+    let write_result = (|| -> anyhow::Result<()> {
+        let path = data_path(MALFORMED_ACTIVITIES_FILE)?;
+
+        let mut recorded: Vec<MalformedActivity> = match read_to_string(&path) {
+            Ok(s) => s
+                .lines()
+                .filter_map(|l| serde_json::from_str(l).ok())
+                .collect(),
+            Err(e) if e.kind() == ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut changed = false;
+
+        for entry in entries {
+            if recorded.iter().any(|r| r.same_entry(entry)) {
+                continue;
             }
+
+            eprintln!("Skipping malformed activity history entry: {}", entry.error);
+            recorded.push(entry.clone());
+            changed = true;
         }
-    }
 
-    Ok(result)
+        if !changed {
+            return Ok(());
+        }
+
+        let excess = recorded.len().saturating_sub(MALFORMED_ACTIVITIES_LIMIT);
+        recorded.drain(..excess);
+
+        let mut contents = String::new();
+
+        for entry in recorded.iter() {
+            contents.push_str(&serde_json::to_string(entry)?);
+            contents.push('\n');
+        }
+
+        if let Some(dir) = path.parent() {
+            create_dir_all(dir)?;
+        }
+
+        write(path, contents)?;
+
+        Ok(())
+    })();
+
+    if let Err(e) = write_result {
+        eprintln!("Failed to record malformed activities: {}", e);
+    }
 }
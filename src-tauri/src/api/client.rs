@@ -0,0 +1,66 @@
+use std::{
+    env,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::Value;
+
+use super::{
+    fixtures::FixtureClient,
+    requests::{make_request, BungieRequest, BungieResponseError},
+};
+use crate::consts::{API_BASE_ENV, API_PATH, API_RECORD_ENV, API_REPLAY_ENV};
+
+pub fn client_from_env() -> anyhow::Result<Arc<dyn BungieClient>> {
+    if let Ok(dir) = env::var(API_REPLAY_ENV) {
+        return Ok(Arc::new(FixtureClient::from_dir(Path::new(&dir))?));
+    }
+
+    let base_url = env::var(API_BASE_ENV).unwrap_or_else(|_| API_PATH.to_string());
+    let record_dir = env::var(API_RECORD_ENV).ok().map(PathBuf::from);
+
+    Ok(Arc::new(ReqwestClient::new(base_url, record_dir)))
+}
+
+#[async_trait]
+pub trait BungieClient: Send + Sync {
+    async fn request(&self, req: BungieRequest<'_>) -> Result<Value, BungieResponseError>;
+}
+
+pub struct ReqwestClient {
+    client: Client,
+    base_url: String,
+    record_dir: Option<PathBuf>,
+}
+
+impl Default for ReqwestClient {
+    fn default() -> Self {
+        Self::new(API_PATH.to_string(), None)
+    }
+}
+
+impl ReqwestClient {
+    pub fn new(base_url: String, record_dir: Option<PathBuf>) -> Self {
+        Self {
+            client: Client::new(),
+            base_url,
+            record_dir,
+        }
+    }
+}
+
+#[async_trait]
+impl BungieClient for ReqwestClient {
+    async fn request(&self, req: BungieRequest<'_>) -> Result<Value, BungieResponseError> {
+        make_request(
+            &self.client,
+            &self.base_url,
+            &req,
+            self.record_dir.as_deref(),
+        )
+        .await
+    }
+}
@@ -0,0 +1,76 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use super::{
+    client::BungieClient,
+    requests::{parse_response, BungieRequest, BungieResponseError},
+};
+
+/// Answers requests with canned responses by path, and keeps the paths it was
+/// asked for.
+#[derive(Default)]
+pub struct FakeBungieClient {
+    responses: Mutex<HashMap<String, (u16, String)>>,
+    requests: Mutex<Vec<String>>,
+}
+
+impl FakeBungieClient {
+    pub fn respond(&self, path: &str, response: Value) {
+        let body = json!({
+            "ErrorCode": 1,
+            "Message": "Ok",
+            "ThrottleSeconds": 0,
+            "Response": response,
+        });
+
+        self.respond_raw(path, 200, body.to_string());
+    }
+
+    pub fn respond_error(
+        &self,
+        path: &str,
+        error_code: isize,
+        message: &str,
+        throttle_seconds: isize,
+    ) {
+        let body = json!({
+            "ErrorCode": error_code,
+            "Message": message,
+            "ThrottleSeconds": throttle_seconds,
+        });
+
+        self.respond_raw(path, 200, body.to_string());
+    }
+
+    pub fn respond_raw(&self, path: &str, status_code: u16, body: String) {
+        self.responses
+            .lock()
+            .unwrap()
+            .insert(path.to_string(), (status_code, body));
+    }
+
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl BungieClient for FakeBungieClient {
+    async fn request(&self, req: BungieRequest<'_>) -> Result<Value, BungieResponseError> {
+        let path = req.path();
+
+        self.requests.lock().unwrap().push(path.clone());
+
+        let response = self.responses.lock().unwrap().get(&path).cloned();
+
+        match response {
+            Some((status_code, body)) => parse_response(status_code, &body),
+            None => Err(BungieResponseError::NetworkError(anyhow!(
+                "No fake response for {path}"
+            ))),
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use std::{
+    collections::HashMap,
+    fs::{create_dir_all, read_dir, read_to_string},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{
+    client::BungieClient,
+    requests::{parse_response, BungieRequest, BungieResponseError},
+};
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Fixture {
+    pub method: String,
+    pub path: String,
+    pub body: Option<String>,
+    pub status_code: u16,
+    pub response: String,
+}
+
+impl Fixture {
+    pub fn new(req: &BungieRequest<'_>, status_code: u16, response: String) -> Self {
+        Self {
+            method: req.method().to_string(),
+            path: req.path(),
+            body: req.body(),
+            status_code,
+            response,
+        }
+    }
+
+    pub fn write(&self, dir: &Path) -> Result<()> {
+        create_dir_all(dir)?;
+
+        Ok(std::fs::write(
+            self.file_path(dir),
+            serde_json::to_string_pretty(self)?,
+        )?)
+    }
+
+    /// Identifies the request the fixture answers. Player searches all go to
+    /// the same path, so requests with a body are told apart by its hash.
+    pub fn key(&self) -> String {
+        request_key(&self.method, &self.path, self.body.as_deref())
+    }
+
+    pub fn read_all(dir: &Path) -> Result<Vec<Fixture>> {
+        let mut fixtures = Vec::new();
+
+        for entry in read_dir(dir)? {
+            let path = entry?.path();
+
+            if path.extension().map_or(true, |e| e != "json") {
+                continue;
+            }
+
+            fixtures.push(serde_json::from_str(&read_to_string(path)?)?);
+        }
+
+        Ok(fixtures)
+    }
+
+    fn file_path(&self, dir: &Path) -> PathBuf {
+        let name: String = self
+            .key()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+
+        dir.join(format!("{name}.json"))
+    }
+}
+
+/// FNV-1a, which unlike the hashers in std is guaranteed to give the same
+/// result in every build, so recorded file names stay valid.
+fn body_hash(body: &str) -> u64 {
+    body.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn request_key(method: &str, path: &str, body: Option<&str>) -> String {
+    match body {
+        Some(body) => format!("{method}{path}#{:016x}", body_hash(body)),
+        None => format!("{method}{path}"),
+    }
+}
+
+/// Replays recorded fixtures in place of the Bungie API.
+pub struct FixtureClient {
+    responses: HashMap<String, (u16, String)>,
+}
+
+impl FixtureClient {
+    pub fn from_dir(dir: &Path) -> Result<Self> {
+        let responses = Fixture::read_all(dir)?
+            .into_iter()
+            .map(|f| (f.key(), (f.status_code, f.response)))
+            .collect();
+
+        Ok(Self { responses })
+    }
+}
+
+#[async_trait]
+impl BungieClient for FixtureClient {
+    async fn request(&self, req: BungieRequest<'_>) -> Result<Value, BungieResponseError> {
+        let key = request_key(req.method().as_str(), &req.path(), req.body().as_deref());
+
+        match self.responses.get(&key) {
+            Some((status_code, body)) => parse_response(*status_code, body),
+            None => Err(BungieResponseError::NetworkError(anyhow!(
+                "No fixture for {key}"
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+    use crate::{
+        api::{responses::BungieProfile, Api, Source},
+        config::profiles::Profile,
+        consts::{DESTINY2_SYSTEM, PROFILE_SNAPSHOT_COMPONENTS},
+        history::{fetch_history, history_cutoff},
+    };
+
+    fn replay_api() -> Api {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/replay");
+
+        Api::new(Arc::new(FixtureClient::from_dir(&dir).unwrap()))
+    }
+
+    fn profile() -> Profile {
+        Profile {
+            account_platform: 3,
+            account_id: "4611686018467654321".to_string(),
+        }
+    }
+
+    fn search(display_name: &str, display_name_code: usize) -> Fixture {
+        let req = BungieRequest::SearchDestinyPlayerByBungieName {
+            display_name,
+            display_name_code,
+        };
+
+        Fixture::new(&req, 200, String::new())
+    }
+
+    #[test]
+    fn searches_get_their_own_files() {
+        let dir = Path::new("fixtures");
+        let first = search("Guardian", 1234);
+        let second = search("Guardian", 5678);
+
+        assert_eq!(first.path, second.path);
+        assert_ne!(first.key(), second.key());
+        assert_ne!(first.file_path(dir), second.file_path(dir));
+        assert_eq!(first.key(), search("Guardian", 1234).key());
+    }
+
+    #[tokio::test]
+    async fn replays_searches_by_body() {
+        let api = replay_api();
+
+        let first = api
+            .search_profile(&"Guardian".to_string(), 1234)
+            .await
+            .unwrap();
+        let second = api
+            .search_profile(&"Guardian".to_string(), 5678)
+            .await
+            .unwrap();
+
+        let membership_id = |profiles: &[BungieProfile]| {
+            serde_json::to_value(profiles).unwrap()[0]["membershipId"].clone()
+        };
+
+        assert_eq!(membership_id(&first), "4611686018467654321");
+        assert_eq!(membership_id(&second), "4611686018400000001");
+        assert!(api.search_profile(&"Nobody".to_string(), 1).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn replays_a_profile_and_its_history() {
+        let api = replay_api();
+        let profile = profile();
+        let now = Utc.with_ymd_and_hms(2026, 3, 10, 20, 0, 0).unwrap();
+
+        let snapshot = api
+            .get_profile_snapshot(&profile, PROFILE_SNAPSHOT_COMPONENTS)
+            .await
+            .unwrap();
+
+        let profile_info = snapshot.profile_info.as_ref().unwrap();
+        assert_eq!(profile_info.display_name, "Guardian");
+        assert_eq!(profile_info.display_tag, 1234);
+
+        let characters = snapshot.characters.as_ref().unwrap();
+        assert_eq!(characters[0].class_name, "Hunter");
+
+        let activities = snapshot
+            .current_activities
+            .as_ref()
+            .and_then(|a| a.activities.as_ref())
+            .unwrap();
+        assert_eq!(activities.len(), 1);
+
+        let history = fetch_history(&api, &profile, &snapshot, history_cutoff(now))
+            .await
+            .unwrap();
+
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].instance_id, "13700000001");
+        assert!(history[0].completed);
+        assert_eq!(history[0].activity_duration_seconds, 2712);
+        assert_eq!(history[0].character_class.as_deref(), Some("Hunter"));
+        assert!(!history[1].completed);
+        assert!(history[1].failed);
+    }
+
+    #[tokio::test]
+    async fn replays_definitions_and_status() {
+        let api = replay_api();
+
+        let info = api
+            .activity_info_source
+            .lock()
+            .await
+            .get(&3881495763)
+            .await
+            .unwrap();
+        assert_eq!(info.name, "Vault of Glass");
+        assert_eq!(info.activity_modes, [4, 2]);
+
+        let alerts = api.get_global_alerts().await.unwrap();
+        assert_eq!(
+            alerts[0].text,
+            "Destiny 2 servers will be offline for maintenance."
+        );
+
+        let settings = api.get_common_settings().await.unwrap();
+        assert!(settings.is_enabled(DESTINY2_SYSTEM));
+    }
+}
@@ -1,13 +1,20 @@
 use std::{
     error::Error,
     fmt::{Display, Formatter},
+    path::Path,
+    time::{Duration, Instant},
 };
 
+use itertools::Itertools;
 use reqwest::{Client, Method, RequestBuilder};
 use serde::Deserialize;
 use serde_json::{json, Value};
 
-use crate::consts::{API_KEY, API_PATH, USER_AGENT};
+use super::fixtures::Fixture;
+use crate::{
+    consts::{API_KEY, MAINTENANCE_BACKOFF, USER_AGENT},
+    metrics::{self, API_ERRORS, API_LATENCY, API_REQUESTS, API_THROTTLES},
+};
 
 pub enum BungieRequest<'a> {
     SearchDestinyPlayerByBungieName {
@@ -17,7 +24,7 @@ pub enum BungieRequest<'a> {
     GetProfile {
         membership_type: usize,
         membership_id: &'a str,
-        component: usize,
+        components: &'a [usize],
     },
     GetActivityHistory {
         membership_type: usize,
@@ -28,6 +35,8 @@ pub enum BungieRequest<'a> {
     GetDestinyActivityDefinition {
         activity_hash: usize,
     },
+    GetGlobalAlerts,
+    GetCommonSettings,
 }
 
 #[derive(Deserialize)]
@@ -39,6 +48,30 @@ struct BungieResponseStatus {
     response: Option<Value>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BungieErrorKind {
+    SystemDisabled,
+    Throttled,
+    PrivacyRestricted,
+    AccountNotFound,
+    InvalidApiKey,
+    Other,
+}
+
+impl BungieErrorKind {
+    fn from_code(error_code: isize, throttle_seconds: isize) -> Self {
+        match error_code {
+            5 => BungieErrorKind::SystemDisabled,
+            36..=39 | 51..=54 | 1672 => BungieErrorKind::Throttled,
+            1665 => BungieErrorKind::PrivacyRestricted,
+            1601 => BungieErrorKind::AccountNotFound,
+            2101 | 2102 => BungieErrorKind::InvalidApiKey,
+            _ if throttle_seconds > 0 => BungieErrorKind::Throttled,
+            _ => BungieErrorKind::Other,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum BungieResponseError {
     DeserializeError {
@@ -46,6 +79,7 @@ pub enum BungieResponseError {
         status_code: u16,
     },
     BungieError {
+        kind: BungieErrorKind,
         message: String,
         error_code: isize,
         throttle_seconds: isize,
@@ -54,16 +88,89 @@ pub enum BungieResponseError {
     NetworkError(anyhow::Error),
 }
 
+impl BungieResponseError {
+    /// A short label for the error, used in metrics.
+    pub fn label(&self) -> &'static str {
+        match self {
+            BungieResponseError::DeserializeError { .. } => "deserialize",
+            BungieResponseError::BungieError { kind, .. } => match kind {
+                BungieErrorKind::SystemDisabled => "system_disabled",
+                BungieErrorKind::Throttled => "throttled",
+                BungieErrorKind::PrivacyRestricted => "privacy_restricted",
+                BungieErrorKind::AccountNotFound => "account_not_found",
+                BungieErrorKind::InvalidApiKey => "invalid_api_key",
+                BungieErrorKind::Other => "other",
+            },
+            BungieResponseError::ResponseMissing => "response_missing",
+            BungieResponseError::NetworkError(_) => "network",
+        }
+    }
+
+    pub fn kind(&self) -> Option<BungieErrorKind> {
+        match self {
+            BungieResponseError::BungieError { kind, .. } => Some(*kind),
+            _ => None,
+        }
+    }
+
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            BungieResponseError::BungieError { kind, .. } => !matches!(
+                kind,
+                BungieErrorKind::PrivacyRestricted
+                    | BungieErrorKind::AccountNotFound
+                    | BungieErrorKind::InvalidApiKey
+            ),
+            BungieResponseError::ResponseMissing => false,
+            BungieResponseError::DeserializeError { .. } | BungieResponseError::NetworkError(_) => {
+                true
+            }
+        }
+    }
+
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            BungieResponseError::BungieError {
+                kind: BungieErrorKind::SystemDisabled,
+                ..
+            } => Some(MAINTENANCE_BACKOFF),
+            BungieResponseError::BungieError {
+                kind: BungieErrorKind::Throttled,
+                throttle_seconds,
+                ..
+            } => Some(Duration::from_secs((*throttle_seconds).max(1) as u64)),
+            _ => None,
+        }
+    }
+}
+
 impl Display for BungieResponseError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             BungieResponseError::DeserializeError { err, status_code } => {
                 write!(f, "Failed to parse response (code {status_code}): {err}")
             }
+            BungieResponseError::BungieError {
+                kind: BungieErrorKind::SystemDisabled,
+                ..
+            } => f.write_str("Bungie API is down for maintenance"),
+            BungieResponseError::BungieError {
+                kind: BungieErrorKind::PrivacyRestricted,
+                ..
+            } => f.write_str("Profile is private"),
+            BungieResponseError::BungieError {
+                kind: BungieErrorKind::AccountNotFound,
+                ..
+            } => f.write_str("Bungie account not found"),
+            BungieResponseError::BungieError {
+                kind: BungieErrorKind::InvalidApiKey,
+                ..
+            } => f.write_str("Invalid Bungie API key"),
             BungieResponseError::BungieError {
                 message,
                 error_code,
                 throttle_seconds,
+                ..
             } => {
                 if *throttle_seconds > 0 {
                     write!(
@@ -82,29 +189,142 @@ impl Display for BungieResponseError {
 
 impl Error for BungieResponseError {}
 
-fn api_request(path: &str, method: Method) -> RequestBuilder {
-    Client::new()
-        .request(method, format!("{API_PATH}{path}"))
+impl BungieRequest<'_> {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BungieRequest::SearchDestinyPlayerByBungieName { .. } => {
+                "SearchDestinyPlayerByBungieName"
+            }
+            BungieRequest::GetProfile { .. } => "GetProfile",
+            BungieRequest::GetActivityHistory { .. } => "GetActivityHistory",
+            BungieRequest::GetDestinyActivityDefinition { .. } => "GetDestinyActivityDefinition",
+            BungieRequest::GetGlobalAlerts => "GetGlobalAlerts",
+            BungieRequest::GetCommonSettings => "GetCommonSettings",
+        }
+    }
+
+    pub fn method(&self) -> Method {
+        match self {
+            BungieRequest::SearchDestinyPlayerByBungieName { .. } => Method::POST,
+            _ => Method::GET,
+        }
+    }
+
+    pub fn path(&self) -> String {
+        match self {
+            BungieRequest::SearchDestinyPlayerByBungieName { .. } => {
+                "/Destiny2/SearchDestinyPlayerByBungieName/All".to_string()
+            }
+            BungieRequest::GetProfile {
+                membership_type,
+                membership_id,
+                components,
+            } => {
+                let components = components.iter().join(",");
+                format!(
+                    "/Destiny2/{membership_type}/Profile/{membership_id}?components={components}"
+                )
+            }
+            BungieRequest::GetActivityHistory {
+                membership_type,
+                membership_id,
+                character_id,
+                page,
+            } => {
+                format!("/Destiny2/{membership_type}/Account/{membership_id}/Character/{character_id}/Stats/Activities?mode=7&count=25&page={page}")
+            }
+            BungieRequest::GetDestinyActivityDefinition { activity_hash } => {
+                format!("/Destiny2/Manifest/DestinyActivityDefinition/{activity_hash}")
+            }
+            BungieRequest::GetGlobalAlerts => "/GlobalAlerts/".to_string(),
+            BungieRequest::GetCommonSettings => "/Settings/".to_string(),
+        }
+    }
+
+    pub fn body(&self) -> Option<String> {
+        match self {
+            BungieRequest::SearchDestinyPlayerByBungieName {
+                display_name,
+                display_name_code,
+            } => Some(
+                json!({"displayName": display_name, "displayNameCode": display_name_code})
+                    .to_string(),
+            ),
+            _ => None,
+        }
+    }
+}
+
+fn api_request(client: &Client, base_url: &str, req: &BungieRequest<'_>) -> RequestBuilder {
+    let builder = client
+        .request(req.method(), format!("{base_url}{}", req.path()))
         .header("X-API-Key", API_KEY)
-        .header("User-Agent", USER_AGENT)
+        .header("User-Agent", USER_AGENT);
+
+    match req.body() {
+        Some(body) => builder.body(body),
+        None => builder,
+    }
+}
+
+pub async fn make_request(
+    client: &Client,
+    base_url: &str,
+    req: &BungieRequest<'_>,
+    record_dir: Option<&Path>,
+) -> Result<Value, BungieResponseError> {
+    let started = Instant::now();
+    let result = fetch_and_parse(client, base_url, req, record_dir).await;
+
+    record_metrics(req, started.elapsed(), &result);
+
+    result
 }
 
-pub async fn make_request(req: BungieRequest<'_>) -> Result<Value, BungieResponseError> {
-    let builder = match req {
-        BungieRequest::SearchDestinyPlayerByBungieName { display_name, display_name_code } => api_request(
-            "/Destiny2/SearchDestinyPlayerByBungieName/All",
-            Method::POST,
-        ).body(json!({"displayName": display_name, "displayNameCode": display_name_code}).to_string()),
-        BungieRequest::GetProfile { membership_type, membership_id, component } => {
-            api_request(&format!("/Destiny2/{membership_type}/Profile/{membership_id}?components={component}"), Method::GET)
+async fn fetch_and_parse(
+    client: &Client,
+    base_url: &str,
+    req: &BungieRequest<'_>,
+    record_dir: Option<&Path>,
+) -> Result<Value, BungieResponseError> {
+    let (status_code, text) = fetch_response(client, base_url, req).await?;
+
+    if let Some(dir) = record_dir {
+        let fixture = Fixture::new(req, status_code, text.clone());
+
+        if let Err(e) = fixture.write(dir) {
+            eprintln!("Failed to record fixture for {}: {}", req.path(), e);
         }
-        BungieRequest::GetActivityHistory { membership_type, membership_id, character_id, page } => {
-            api_request(&format!("/Destiny2/{membership_type}/Account/{membership_id}/Character/{character_id}/Stats/Activities?mode=7&count=25&page={page}"), Method::GET)
+    }
+
+    parse_response(status_code, &text)
+}
+
+fn record_metrics(
+    req: &BungieRequest<'_>,
+    elapsed: Duration,
+    result: &Result<Value, BungieResponseError>,
+) {
+    let request = req.name();
+
+    metrics::increment(API_REQUESTS, &[("request", request)]);
+    metrics::observe(API_LATENCY, &[("request", request)], elapsed);
+
+    if let Err(e) = result {
+        metrics::increment(API_ERRORS, &[("request", request), ("kind", e.label())]);
+
+        if e.kind() == Some(BungieErrorKind::Throttled) {
+            metrics::increment(API_THROTTLES, &[("request", request)]);
         }
-        BungieRequest::GetDestinyActivityDefinition { activity_hash } => api_request(&format!("/Destiny2/Manifest/DestinyActivityDefinition/{activity_hash}"), Method::GET),
-    };
+    }
+}
 
-    let resp = builder
+async fn fetch_response(
+    client: &Client,
+    base_url: &str,
+    req: &BungieRequest<'_>,
+) -> Result<(u16, String), BungieResponseError> {
+    let resp = api_request(client, base_url, req)
         .send()
         .await
         .map_err(|e| BungieResponseError::NetworkError(e.into()))?;
@@ -116,7 +336,11 @@ pub async fn make_request(req: BungieRequest<'_>) -> Result<Value, BungieRespons
         .await
         .map_err(|e| BungieResponseError::NetworkError(e.into()))?;
 
-    let status: BungieResponseStatus = match serde_json::from_str(&text) {
+    Ok((status_code, text))
+}
+
+pub fn parse_response(status_code: u16, text: &str) -> Result<Value, BungieResponseError> {
+    let status: BungieResponseStatus = match serde_json::from_str(text) {
         Ok(s) => s,
         Err(e) => {
             return Err(BungieResponseError::DeserializeError {
@@ -129,6 +353,7 @@ pub async fn make_request(req: BungieRequest<'_>) -> Result<Value, BungieRespons
 
     if status.error_code != 1 {
         return Err(BungieResponseError::BungieError {
+            kind: BungieErrorKind::from_code(status.error_code, status.throttle_seconds),
             message: status.message,
             error_code: status.error_code,
             throttle_seconds: status.throttle_seconds,
@@ -1,9 +1,12 @@
 use std::{cmp::Ordering, collections::HashMap};
 
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Deserializer, Serialize};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize};
+use serde_json::Value;
 
-use crate::consts::{DUNGEON_ACTIVITY_HASH, DUNGEON_ACTIVITY_MODE, RAID_ACTIVITY_HASH, RAID_ACTIVITY_MODE};
+use crate::consts::{
+    DUNGEON_ACTIVITY_HASH, DUNGEON_ACTIVITY_MODE, RAID_ACTIVITY_HASH, RAID_ACTIVITY_MODE,
+};
 
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -141,10 +144,203 @@ impl<'de> Deserialize<'de> for ProfileCurrentActivities {
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
+pub struct CharacterInfo {
+    pub character_id: String,
+    pub class_name: String,
+    pub race_name: String,
+    pub light: usize,
+    pub emblem_path: Option<String>,
+    pub date_last_played: DateTime<Utc>,
+}
+
+impl<'de> Deserialize<'de> for CharacterInfo {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct _Character {
+            character_id: String,
+            class_type: usize,
+            race_type: usize,
+            light: usize,
+            emblem_path: Option<String>,
+            date_last_played: DateTime<Utc>,
+        }
+
+        let character = _Character::deserialize(deserializer)?;
+
+        let class_name = match character.class_type {
+            0 => "Titan",
+            1 => "Hunter",
+            2 => "Warlock",
+            _ => "Unknown",
+        };
+
+        let race_name = match character.race_type {
+            0 => "Human",
+            1 => "Awoken",
+            2 => "Exo",
+            _ => "Unknown",
+        };
+
+        Ok(Self {
+            character_id: character.character_id,
+            class_name: class_name.to_string(),
+            race_name: race_name.to_string(),
+            light: character.light,
+            emblem_path: character.emblem_path,
+            date_last_played: character.date_last_played,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct ProfileSnapshot {
+    pub profile_info: Option<ProfileInfo>,
+    pub characters: Option<Vec<CharacterInfo>>,
+    pub current_activities: Option<ProfileCurrentActivities>,
+}
+
+impl ProfileSnapshot {
+    pub fn character_classes(&self) -> HashMap<String, String> {
+        self.characters
+            .iter()
+            .flatten()
+            .map(|c| (c.character_id.clone(), c.class_name.clone()))
+            .collect()
+    }
+}
+
+impl<'de> Deserialize<'de> for ProfileSnapshot {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct _Profile {
+            characters: _Characters,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct _Characters {
+            data: Option<HashMap<String, CharacterInfo>>,
+        }
+
+        let value = Value::deserialize(deserializer)?;
+
+        let profile_info = match value.get("profile") {
+            Some(_) => Some(ProfileInfo::deserialize(&value).map_err(D::Error::custom)?),
+            None => None,
+        };
+
+        let characters = match value.get("characters") {
+            Some(_) => _Profile::deserialize(&value)
+                .map_err(D::Error::custom)?
+                .characters
+                .data
+                .map(|d| {
+                    let mut characters: Vec<CharacterInfo> = d.into_values().collect();
+                    characters.sort_by(|a, b| b.date_last_played.cmp(&a.date_last_played));
+                    characters
+                }),
+            None => None,
+        };
+
+        let current_activities = match value.get("characterActivities") {
+            Some(_) => {
+                Some(ProfileCurrentActivities::deserialize(&value).map_err(D::Error::custom)?)
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            profile_info,
+            characters,
+            current_activities,
+        })
+    }
+}
+
 pub struct CharacterActivityHistory {
     pub activities: Option<Vec<CompletedActivity>>,
+    pub malformed: Vec<MalformedActivity>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MalformedActivity {
+    pub error: String,
+    pub payload: Value,
+}
+
+impl MalformedActivity {
+    pub fn instance_id(&self) -> Option<&str> {
+        self.payload
+            .pointer("/activityDetails/instanceId")
+            .and_then(Value::as_str)
+    }
+
+    /// Entries without an instance id can only be told apart by their payload.
+    pub fn same_entry(&self, other: &Self) -> bool {
+        match (self.instance_id(), other.instance_id()) {
+            (Some(a), Some(b)) => a == b,
+            _ => self.payload == other.payload,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for CharacterActivityHistory {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct _History {
+            activities: Option<Vec<Value>>,
+        }
+
+        let history = _History::deserialize(deserializer)?;
+
+        let mut malformed = Vec::new();
+
+        let activities = history.activities.map(|a| {
+            a.into_iter()
+                .filter_map(|payload| match CompletedActivity::deserialize(&payload) {
+                    Ok(activity) => Some(activity),
+                    Err(e) => {
+                        malformed.push(MalformedActivity {
+                            error: e.to_string(),
+                            payload,
+                        });
+                        None
+                    }
+                })
+                .collect()
+        });
+
+        Ok(Self {
+            activities,
+            malformed,
+        })
+    }
+}
+
+pub fn format_duration(seconds: usize) -> String {
+    let hours = seconds / 3600;
+    let minutes = (seconds % 3600) / 60;
+
+    if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else {
+        format!("{minutes}m {}s", seconds % 60)
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Serialize, Clone)]
@@ -155,8 +351,13 @@ pub struct CompletedActivity {
     pub activity_hash: usize,
     pub modes: Vec<usize>,
     pub completed: bool,
+    /// The character stayed until the end, but the activity ended without
+    /// being completed. Leaving early is neither completed nor failed.
+    pub failed: bool,
     pub activity_duration: String,
     pub activity_duration_seconds: usize,
+    /// Seconds into the instance the character joined, 0 for a full run.
+    pub start_seconds: usize,
     pub character_class: Option<String>,
 }
 
@@ -190,15 +391,18 @@ impl<'de> Deserialize<'de> for CompletedActivity {
         struct _ActivityDetails {
             instance_id: String,
             director_activity_hash: usize,
+            #[serde(default)]
             modes: Vec<usize>,
         }
 
         #[derive(Deserialize)]
         #[serde(rename_all = "camelCase")]
         struct _Values {
-            completion_reason: _Value,
-            completed: _Value,
+            completion_reason: Option<_Value>,
+            completed: Option<_Value>,
+            // Without it every entry would count as a 0s clear.
             activity_duration_seconds: _Value,
+            start_seconds: Option<_Value>,
         }
 
         #[derive(Deserialize)]
@@ -211,24 +415,39 @@ impl<'de> Deserialize<'de> for CompletedActivity {
         #[serde(rename_all = "camelCase")]
         struct _BasicValue {
             value: f32,
-            display_value: String,
+            display_value: Option<String>,
         }
 
         let activity = _Activity::deserialize(deserializer)?;
+
+        let completed = activity
+            .values
+            .completed
+            .map_or(false, |v| v.basic.value == 1.0);
+        let completion_reason = activity
+            .values
+            .completion_reason
+            .map_or(0.0, |v| v.basic.value);
+
+        let duration = activity.values.activity_duration_seconds.basic;
+        let activity_duration_seconds = duration.value as usize;
+        let activity_duration = duration
+            .display_value
+            .unwrap_or_else(|| format_duration(activity_duration_seconds));
+
         Ok(Self {
             period: activity.period,
             instance_id: activity.activity_details.instance_id,
             activity_hash: activity.activity_details.director_activity_hash,
             modes: activity.activity_details.modes,
-            completed: activity.values.completed.basic.value == 1.0
-                && activity.values.completion_reason.basic.value == 0.0,
-            activity_duration: activity
+            completed: completed && completion_reason == 0.0,
+            failed: completed && completion_reason != 0.0,
+            activity_duration,
+            activity_duration_seconds,
+            start_seconds: activity
                 .values
-                .activity_duration_seconds
-                .basic
-                .display_value,
-            activity_duration_seconds: activity.values.activity_duration_seconds.basic.value
-                as usize,
+                .start_seconds
+                .map_or(0, |v| v.basic.value as usize),
             character_class: None,
         })
     }
@@ -287,3 +506,120 @@ impl<'de> Deserialize<'de> for ActivityInfo {
         })
     }
 }
+
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct GlobalAlert {
+    pub key: String,
+    pub html: String,
+    /// The alert with its markup stripped, which is what the windows show.
+    pub text: String,
+    pub link: Option<String>,
+    pub level: usize,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl<'de> Deserialize<'de> for GlobalAlert {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct _Alert {
+            alert_key: String,
+            alert_html: String,
+            alert_link: Option<String>,
+            alert_level: usize,
+            alert_timestamp: DateTime<Utc>,
+        }
+
+        let alert = _Alert::deserialize(deserializer)?;
+        Ok(Self {
+            key: alert.alert_key,
+            text: html_to_text(&alert.alert_html),
+            html: alert.alert_html,
+            link: alert.alert_link,
+            level: alert.alert_level,
+            timestamp: alert.alert_timestamp,
+        })
+    }
+}
+
+/// Drops the tags and decodes the few entities Bungie uses in alerts.
+fn html_to_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut tag: Option<String> = None;
+
+    for c in html.chars() {
+        match (&mut tag, c) {
+            (None, '<') => tag = Some(String::new()),
+            (Some(name), '>') => {
+                let name = name
+                    .trim_matches('/')
+                    .split_whitespace()
+                    .next()
+                    .unwrap_or("")
+                    .to_lowercase();
+
+                // Keeps the words around line breaks apart.
+                if matches!(name.as_str(), "br" | "p" | "div" | "li") {
+                    text.push(' ');
+                }
+
+                tag = None;
+            }
+            (Some(name), c) => name.push(c),
+            (None, c) => text.push(c),
+        }
+    }
+
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[derive(Debug, Clone)]
+pub struct CommonSettings {
+    pub systems: HashMap<String, bool>,
+}
+
+impl CommonSettings {
+    pub fn is_enabled(&self, system: &str) -> bool {
+        self.systems.get(system).copied().unwrap_or(true)
+    }
+}
+
+impl<'de> Deserialize<'de> for CommonSettings {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct _Settings {
+            systems: HashMap<String, _System>,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct _System {
+            enabled: bool,
+        }
+
+        let settings = _Settings::deserialize(deserializer)?;
+        Ok(Self {
+            systems: settings
+                .systems
+                .into_iter()
+                .map(|(name, system)| (name, system.enabled))
+                .collect(),
+        })
+    }
+}
@@ -0,0 +1,533 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{create_dir_all, read_to_string, write},
+    io::ErrorKind,
+};
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    api::responses::{format_duration, CompletedActivity},
+    config::{data_path, profiles::Profile},
+    consts::{ATTEMPTS_FILE, ATTEMPT_HISTORY_GRACE_MINUTES, ATTEMPT_MATCH_SLACK_SECS},
+    filter::HistoryFilter,
+    history::{history_cutoff, is_tracked},
+    stats::consolidate,
+};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum AttemptOutcome {
+    Cleared,
+    Failed,
+    Abandoned,
+    /// Seen in the current activity, but never showed up in the history,
+    /// like after a disconnect.
+    Unknown,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Attempt {
+    pub activity_hash: usize,
+    pub instance_id: Option<String>,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub duration_seconds: usize,
+    pub outcome: AttemptOutcome,
+}
+
+/// A tracked activity seen in the current activity of the profile, from the
+/// moment it started until the profile moved on.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ObservedAttempt {
+    pub activity_hash: usize,
+    pub modes: Vec<usize>,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl ObservedAttempt {
+    /// Stands in for the history entry the attempt never got, so it can go
+    /// through the same filters.
+    fn to_completed_activity(&self) -> CompletedActivity {
+        let seconds = (self.end - self.start).num_seconds().max(0) as usize;
+
+        CompletedActivity {
+            period: self.start,
+            instance_id: String::new(),
+            activity_hash: self.activity_hash,
+            modes: self.modes.clone(),
+            completed: false,
+            failed: false,
+            activity_duration: format_duration(seconds),
+            activity_duration_seconds: seconds,
+            start_seconds: 0,
+            character_class: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct AttemptLog {
+    profiles: HashMap<String, Vec<ObservedAttempt>>,
+}
+
+impl AttemptLog {
+    fn load() -> Self {
+        let read = || -> Result<Self> {
+            match read_to_string(data_path(ATTEMPTS_FILE)?) {
+                Ok(s) => Ok(serde_json::from_str(&s)?),
+                Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+                Err(e) => Err(e.into()),
+            }
+        };
+
+        read().unwrap_or_else(|e| {
+            eprintln!("Failed to read attempts: {}", e);
+            Self::default()
+        })
+    }
+
+    fn save(&self) -> Result<()> {
+        let path = data_path(ATTEMPTS_FILE)?;
+
+        if let Some(dir) = path.parent() {
+            create_dir_all(dir)?;
+        }
+
+        write(path, serde_json::to_string(self)?)?;
+
+        Ok(())
+    }
+}
+
+/// Follows the current activity of the profile and logs every tracked
+/// activity it goes through. The attempt in progress is only kept in memory,
+/// since there is no telling when it ended if groundsub closes first.
+#[derive(Default)]
+pub struct AttemptTracker {
+    log: AttemptLog,
+    open: Option<(String, ObservedAttempt)>,
+}
+
+impl AttemptTracker {
+    pub fn load() -> Self {
+        Self {
+            log: AttemptLog::load(),
+            open: None,
+        }
+    }
+
+    pub fn save(&self) {
+        if let Err(e) = self.log.save() {
+            eprintln!("Failed to save attempts: {}", e);
+        }
+    }
+
+    /// Feeds the current activity, as its hash, activity modes and start time,
+    /// or `None` when the profile isn't in any activity. Returns whether an
+    /// attempt ended and was added to the log, which then needs saving.
+    pub fn observe(
+        &mut self,
+        profile: &Profile,
+        current: Option<(usize, &[usize], DateTime<Utc>)>,
+        now: DateTime<Utc>,
+    ) -> bool {
+        let key = profile.key();
+        let current = current.filter(|(hash, modes, _)| is_tracked(*hash, modes));
+
+        if let (Some((open_key, open)), Some((hash, _, start))) = (&self.open, current) {
+            if *open_key == key && open.activity_hash == hash && open.start == start {
+                return false;
+            }
+        }
+
+        let closed = self.open.take();
+        let logged = closed.is_some();
+
+        if let Some((open_key, mut open)) = closed {
+            // A new activity starting is the end of the last one, going to
+            // orbit or closing the game is only noticed on the next poll.
+            open.end = match current {
+                Some((_, _, start)) if start > open.start => start.min(now),
+                _ => now,
+            };
+
+            let cutoff = history_cutoff(now);
+            let attempts = self.log.profiles.entry(open_key).or_default();
+            attempts.retain(|a| a.end >= cutoff);
+            attempts.push(open);
+        }
+
+        self.open = current.map(|(hash, modes, start)| {
+            (
+                key,
+                ObservedAttempt {
+                    activity_hash: hash,
+                    modes: modes.to_vec(),
+                    start,
+                    end: start,
+                },
+            )
+        });
+
+        logged
+    }
+
+    pub fn observed(&self, profile: &Profile) -> &[ObservedAttempt] {
+        self.log
+            .profiles
+            .get(&profile.key())
+            .map(|a| a.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AttemptStats {
+    pub attempts: usize,
+    pub clears: usize,
+    pub failed: usize,
+    pub abandoned: usize,
+    pub unknown: usize,
+    pub attempts_per_clear: Option<f64>,
+    pub unfinished_seconds: usize,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityAttempts {
+    pub activity_hash: usize,
+    #[serde(flatten)]
+    pub stats: AttemptStats,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AttemptReport {
+    #[serde(flatten)]
+    pub overall: AttemptStats,
+    pub activities: Vec<ActivityAttempts>,
+    pub attempts: Vec<Attempt>,
+}
+
+fn history_attempt(activity: &CompletedActivity) -> Attempt {
+    let outcome = if activity.completed {
+        AttemptOutcome::Cleared
+    } else if activity.failed {
+        AttemptOutcome::Failed
+    } else {
+        AttemptOutcome::Abandoned
+    };
+
+    Attempt {
+        activity_hash: activity.activity_hash,
+        instance_id: Some(activity.instance_id.clone()),
+        start: activity.period,
+        end: activity
+            .period
+            .checked_add_signed(Duration::seconds(activity.activity_duration_seconds as i64))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC),
+        duration_seconds: activity.activity_duration_seconds,
+        outcome,
+    }
+}
+
+fn attempt_stats(attempts: &[&Attempt]) -> AttemptStats {
+    let count = |outcome| attempts.iter().filter(|a| a.outcome == outcome).count();
+    let clears = count(AttemptOutcome::Cleared);
+
+    AttemptStats {
+        attempts: attempts.len(),
+        clears,
+        failed: count(AttemptOutcome::Failed),
+        abandoned: count(AttemptOutcome::Abandoned),
+        unknown: count(AttemptOutcome::Unknown),
+        attempts_per_clear: (clears > 0).then(|| attempts.len() as f64 / clears as f64),
+        unfinished_seconds: attempts
+            .iter()
+            .filter(|a| a.outcome != AttemptOutcome::Cleared)
+            .map(|a| a.duration_seconds)
+            .sum(),
+    }
+}
+
+/// Every attempt in the history, plus the observed ones the history has no
+/// entry for. Observed attempts get some time to show up in the history
+/// before they count as unknown. Returns the latest attempt first.
+pub fn attempt_report(
+    history: &[CompletedActivity],
+    observed: &[ObservedAttempt],
+    filter: &HistoryFilter,
+    now: DateTime<Utc>,
+) -> AttemptReport {
+    let mut attempts: Vec<Attempt> = consolidate(history.iter().filter(|a| filter.matches(a)))
+        .into_iter()
+        .map(history_attempt)
+        .collect();
+
+    let slack = Duration::seconds(ATTEMPT_MATCH_SLACK_SECS);
+    let grace = Duration::minutes(ATTEMPT_HISTORY_GRACE_MINUTES);
+
+    // Matched by time alone, since the history and the current activity
+    // don't always agree on the activity hash.
+    let in_history = |o: &ObservedAttempt| {
+        history.iter().any(|a| {
+            let end = a
+                .period
+                .checked_add_signed(Duration::seconds(a.activity_duration_seconds as i64))
+                .unwrap_or(DateTime::<Utc>::MAX_UTC);
+            a.period <= o.end + slack && end + slack >= o.start
+        })
+    };
+
+    for activity in observed
+        .iter()
+        .filter(|o| now - o.end > grace && !in_history(*o))
+        .map(|o| o.to_completed_activity())
+        .filter(|a| filter.matches(a))
+    {
+        let mut attempt = history_attempt(&activity);
+        attempt.instance_id = None;
+        attempt.outcome = AttemptOutcome::Unknown;
+        attempts.push(attempt);
+    }
+
+    attempts.sort_by(|a, b| b.start.cmp(&a.start));
+
+    let mut per_activity: BTreeMap<usize, Vec<&Attempt>> = BTreeMap::new();
+
+    for attempt in attempts.iter() {
+        per_activity
+            .entry(attempt.activity_hash)
+            .or_default()
+            .push(attempt);
+    }
+
+    let mut activities: Vec<ActivityAttempts> = per_activity
+        .into_iter()
+        .map(|(activity_hash, attempts)| ActivityAttempts {
+            activity_hash,
+            stats: attempt_stats(&attempts),
+        })
+        .collect();
+
+    activities.sort_by(|a, b| b.stats.attempts.cmp(&a.stats.attempts));
+
+    AttemptReport {
+        overall: attempt_stats(&attempts.iter().collect::<Vec<_>>()),
+        activities,
+        attempts,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::consts::{
+        DUNGEON_ACTIVITY_MODE, EXCLUDED_ACTIVITY_HASHES, RAID_ACTIVITY_MODE, STORY_ACTIVITY_MODE,
+    };
+
+    const RAID: usize = 1374392663;
+    const DUNGEON: usize = 2032534090;
+
+    fn at(hour: u32, minute: u32, second: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 12, hour, minute, second)
+            .unwrap()
+    }
+
+    fn profile() -> Profile {
+        Profile {
+            account_platform: 3,
+            account_id: "4611686018467654321".to_string(),
+        }
+    }
+
+    fn activity(
+        instance_id: &str,
+        period: DateTime<Utc>,
+        seconds: usize,
+        completed: bool,
+        failed: bool,
+    ) -> CompletedActivity {
+        CompletedActivity {
+            period,
+            instance_id: instance_id.to_string(),
+            activity_hash: RAID,
+            modes: vec![RAID_ACTIVITY_MODE],
+            completed,
+            failed,
+            activity_duration: format_duration(seconds),
+            activity_duration_seconds: seconds,
+            start_seconds: 0,
+            character_class: None,
+        }
+    }
+
+    fn observed(start: DateTime<Utc>, end: DateTime<Utc>) -> ObservedAttempt {
+        ObservedAttempt {
+            activity_hash: RAID,
+            modes: vec![RAID_ACTIVITY_MODE],
+            start,
+            end,
+        }
+    }
+
+    #[test]
+    fn closes_an_attempt_when_the_next_activity_starts() {
+        let mut tracker = AttemptTracker::default();
+        let raid: &[usize] = &[RAID_ACTIVITY_MODE];
+        let dungeon: &[usize] = &[DUNGEON_ACTIVITY_MODE];
+
+        assert!(!tracker.observe(&profile(), Some((RAID, raid, at(18, 0, 0))), at(18, 1, 0)));
+        assert!(!tracker.observe(&profile(), Some((RAID, raid, at(18, 0, 0))), at(18, 20, 0)));
+        assert!(tracker.observe(
+            &profile(),
+            Some((DUNGEON, dungeon, at(18, 40, 0))),
+            at(18, 42, 0)
+        ));
+
+        let attempts = tracker.observed(&profile());
+        assert_eq!(attempts.len(), 1);
+        assert_eq!(attempts[0].activity_hash, RAID);
+        assert_eq!(attempts[0].start, at(18, 0, 0));
+        assert_eq!(attempts[0].end, at(18, 40, 0));
+    }
+
+    #[test]
+    fn closes_an_attempt_on_the_poll_that_sees_orbit() {
+        let mut tracker = AttemptTracker::default();
+        let raid: &[usize] = &[RAID_ACTIVITY_MODE];
+
+        tracker.observe(&profile(), Some((RAID, raid, at(18, 0, 0))), at(18, 1, 0));
+        assert!(tracker.observe(&profile(), None, at(18, 45, 0)));
+        assert!(!tracker.observe(&profile(), None, at(18, 46, 0)));
+
+        let attempts = tracker.observed(&profile());
+        assert_eq!(attempts.len(), 1);
+        assert_eq!(attempts[0].end, at(18, 45, 0));
+    }
+
+    #[test]
+    fn ignores_untracked_activities() {
+        let mut tracker = AttemptTracker::default();
+        let story: &[usize] = &[STORY_ACTIVITY_MODE];
+        let shooting_range = EXCLUDED_ACTIVITY_HASHES[0];
+
+        tracker.observe(
+            &profile(),
+            Some((shooting_range, story, at(18, 0, 0))),
+            at(18, 1, 0),
+        );
+        assert!(!tracker.observe(&profile(), None, at(18, 30, 0)));
+        assert!(tracker.observed(&profile()).is_empty());
+    }
+
+    #[test]
+    fn classifies_history_attempts() {
+        let history = vec![
+            activity("1", at(18, 0, 0), 1800, true, false),
+            activity("2", at(19, 0, 0), 600, false, true),
+            activity("3", at(20, 0, 0), 300, false, false),
+        ];
+
+        let report = attempt_report(&history, &[], &HistoryFilter::default(), at(21, 0, 0));
+
+        let outcomes: Vec<AttemptOutcome> = report.attempts.iter().map(|a| a.outcome).collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                AttemptOutcome::Abandoned,
+                AttemptOutcome::Failed,
+                AttemptOutcome::Cleared
+            ]
+        );
+        assert_eq!(report.attempts[2].end, at(18, 30, 0));
+        assert_eq!(report.attempts[2].instance_id.as_deref(), Some("1"));
+    }
+
+    #[test]
+    fn matches_observed_attempts_to_history_within_the_slack() {
+        let history = vec![activity("1", at(18, 0, 0), 1800, true, false)];
+        let slack = Duration::seconds(ATTEMPT_MATCH_SLACK_SECS);
+
+        let matched = [observed(at(18, 30, 0) + slack, at(18, 50, 0))];
+        let report = attempt_report(&history, &matched, &HistoryFilter::default(), at(22, 0, 0));
+        assert_eq!(report.overall.attempts, 1);
+        assert_eq!(report.overall.unknown, 0);
+
+        let start = at(18, 30, 1) + slack;
+        let unmatched = [observed(start, at(18, 50, 0))];
+        let report = attempt_report(
+            &history,
+            &unmatched,
+            &HistoryFilter::default(),
+            at(22, 0, 0),
+        );
+        assert_eq!(report.overall.attempts, 2);
+        assert_eq!(report.overall.unknown, 1);
+        assert_eq!(report.attempts[0].outcome, AttemptOutcome::Unknown);
+        assert_eq!(report.attempts[0].instance_id, None);
+        assert_eq!(report.attempts[0].start, start);
+        assert_eq!(
+            report.attempts[0].duration_seconds as i64,
+            (at(18, 50, 0) - start).num_seconds()
+        );
+    }
+
+    #[test]
+    fn waits_for_the_history_before_counting_an_attempt_as_unknown() {
+        let attempt = [observed(at(19, 0, 0), at(19, 20, 0))];
+        let grace = ATTEMPT_HISTORY_GRACE_MINUTES as u32;
+
+        let report = attempt_report(
+            &[],
+            &attempt,
+            &HistoryFilter::default(),
+            at(19, 20 + grace, 0),
+        );
+        assert_eq!(report.overall.attempts, 0);
+
+        let report = attempt_report(
+            &[],
+            &attempt,
+            &HistoryFilter::default(),
+            at(19, 20 + grace, 1),
+        );
+        assert_eq!(report.overall.attempts, 1);
+        assert_eq!(report.overall.unknown, 1);
+    }
+
+    #[test]
+    fn counts_attempts_per_clear_and_unfinished_time() {
+        let history = vec![
+            activity("1", at(15, 0, 0), 1800, true, false),
+            activity("2", at(16, 0, 0), 600, false, true),
+            activity("3", at(17, 0, 0), 300, false, false),
+        ];
+        let attempt = [observed(at(19, 0, 0), at(19, 20, 0))];
+
+        let report = attempt_report(&history, &attempt, &HistoryFilter::default(), at(22, 0, 0));
+
+        assert_eq!(report.overall.attempts, 4);
+        assert_eq!(report.overall.clears, 1);
+        assert_eq!(report.overall.failed, 1);
+        assert_eq!(report.overall.abandoned, 1);
+        assert_eq!(report.overall.unknown, 1);
+        assert_eq!(report.overall.attempts_per_clear, Some(4.0));
+        assert_eq!(report.overall.unfinished_seconds, 600 + 300 + 1200);
+        assert_eq!(report.activities.len(), 1);
+        assert_eq!(report.activities[0].stats.attempts, 4);
+
+        let report = attempt_report(&history[1..], &[], &HistoryFilter::default(), at(22, 0, 0));
+        assert_eq!(report.overall.attempts_per_clear, None);
+    }
+}
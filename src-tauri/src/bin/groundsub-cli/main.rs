@@ -0,0 +1,309 @@
+use std::{
+    env,
+    io::{stdout, Write},
+    time::Duration,
+};
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use groundsub::{
+    api::{client::client_from_env, responses::ProfileSnapshot, Api, Source},
+    clock::{Clock, SystemClock},
+    config::{profiles::Profile, ConfigManager},
+    consts::PROFILE_SNAPSHOT_COMPONENTS,
+    filter::HistoryFilter,
+    history::{fetch_history, history_cutoff},
+    stats::compute_stats,
+    timerange::{last_daily_reset, last_weekly_reset},
+};
+use serde::Serialize;
+
+const WATCH_REFRESH_INTERVAL: u32 = 10;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Summary {
+    display_name: String,
+    current_activity: Option<CurrentActivitySummary>,
+    clears_today: usize,
+    clears_this_week: usize,
+    activities: Vec<ActivitySummary>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct CurrentActivitySummary {
+    name: String,
+    activity_hash: usize,
+    start_date: DateTime<Utc>,
+    elapsed_seconds: i64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ActivitySummary {
+    name: String,
+    activity_hash: usize,
+    attempts: usize,
+    clears: usize,
+    average_seconds: usize,
+    fastest_seconds: Option<usize>,
+    median_seconds: Option<usize>,
+    p90_seconds: Option<usize>,
+}
+
+struct Args {
+    json: bool,
+    watch: bool,
+}
+
+impl Args {
+    fn parse() -> Result<Self> {
+        let mut args = Self {
+            json: false,
+            watch: false,
+        };
+
+        for arg in env::args().skip(1) {
+            match arg.as_str() {
+                "--json" => args.json = true,
+                "--watch" => args.watch = true,
+                _ => return Err(anyhow!("Usage: groundsub-cli [--json] [--watch]")),
+            }
+        }
+
+        Ok(args)
+    }
+}
+
+fn format_seconds(seconds: i64) -> String {
+    let seconds = seconds.max(0);
+    let hours = seconds / 3600;
+    let minutes = (seconds % 3600) / 60;
+
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{:02}", seconds % 60)
+    } else {
+        format!("{minutes:02}:{:02}", seconds % 60)
+    }
+}
+
+async fn activity_name(api: &Api, activity_hash: usize) -> String {
+    match api
+        .activity_info_source
+        .lock()
+        .await
+        .get(&activity_hash)
+        .await
+    {
+        Ok(a) if !a.name.is_empty() => a.name,
+        _ => activity_hash.to_string(),
+    }
+}
+
+async fn current_activity(
+    api: &Api,
+    snapshot: &ProfileSnapshot,
+    now: DateTime<Utc>,
+) -> Result<Option<CurrentActivitySummary>> {
+    let latest = snapshot
+        .current_activities
+        .as_ref()
+        .and_then(|c| c.activities.as_ref())
+        .ok_or(anyhow!("Profile is private"))?
+        .values()
+        .max()
+        .cloned();
+
+    let latest = match latest {
+        Some(l) if l.current_activity_hash != 0 => l,
+        _ => return Ok(None),
+    };
+
+    Ok(Some(CurrentActivitySummary {
+        name: activity_name(api, latest.current_activity_hash).await,
+        activity_hash: latest.current_activity_hash,
+        start_date: latest.date_activity_started,
+        elapsed_seconds: (now - latest.date_activity_started).num_seconds(),
+    }))
+}
+
+async fn summarize(api: &Api, profile: &Profile, clock: &dyn Clock) -> Result<Summary> {
+    let now = clock.now();
+
+    let snapshot = api
+        .get_profile_snapshot(profile, PROFILE_SNAPSHOT_COMPONENTS)
+        .await?;
+
+    let display_name = snapshot
+        .profile_info
+        .as_ref()
+        .map(|p| format!("{}#{:04}", p.display_name, p.display_tag))
+        .unwrap_or_default();
+
+    let history = fetch_history(api, profile, &snapshot, history_cutoff(now)).await?;
+
+    let since = |reset| HistoryFilter {
+        since: Some(reset),
+        ..HistoryFilter::default()
+    };
+
+    let mut activities = Vec::new();
+    for activity in compute_stats(&history, &HistoryFilter::default()).activities {
+        if activity.stats.clears == 0 {
+            continue;
+        }
+
+        activities.push(ActivitySummary {
+            name: activity_name(api, activity.activity_hash).await,
+            activity_hash: activity.activity_hash,
+            attempts: activity.stats.attempts,
+            clears: activity.stats.clears,
+            average_seconds: activity.stats.average_clear_seconds,
+            fastest_seconds: activity.stats.fastest_clear_seconds,
+            median_seconds: activity.stats.median_clear_seconds,
+            p90_seconds: activity.stats.p90_clear_seconds,
+        });
+    }
+
+    Ok(Summary {
+        display_name,
+        current_activity: current_activity(api, &snapshot, now).await?,
+        clears_today: compute_stats(&history, &since(last_daily_reset(now)))
+            .overall
+            .clears,
+        clears_this_week: compute_stats(&history, &since(last_weekly_reset(now)))
+            .overall
+            .clears,
+        activities,
+    })
+}
+
+fn print_summary(summary: &Summary) {
+    println!("{}", summary.display_name);
+
+    match &summary.current_activity {
+        Some(a) => println!(
+            "Current activity: {} ({})",
+            a.name,
+            format_seconds(a.elapsed_seconds)
+        ),
+        None => println!("Current activity: none"),
+    }
+
+    println!("Clears today: {}", summary.clears_today);
+    println!("Clears this week: {}", summary.clears_this_week);
+
+    if summary.activities.is_empty() {
+        return;
+    }
+
+    println!();
+    println!(
+        "{:<40} {:>8} {:>6} {:>9} {:>9} {:>9} {:>9}",
+        "Activity", "Attempts", "Clears", "Average", "Fastest", "Median", "P90"
+    );
+
+    let format_clear =
+        |seconds: Option<usize>| seconds.map_or(String::new(), |s| format_seconds(s as i64));
+
+    for a in summary.activities.iter() {
+        println!(
+            "{:<40} {:>8} {:>6} {:>9} {:>9} {:>9} {:>9}",
+            a.name,
+            a.attempts,
+            a.clears,
+            format_seconds(a.average_seconds as i64),
+            format_clear(a.fastest_seconds),
+            format_clear(a.median_seconds),
+            format_clear(a.p90_seconds)
+        );
+    }
+}
+
+async fn refresh_current_activity(
+    api: &Api,
+    profile: &Profile,
+    now: DateTime<Utc>,
+) -> Result<Option<CurrentActivitySummary>> {
+    let snapshot = api
+        .get_profile_snapshot(profile, PROFILE_SNAPSHOT_COMPONENTS)
+        .await?;
+
+    current_activity(api, &snapshot, now).await
+}
+
+/// Keeps showing the last known activity when a refresh fails, and tries
+/// again on the next one.
+async fn watch(api: &Api, profile: &Profile, clock: &dyn Clock, json: bool) -> Result<()> {
+    let mut current = None;
+    let mut tick = 0;
+
+    loop {
+        if tick % WATCH_REFRESH_INTERVAL == 0 {
+            match refresh_current_activity(api, profile, clock.now()).await {
+                Ok(c) => current = c,
+                Err(e) => {
+                    if !json {
+                        print!("\r\x1b[2K");
+                        stdout().flush()?;
+                    }
+
+                    eprintln!("Failed to refresh the current activity: {}", e);
+                }
+            }
+        }
+
+        if let Some(ref mut a) = current {
+            a.elapsed_seconds = (clock.now() - a.start_date).num_seconds();
+        }
+
+        if json {
+            println!("{}", serde_json::to_string(&current)?);
+        } else {
+            let line = match &current {
+                Some(a) => format!("{} {}", a.name, format_seconds(a.elapsed_seconds)),
+                None => "Not in activity".to_string(),
+            };
+
+            print!("\r\x1b[2K{line}");
+            stdout().flush()?;
+        }
+
+        tick += 1;
+        clock.sleep(Duration::from_secs(1)).await;
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse()?;
+
+    let config = ConfigManager::load()?;
+    let profile = config
+        .get_profiles()
+        .selected_profile
+        .clone()
+        .ok_or(anyhow!("No profile set, select one in groundsub first"))?;
+
+    let api = Api::new(client_from_env()?);
+    let clock = SystemClock;
+
+    let summary = summarize(&api, &profile, &clock).await?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+    } else {
+        print_summary(&summary);
+    }
+
+    if args.watch {
+        if !args.json {
+            println!();
+        }
+
+        watch(&api, &profile, &clock, args.json).await?;
+    }
+
+    Ok(())
+}
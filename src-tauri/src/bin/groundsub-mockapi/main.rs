@@ -0,0 +1,346 @@
+use std::{
+    collections::HashMap, env, net::SocketAddr, path::PathBuf, sync::Arc, time::Instant,
+};
+
+use anyhow::{anyhow, Result};
+use axum::{
+    extract::{Path, Query, State},
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use serde_json::{json, Map, Value};
+
+use self::scenario::{Scenario, World};
+
+mod scenario;
+
+const DEFAULT_PORT: u16 = 8080;
+const DEFAULT_HISTORY_COUNT: usize = 25;
+
+struct MockState {
+    scenario: Scenario,
+    epoch: DateTime<Utc>,
+    started: Instant,
+    speed: f64,
+}
+
+impl MockState {
+    fn world(&self) -> World {
+        let elapsed = self.started.elapsed().as_secs_f64() * self.speed;
+        self.scenario.world_at(self.epoch, elapsed)
+    }
+
+    fn now(&self) -> DateTime<Utc> {
+        let elapsed = self.started.elapsed().as_secs_f64() * self.speed;
+        self.epoch + chrono::Duration::milliseconds((elapsed * 1000.0) as i64)
+    }
+}
+
+struct Args {
+    scenario: PathBuf,
+    port: u16,
+    speed: f64,
+}
+
+impl Args {
+    fn parse() -> Result<Self> {
+        let mut scenario = None;
+        let mut port = DEFAULT_PORT;
+        let mut speed = 1.0;
+
+        let mut args = env::args().skip(1);
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--port" => port = args.next().ok_or(anyhow!("--port needs a value"))?.parse()?,
+                "--speed" => speed = args.next().ok_or(anyhow!("--speed needs a value"))?.parse()?,
+                _ => scenario = Some(PathBuf::from(arg)),
+            }
+        }
+
+        Ok(Self {
+            scenario: scenario.ok_or(anyhow!(
+                "Usage: groundsub-mockapi <scenario.json> [--port <port>] [--speed <multiplier>]"
+            ))?,
+            port,
+            speed,
+        })
+    }
+}
+
+fn respond(response: Value) -> Json<Value> {
+    Json(json!({
+        "ErrorCode": 1,
+        "ErrorStatus": "Success",
+        "Message": "Ok",
+        "ThrottleSeconds": 0,
+        "Response": response,
+    }))
+}
+
+fn system_disabled() -> Json<Value> {
+    Json(json!({
+        "ErrorCode": 5,
+        "ErrorStatus": "SystemDisabled",
+        "Message": "This system is temporarily disabled for maintenance.",
+        "ThrottleSeconds": 0,
+    }))
+}
+
+async fn search(State(state): State<Arc<MockState>>, body: String) -> Json<Value> {
+    let query: Value = serde_json::from_str(&body).unwrap_or_default();
+    let player = &state.scenario.player;
+
+    let matches = query["displayName"].as_str() == Some(player.display_name.as_str())
+        && query["displayNameCode"].as_u64() == Some(player.display_name_code as u64);
+
+    if !matches {
+        return respond(json!([]));
+    }
+
+    respond(json!([{
+        "membershipType": player.membership_type,
+        "membershipId": player.membership_id,
+        "bungieGlobalDisplayName": player.display_name,
+        "bungieGlobalDisplayNameCode": player.display_name_code,
+        "crossSaveOverride": 0,
+    }]))
+}
+
+async fn profile(
+    State(state): State<Arc<MockState>>,
+    Path((_, _)): Path<(usize, String)>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Json<Value> {
+    let world = state.world();
+
+    if world.destiny2_disabled {
+        return system_disabled();
+    }
+
+    let player = &state.scenario.player;
+    let components = query.get("components").cloned().unwrap_or_default();
+    let mut response = Map::new();
+
+    for component in components.split(',') {
+        match component {
+            "100" => {
+                response.insert(
+                    "profile".to_string(),
+                    json!({
+                        "data": {
+                            "userInfo": {
+                                "bungieGlobalDisplayName": player.display_name,
+                                "bungieGlobalDisplayNameCode": player.display_name_code,
+                            },
+                            "characterIds": state
+                                .scenario
+                                .characters
+                                .iter()
+                                .map(|c| c.character_id.clone())
+                                .collect::<Vec<_>>(),
+                        },
+                        "privacy": 1,
+                    }),
+                );
+            }
+            "200" => {
+                let characters: Map<String, Value> = state
+                    .scenario
+                    .characters
+                    .iter()
+                    .map(|c| {
+                        let last_played = world
+                            .last_played
+                            .get(&c.character_id)
+                            .copied()
+                            .unwrap_or(state.epoch);
+
+                        (
+                            c.character_id.clone(),
+                            json!({
+                                "characterId": c.character_id,
+                                "classType": c.class_type,
+                                "raceType": c.race_type,
+                                "light": c.light,
+                                "emblemPath": c.emblem_path,
+                                "dateLastPlayed": last_played,
+                            }),
+                        )
+                    })
+                    .collect();
+
+                response.insert(
+                    "characters".to_string(),
+                    json!({ "data": characters, "privacy": 1 }),
+                );
+            }
+            "204" => {
+                let activities: Map<String, Value> = state
+                    .scenario
+                    .characters
+                    .iter()
+                    .map(|c| {
+                        let (hash, started) = match world.current.get(&c.character_id) {
+                            Some(a) => (a.activity_hash, a.started),
+                            None => (0, state.epoch),
+                        };
+
+                        (
+                            c.character_id.clone(),
+                            json!({
+                                "dateActivityStarted": started,
+                                "currentActivityHash": hash,
+                            }),
+                        )
+                    })
+                    .collect();
+
+                response.insert(
+                    "characterActivities".to_string(),
+                    json!({ "data": activities, "privacy": 1 }),
+                );
+            }
+            _ => (),
+        }
+    }
+
+    respond(Value::Object(response))
+}
+
+async fn history(
+    State(state): State<Arc<MockState>>,
+    Path((_, _, character_id)): Path<(usize, String, String)>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Json<Value> {
+    let world = state.world();
+
+    if world.destiny2_disabled {
+        return system_disabled();
+    }
+
+    let page: usize = query.get("page").and_then(|p| p.parse().ok()).unwrap_or(0);
+    let count: usize = query
+        .get("count")
+        .and_then(|c| c.parse().ok())
+        .unwrap_or(DEFAULT_HISTORY_COUNT);
+
+    let activities: Vec<Value> = world
+        .history
+        .get(&character_id)
+        .map(|h| {
+            h.iter()
+                .rev()
+                .skip(page * count)
+                .take(count)
+                .map(|e| {
+                    json!({
+                        "period": e.period,
+                        "activityDetails": {
+                            "instanceId": e.instance_id,
+                            "directorActivityHash": e.activity_hash,
+                            "modes": e.modes,
+                        },
+                        "values": {
+                            "completed": { "basic": { "value": if e.completed { 1.0 } else { 0.0 } } },
+                            "completionReason": { "basic": { "value": if e.completed { 0.0 } else { 2.0 } } },
+                            "activityDurationSeconds": { "basic": { "value": e.duration_seconds as f64 } },
+                        },
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if activities.is_empty() {
+        return respond(json!({}));
+    }
+
+    respond(json!({ "activities": activities }))
+}
+
+async fn activity_definition(
+    State(state): State<Arc<MockState>>,
+    Path(activity_hash): Path<usize>,
+) -> Json<Value> {
+    match state.scenario.activities.get(&activity_hash) {
+        Some(a) => respond(json!({
+            "originalDisplayProperties": { "name": a.name },
+            "activityModeTypes": a.modes,
+            "activityTypeHash": a.activity_type_hash,
+            "pgcrImage": a.pgcr_image,
+        })),
+        None => Json(json!({
+            "ErrorCode": 1,
+            "ErrorStatus": "Success",
+            "Message": "Ok",
+            "ThrottleSeconds": 0,
+        })),
+    }
+}
+
+async fn global_alerts(State(state): State<Arc<MockState>>) -> Json<Value> {
+    if state.world().destiny2_disabled {
+        return respond(json!([{
+            "AlertKey": "mockapi-maintenance",
+            "AlertHtml": "Destiny 2 is down for scripted maintenance.",
+            "AlertLink": null,
+            "AlertLevel": 2,
+            "AlertTimestamp": state.now(),
+        }]));
+    }
+
+    respond(json!([]))
+}
+
+async fn settings(State(state): State<Arc<MockState>>) -> Json<Value> {
+    respond(json!({
+        "systems": {
+            "Destiny2": { "enabled": !state.world().destiny2_disabled },
+        },
+    }))
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse()?;
+
+    let state = Arc::new(MockState {
+        scenario: Scenario::load(&args.scenario)?,
+        epoch: Utc::now(),
+        started: Instant::now(),
+        speed: args.speed,
+    });
+
+    let app = Router::new()
+        .route(
+            "/Platform/Destiny2/SearchDestinyPlayerByBungieName/All",
+            post(search),
+        )
+        .route(
+            "/Platform/Destiny2/:membership_type/Profile/:membership_id",
+            get(profile),
+        )
+        .route(
+            "/Platform/Destiny2/:membership_type/Account/:membership_id/Character/:character_id/Stats/Activities",
+            get(history),
+        )
+        .route(
+            "/Platform/Destiny2/Manifest/DestinyActivityDefinition/:activity_hash",
+            get(activity_definition),
+        )
+        .route("/Platform/GlobalAlerts/", get(global_alerts))
+        .route("/Platform/Settings/", get(settings))
+        .with_state(state);
+
+    let addr = SocketAddr::from(([127, 0, 0, 1], args.port));
+
+    println!("Serving mock Bungie API on http://{addr}/Platform");
+
+    axum::Server::bind(&addr)
+        .serve(app.into_make_service())
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,328 @@
+use std::{collections::HashMap, fs::read_to_string, path::Path};
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Scenario {
+    pub player: Player,
+    pub characters: Vec<Character>,
+    #[serde(default)]
+    pub activities: HashMap<usize, ActivityDefinition>,
+    #[serde(default)]
+    pub events: Vec<Event>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Player {
+    pub membership_type: usize,
+    pub membership_id: String,
+    pub display_name: String,
+    pub display_name_code: usize,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Character {
+    pub character_id: String,
+    pub class_type: usize,
+    #[serde(default)]
+    pub race_type: usize,
+    #[serde(default)]
+    pub light: usize,
+    pub emblem_path: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub modes: Vec<usize>,
+    #[serde(default)]
+    pub activity_type_hash: usize,
+    pub pgcr_image: Option<String>,
+}
+
+/// A scripted change to the world, `at` seconds after the server started.
+/// Negative offsets describe activities that happened before startup.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Event {
+    pub at: f64,
+    pub character_id: Option<String>,
+    #[serde(flatten)]
+    pub kind: EventKind,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EventKind {
+    Start {
+        #[serde(rename = "activityHash")]
+        activity_hash: usize,
+    },
+    Complete,
+    Abandon,
+    Disable,
+    Enable,
+}
+
+pub struct CurrentActivity {
+    pub activity_hash: usize,
+    pub started: DateTime<Utc>,
+}
+
+pub struct HistoryEntry {
+    pub instance_id: String,
+    pub activity_hash: usize,
+    pub modes: Vec<usize>,
+    pub period: DateTime<Utc>,
+    pub duration_seconds: i64,
+    pub completed: bool,
+}
+
+#[derive(Default)]
+pub struct World {
+    pub current: HashMap<String, CurrentActivity>,
+    pub history: HashMap<String, Vec<HistoryEntry>>,
+    pub last_played: HashMap<String, DateTime<Utc>>,
+    pub destiny2_disabled: bool,
+}
+
+impl Scenario {
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::parse(&read_to_string(path)?)
+    }
+
+    pub fn parse(json: &str) -> Result<Self> {
+        let mut scenario: Scenario = serde_json::from_str(json)?;
+        scenario.events.sort_by(|a, b| a.at.total_cmp(&b.at));
+
+        Ok(scenario)
+    }
+
+    /// Replays every event up to `elapsed` seconds, anchoring scenario time at `epoch`.
+    pub fn world_at(&self, epoch: DateTime<Utc>, elapsed: f64) -> World {
+        let mut world = World::default();
+        let mut instance_counter = 0;
+
+        for event in self.events.iter().take_while(|e| e.at <= elapsed) {
+            let date = epoch + Duration::milliseconds((event.at * 1000.0) as i64);
+
+            let character_id = match &event.character_id {
+                Some(c) => c.clone(),
+                None => self
+                    .characters
+                    .first()
+                    .map(|c| c.character_id.clone())
+                    .unwrap_or_default(),
+            };
+
+            match &event.kind {
+                EventKind::Start { activity_hash } => {
+                    world.current.insert(
+                        character_id.clone(),
+                        CurrentActivity {
+                            activity_hash: *activity_hash,
+                            started: date,
+                        },
+                    );
+                }
+                EventKind::Complete | EventKind::Abandon => {
+                    if let Some(current) = world
+                        .current
+                        .remove(&character_id)
+                        .filter(|c| c.activity_hash != 0)
+                    {
+                        instance_counter += 1;
+
+                        let modes = self
+                            .activities
+                            .get(&current.activity_hash)
+                            .map(|a| a.modes.clone())
+                            .unwrap_or_default();
+
+                        world
+                            .history
+                            .entry(character_id.clone())
+                            .or_default()
+                            .push(HistoryEntry {
+                                instance_id: (10_000_000_000u64 + instance_counter).to_string(),
+                                activity_hash: current.activity_hash,
+                                modes,
+                                period: current.started,
+                                duration_seconds: (date - current.started).num_seconds(),
+                                completed: matches!(event.kind, EventKind::Complete),
+                            });
+                    }
+
+                    world.current.insert(
+                        character_id.clone(),
+                        CurrentActivity {
+                            activity_hash: 0,
+                            started: date,
+                        },
+                    );
+                }
+                EventKind::Disable => {
+                    world.destiny2_disabled = true;
+                    continue;
+                }
+                EventKind::Enable => {
+                    world.destiny2_disabled = false;
+                    continue;
+                }
+            }
+
+            world.last_played.insert(character_id, date);
+        }
+
+        world
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    const RAID: usize = 3881495763;
+    const DUNGEON: usize = 2032534090;
+
+    fn epoch() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 10, 18, 0, 0).unwrap()
+    }
+
+    fn scenario(events: &str) -> Scenario {
+        Scenario::parse(&format!(
+            r#"{{
+                "player": {{
+                    "membershipType": 3,
+                    "membershipId": "4611686018467284386",
+                    "displayName": "Guardian",
+                    "displayNameCode": 1234
+                }},
+                "characters": [
+                    {{ "characterId": "1", "classType": 1 }},
+                    {{ "characterId": "2", "classType": 2 }}
+                ],
+                "activities": {{
+                    "{RAID}": {{ "name": "Vault of Glass", "modes": [4] }}
+                }},
+                "events": {events}
+            }}"#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn records_completed_and_abandoned_activities() {
+        let scenario = scenario(&format!(
+            r#"[
+                {{ "at": 0, "type": "start", "activityHash": {RAID} }},
+                {{ "at": 1800, "type": "complete" }},
+                {{ "at": 1900, "type": "start", "activityHash": {DUNGEON} }},
+                {{ "at": 2000, "type": "abandon" }}
+            ]"#
+        ));
+
+        let world = scenario.world_at(epoch(), 2500.0);
+        let history = &world.history["1"];
+
+        assert_eq!(history.len(), 2);
+        assert_ne!(history[0].instance_id, history[1].instance_id);
+
+        assert_eq!(history[0].activity_hash, RAID);
+        assert_eq!(history[0].modes, vec![4]);
+        assert_eq!(history[0].period, epoch());
+        assert_eq!(history[0].duration_seconds, 1800);
+        assert!(history[0].completed);
+
+        assert_eq!(history[1].activity_hash, DUNGEON);
+        assert!(history[1].modes.is_empty());
+        assert_eq!(history[1].period, epoch() + Duration::seconds(1900));
+        assert_eq!(history[1].duration_seconds, 100);
+        assert!(!history[1].completed);
+
+        let orbit = &world.current["1"];
+        assert_eq!(orbit.activity_hash, 0);
+        assert_eq!(orbit.started, epoch() + Duration::seconds(2000));
+        assert_eq!(world.last_played["1"], epoch() + Duration::seconds(2000));
+        assert!(!world.history.contains_key("2"));
+    }
+
+    #[test]
+    fn replays_only_the_events_so_far() {
+        let scenario = scenario(&format!(
+            r#"[
+                {{ "at": 0, "type": "start", "activityHash": {RAID}, "characterId": "2" }},
+                {{ "at": 1800, "type": "complete", "characterId": "2" }}
+            ]"#
+        ));
+
+        let world = scenario.world_at(epoch(), 1799.0);
+
+        assert_eq!(world.current["2"].activity_hash, RAID);
+        assert!(!world.current.contains_key("1"));
+        assert!(world.history.is_empty());
+        assert_eq!(world.last_played["2"], epoch());
+    }
+
+    #[test]
+    fn ignores_a_complete_in_orbit() {
+        let scenario = scenario(
+            r#"[
+                { "at": 0, "type": "abandon" },
+                { "at": 10, "type": "complete" }
+            ]"#,
+        );
+
+        let world = scenario.world_at(epoch(), 60.0);
+
+        assert!(world.history.is_empty());
+        assert_eq!(world.current["1"].activity_hash, 0);
+        assert_eq!(world.current["1"].started, epoch() + Duration::seconds(10));
+    }
+
+    #[test]
+    fn disables_and_enables_destiny2() {
+        let scenario = scenario(
+            r#"[
+                { "at": 10, "type": "disable" },
+                { "at": 20, "type": "enable" }
+            ]"#,
+        );
+
+        assert!(!scenario.world_at(epoch(), 5.0).destiny2_disabled);
+        assert!(scenario.world_at(epoch(), 15.0).destiny2_disabled);
+
+        let world = scenario.world_at(epoch(), 25.0);
+        assert!(!world.destiny2_disabled);
+        assert!(world.last_played.is_empty());
+    }
+
+    #[test]
+    fn replays_negative_offsets_before_startup() {
+        let scenario = scenario(&format!(
+            r#"[
+                {{ "at": 600, "type": "start", "activityHash": {RAID} }},
+                {{ "at": -1800, "type": "complete" }},
+                {{ "at": -3600, "type": "start", "activityHash": {RAID} }}
+            ]"#
+        ));
+
+        let world = scenario.world_at(epoch(), 0.0);
+        let history = &world.history["1"];
+
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].period, epoch() - Duration::seconds(3600));
+        assert_eq!(history[0].duration_seconds, 1800);
+        assert_eq!(world.current["1"].activity_hash, 0);
+        assert_eq!(world.last_played["1"], epoch() - Duration::seconds(1800));
+    }
+}
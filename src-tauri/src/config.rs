@@ -21,8 +21,14 @@ pub struct ConfigManager {
 
 impl ConfigManager {
     pub fn load() -> Result<Self> {
+        let mut preferences = Preferences::load()?;
+
+        if preferences.ensure_local_api_token("") {
+            preferences.write()?;
+        }
+
         Ok(Self {
-            preferences: Preferences::load()?,
+            preferences,
             profiles: Profiles::load()?,
         })
     }
@@ -46,6 +52,17 @@ impl ConfigManager {
     }
 }
 
+pub fn data_path(filename: &str) -> Result<PathBuf> {
+    BaseDirs::new()
+        .map(|d| {
+            let mut path = d.data_dir().to_owned();
+            path.push(APP_NAME);
+            path.push(filename);
+            path
+        })
+        .ok_or(anyhow!("No data_dir available"))
+}
+
 trait ConfigFile: Serialize + DeserializeOwned + Default {
     fn load() -> Result<Self> {
         match read_to_string(Self::get_path()?) {
@@ -76,14 +93,7 @@ trait ConfigFile: Serialize + DeserializeOwned + Default {
     }
 
     fn get_path() -> Result<PathBuf> {
-        BaseDirs::new()
-            .map(|d| {
-                let mut path = d.data_dir().to_owned();
-                path.push(APP_NAME);
-                path.push(Self::get_filename());
-                path
-            })
-            .ok_or(anyhow!("No data_dir available"))
+        data_path(Self::get_filename())
     }
 
     fn get_filename() -> &'static str;
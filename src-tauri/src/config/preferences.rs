@@ -1,6 +1,8 @@
+use rand::{distributions::Alphanumeric, Rng};
 use serde::{Deserialize, Serialize};
 
 use super::ConfigFile;
+use crate::consts::LOCAL_API_TOKEN_LENGTH;
 
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -17,7 +19,12 @@ pub struct Preferences {
     pub use_real_time: bool,
     pub display_average_clear_time_details: bool,
     pub display_difference_indicator: bool,
+    pub display_personal_best_notifications: bool,
+    pub personal_best_per_class: bool,
+    pub personal_best_full_runs_only: bool,
+    pub session_gap_minutes: u32,
     pub filter_activity_type: String,
+    pub filter_activity_hashes: Vec<usize>,
     pub filter_timespan: String,
     pub timer_mode: String,
     pub raid_link_provider: String,
@@ -35,6 +42,49 @@ pub struct Preferences {
     pub custom_start_date: String,
     pub display_now_playing: bool,
     pub overlay_background_opacity: i32,
+    pub enable_local_api: bool,
+    pub local_api_port: u16,
+    pub local_api_token: String,
+    pub enable_file_output: bool,
+    pub file_output_dir: String,
+    pub enable_discord_presence: bool,
+    pub discord_show_timer: bool,
+    pub discord_show_clears: bool,
+    pub discord_show_image: bool,
+    pub enable_livesplit: bool,
+    pub livesplit_host: String,
+    pub livesplit_port: u16,
+    pub enable_webhooks: bool,
+    pub webhooks: Vec<Webhook>,
+    pub enable_mqtt: bool,
+    pub mqtt_host: String,
+    pub mqtt_port: u16,
+    pub mqtt_username: String,
+    pub mqtt_password: String,
+    pub mqtt_topic_prefix: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+pub struct Webhook {
+    pub url: String,
+    pub preset: String,
+    pub template: String,
+    pub on_clear: bool,
+    pub on_personal_best: bool,
+}
+
+impl Default for Webhook {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            preset: "discord".to_string(),
+            template: String::new(),
+            on_clear: true,
+            on_personal_best: true,
+        }
+    }
 }
 
 impl Default for Preferences {
@@ -51,7 +101,12 @@ impl Default for Preferences {
             use_real_time: false,
             display_average_clear_time_details: false,
             display_difference_indicator: false,
+            display_personal_best_notifications: true,
+            personal_best_per_class: false,
+            personal_best_full_runs_only: false,
+            session_gap_minutes: 30,
             filter_activity_type: "all".to_string(),
+            filter_activity_hashes: Vec::new(),
             filter_timespan: "1".to_string(),
             timer_mode: "default".to_string(),
             raid_link_provider: "raid.report".to_string(),
@@ -69,12 +124,111 @@ impl Default for Preferences {
             custom_start_date: String::new(),
             display_now_playing: false,
             overlay_background_opacity: 0,
+            enable_local_api: false,
+            local_api_port: 7120,
+            local_api_token: String::new(),
+            enable_file_output: false,
+            file_output_dir: String::new(),
+            enable_discord_presence: false,
+            discord_show_timer: true,
+            discord_show_clears: true,
+            discord_show_image: true,
+            enable_livesplit: false,
+            livesplit_host: "localhost".to_string(),
+            livesplit_port: 16834,
+            enable_webhooks: false,
+            webhooks: Vec::new(),
+            enable_mqtt: false,
+            mqtt_host: "localhost".to_string(),
+            mqtt_port: 1883,
+            mqtt_username: String::new(),
+            mqtt_password: String::new(),
+            mqtt_topic_prefix: "groundsub".to_string(),
+        }
+    }
+}
+
+impl Preferences {
+    /// The local API never runs without a token. An empty token keeps the
+    /// previous one, and a new one is generated the first time the API is
+    /// enabled. Returns whether the token changed.
+    pub fn ensure_local_api_token(&mut self, previous: &str) -> bool {
+        let before = self.local_api_token.clone();
+
+        if self.local_api_token.is_empty() {
+            self.local_api_token = previous.to_string();
+        }
+
+        if self.enable_local_api && self.local_api_token.is_empty() {
+            self.local_api_token = generate_token();
+        }
+
+        self.local_api_token != before
+    }
+
+    /// A copy that is safe to hand to external tools, with secrets cleared.
+    pub fn redacted(&self) -> Self {
+        Self {
+            local_api_token: String::new(),
+            mqtt_password: String::new(),
+            webhooks: self
+                .webhooks
+                .iter()
+                .map(|w| Webhook {
+                    url: String::new(),
+                    ..w.clone()
+                })
+                .collect(),
+            ..self.clone()
         }
     }
 }
 
+fn generate_token() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(LOCAL_API_TOKEN_LENGTH)
+        .map(char::from)
+        .collect()
+}
+
 impl ConfigFile for Preferences {
     fn get_filename() -> &'static str {
         "preferences.json"
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_a_token_when_the_api_is_enabled() {
+        let mut preferences = Preferences::default();
+        assert!(!preferences.ensure_local_api_token(""));
+        assert!(preferences.local_api_token.is_empty());
+
+        preferences.enable_local_api = true;
+        assert!(preferences.ensure_local_api_token(""));
+        assert_eq!(preferences.local_api_token.len(), LOCAL_API_TOKEN_LENGTH);
+
+        let token = preferences.local_api_token.clone();
+        assert!(!preferences.ensure_local_api_token(""));
+        assert_eq!(preferences.local_api_token, token);
+    }
+
+    #[test]
+    fn an_empty_token_keeps_the_previous_one() {
+        let mut preferences = Preferences {
+            enable_local_api: true,
+            ..Preferences::default()
+        };
+
+        assert!(preferences.ensure_local_api_token("previous"));
+        assert_eq!(preferences.local_api_token, "previous");
+
+        preferences.local_api_token = "chosen".to_string();
+        assert!(!preferences.ensure_local_api_token("previous"));
+        assert_eq!(preferences.local_api_token, "chosen");
+    }
+}
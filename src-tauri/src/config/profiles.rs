@@ -38,6 +38,13 @@ pub struct Profile {
     pub account_id: String,
 }
 
+impl Profile {
+    /// Identifies the profile in files that hold data for several profiles.
+    pub fn key(&self) -> String {
+        format!("{}-{}", self.account_platform, self.account_id)
+    }
+}
+
 impl ConfigFile for Profiles {
     fn get_filename() -> &'static str {
         "profiles.json"
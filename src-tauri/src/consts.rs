@@ -2,13 +2,63 @@ use std::time::Duration;
 
 pub const TARGET_NAME: &str = "destiny2.exe";
 pub const OVERLAY_POLL_INTERVAL: Duration = Duration::from_millis(200);
+pub const HEALTH_POLL_INTERVAL: Duration = Duration::from_secs(60);
+pub const PLAYERDATA_POLL_INTERVAL: Duration = Duration::from_secs(2);
+pub const HISTORY_WINDOW_DAYS: i64 = 30;
+pub const MAX_ACTIVITY_DURATION_SECS: usize = 24 * 60 * 60;
+pub const DAILY_RESET_HOUR_UTC: u32 = 17;
+pub const ATTEMPT_MATCH_SLACK_SECS: i64 = 120;
+pub const ATTEMPT_HISTORY_GRACE_MINUTES: i64 = 15;
+pub const MAINTENANCE_BACKOFF: Duration = Duration::from_secs(60);
+pub const APP_EVENT_BUFFER: usize = 64;
+pub const FILE_OUTPUT_INTERVAL: Duration = Duration::from_secs(1);
+pub const DISCORD_UPDATE_INTERVAL: Duration = Duration::from_secs(15);
+pub const DISCORD_RECONNECT_INTERVAL: Duration = Duration::from_secs(30);
+pub const DISCORD_IPC_TIMEOUT: Duration = Duration::from_secs(10);
+pub const LIVESPLIT_SYNC_INTERVAL: Duration = Duration::from_secs(5);
+pub const LIVESPLIT_RECONNECT_INTERVAL: Duration = Duration::from_secs(10);
+pub const WEBHOOK_POLL_INTERVAL: Duration = Duration::from_secs(5);
+pub const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+pub const WEBHOOK_RETRY_DELAY_SECS: i64 = 30;
+pub const WEBHOOK_MAX_ATTEMPTS: u32 = 6;
+pub const MQTT_PUBLISH_INTERVAL: Duration = Duration::from_secs(1);
+pub const MQTT_KEEP_ALIVE: Duration = Duration::from_secs(30);
+pub const MQTT_RECONNECT_INTERVAL: Duration = Duration::from_secs(10);
+pub const MQTT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+pub const LOCAL_API_TOKEN_LENGTH: usize = 32;
 pub const APP_NAME: &str = "groundsub";
+pub const MQTT_CLIENT_ID: &str = "groundsub";
 pub const APP_VER: &str = env!("CARGO_PKG_VERSION");
 pub const API_KEY: &str = env!("BUNGIE_API_KEY");
 pub const API_PATH: &str = "https://www.bungie.net/Platform";
+pub const BUNGIE_BASE_PATH: &str = "https://www.bungie.net";
+pub const DISCORD_CLIENT_ID: Option<&str> = option_env!("DISCORD_CLIENT_ID");
+pub const MALFORMED_ACTIVITIES_FILE: &str = "malformed_activities.jsonl";
+pub const MALFORMED_ACTIVITIES_LIMIT: usize = 200;
+pub const WEBHOOK_QUEUE_FILE: &str = "webhook_queue.json";
+pub const IMPORTED_HISTORY_FILE: &str = "imported_history.json";
+pub const PERSONAL_BESTS_FILE: &str = "personal_bests.json";
+pub const ATTEMPTS_FILE: &str = "attempts.json";
+pub const API_BASE_ENV: &str = "GROUNDSUB_API_BASE";
+pub const API_RECORD_ENV: &str = "GROUNDSUB_API_RECORD";
+pub const API_REPLAY_ENV: &str = "GROUNDSUB_API_REPLAY";
+pub const DISCORD_IPC_ENV: &str = "GROUNDSUB_DISCORD_IPC";
+pub const DISCORD_IPC_PIPES: usize = 10;
+pub const DISCORD_IPC_MAX_FRAME: usize = 64 * 1024;
 pub const NAMED_PIPE: &str = r"\\.\pipe\groundsub-open";
 pub const USER_AGENT: &str = concat!("groundsub/", env!("CARGO_PKG_VERSION"), " (https://github.com/distaste4596/groundsub)");
 
+pub const DESTINY2_SYSTEM: &str = "Destiny2";
+
+pub const PROFILES_COMPONENT: usize = 100;
+pub const CHARACTERS_COMPONENT: usize = 200;
+pub const CHARACTER_ACTIVITIES_COMPONENT: usize = 204;
+pub const PROFILE_SNAPSHOT_COMPONENTS: &[usize] = &[
+    PROFILES_COMPONENT,
+    CHARACTERS_COMPONENT,
+    CHARACTER_ACTIVITIES_COMPONENT,
+];
+
 pub const RAID_ACTIVITY_MODE: usize = 4;
 pub const DUNGEON_ACTIVITY_MODE: usize = 82;
 pub const STRIKE_ACTIVITY_MODE: usize = 18;
@@ -20,3 +70,5 @@ pub const DUNGEON_ACTIVITY_HASH: usize = 608898761;
 pub const EXCLUDED_ACTIVITY_HASHES: &[usize] = &[
     3830679567, // Shooting Range
 ];
+
+pub const PERSISTENT_START_BUFFER_SECS: i64 = 40;
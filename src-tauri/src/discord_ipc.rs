@@ -0,0 +1,160 @@
+use std::{env, path::PathBuf};
+
+use anyhow::{anyhow, bail, Result};
+use serde_json::Value;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::consts::{DISCORD_IPC_ENV, DISCORD_IPC_MAX_FRAME, DISCORD_IPC_PIPES};
+
+#[cfg(windows)]
+pub type IpcStream = tokio::net::windows::named_pipe::NamedPipeClient;
+#[cfg(unix)]
+pub type IpcStream = tokio::net::UnixStream;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Opcode {
+    Handshake,
+    Frame,
+    Close,
+    Ping,
+    Pong,
+}
+
+impl Opcode {
+    fn to_u32(self) -> u32 {
+        match self {
+            Self::Handshake => 0,
+            Self::Frame => 1,
+            Self::Close => 2,
+            Self::Ping => 3,
+            Self::Pong => 4,
+        }
+    }
+
+    fn from_u32(value: u32) -> Result<Self> {
+        Ok(match value {
+            0 => Self::Handshake,
+            1 => Self::Frame,
+            2 => Self::Close,
+            3 => Self::Ping,
+            4 => Self::Pong,
+            _ => bail!("Unknown Discord IPC opcode {}", value),
+        })
+    }
+}
+
+/// Every frame is a little-endian opcode and length followed by a JSON payload.
+pub async fn write_frame<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    opcode: Opcode,
+    payload: &Value,
+) -> Result<()> {
+    let body = serde_json::to_vec(payload)?;
+
+    let mut frame = Vec::with_capacity(8 + body.len());
+    frame.extend_from_slice(&opcode.to_u32().to_le_bytes());
+    frame.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    frame.extend_from_slice(&body);
+
+    writer.write_all(&frame).await?;
+    writer.flush().await?;
+
+    Ok(())
+}
+
+/// Anything can listen on the IPC path, so the length isn't trusted with an
+/// allocation beyond what Discord ever sends.
+pub async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> Result<(Opcode, Value)> {
+    let opcode = Opcode::from_u32(reader.read_u32_le().await?)?;
+    let length = reader.read_u32_le().await? as usize;
+
+    if length > DISCORD_IPC_MAX_FRAME {
+        bail!("Discord IPC frame of {} bytes is too large", length);
+    }
+
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).await?;
+
+    Ok((opcode, serde_json::from_slice(&body)?))
+}
+
+pub fn ipc_paths() -> Vec<PathBuf> {
+    if let Ok(path) = env::var(DISCORD_IPC_ENV) {
+        return vec![PathBuf::from(path)];
+    }
+
+    #[cfg(windows)]
+    let dir = PathBuf::from(r"\\.\pipe");
+
+    #[cfg(unix)]
+    let dir = ["XDG_RUNTIME_DIR", "TMPDIR", "TMP", "TEMP"]
+        .iter()
+        .find_map(|v| env::var(v).ok())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("/tmp"));
+
+    (0..DISCORD_IPC_PIPES)
+        .map(|i| dir.join(format!("discord-ipc-{i}")))
+        .collect()
+}
+
+/// Connects to the first Discord client that is listening.
+pub async fn connect() -> Result<IpcStream> {
+    for path in ipc_paths() {
+        #[cfg(windows)]
+        let stream = tokio::net::windows::named_pipe::ClientOptions::new().open(&path);
+
+        #[cfg(unix)]
+        let stream = tokio::net::UnixStream::connect(&path).await;
+
+        if let Ok(s) = stream {
+            return Ok(s);
+        }
+    }
+
+    Err(anyhow!("Discord is not running"))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use tokio::io::duplex;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn reads_back_written_frames() {
+        let (mut client, mut discord) = duplex(4096);
+        let payload = json!({ "cmd": "SET_ACTIVITY", "nonce": "1" });
+
+        write_frame(&mut client, Opcode::Frame, &payload)
+            .await
+            .unwrap();
+
+        let (opcode, read) = read_frame(&mut discord).await.unwrap();
+        assert_eq!(opcode, Opcode::Frame);
+        assert_eq!(read, payload);
+    }
+
+    #[tokio::test]
+    async fn rejects_oversized_frames() {
+        let (mut client, mut discord) = duplex(64);
+
+        let mut header = Vec::new();
+        header.extend_from_slice(&Opcode::Frame.to_u32().to_le_bytes());
+        header.extend_from_slice(&u32::MAX.to_le_bytes());
+        discord.write_all(&header).await.unwrap();
+
+        let err = read_frame(&mut client).await.unwrap_err();
+        assert!(err.to_string().contains("too large"));
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_opcodes() {
+        let (mut client, mut discord) = duplex(64);
+
+        discord.write_all(&9u32.to_le_bytes()).await.unwrap();
+
+        assert!(read_frame(&mut client).await.is_err());
+    }
+}
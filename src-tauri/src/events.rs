@@ -0,0 +1,49 @@
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+use tokio::sync::broadcast;
+
+use crate::consts::APP_EVENT_BUFFER;
+
+#[derive(Serialize, Clone)]
+pub struct AppEvent {
+    pub event: String,
+    pub payload: Value,
+}
+
+/// Carries the events sent to the windows to everything else that wants them,
+/// like the local API and the integrations.
+pub struct AppEvents(broadcast::Sender<AppEvent>);
+
+impl Default for AppEvents {
+    fn default() -> Self {
+        Self(broadcast::channel(APP_EVENT_BUFFER).0)
+    }
+}
+
+impl AppEvents {
+    pub fn subscribe(&self) -> broadcast::Receiver<AppEvent> {
+        self.0.subscribe()
+    }
+}
+
+pub fn publish_event<S: Serialize>(handle: &AppHandle, event: &str, payload: &S) {
+    let events = match handle.try_state::<AppEvents>() {
+        Some(e) => e,
+        None => return,
+    };
+
+    if events.0.receiver_count() == 0 {
+        return;
+    }
+
+    match serde_json::to_value(payload) {
+        Ok(payload) => {
+            let _ = events.0.send(AppEvent {
+                event: event.to_string(),
+                payload,
+            });
+        }
+        Err(e) => eprintln!("Failed to serialize {} event: {}", event, e),
+    }
+}
@@ -0,0 +1,182 @@
+use std::{fs::write, path::Path};
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    api::{responses::CompletedActivity, Api},
+    filter::HistoryFilter,
+};
+
+pub const CSV_HEADER: &[&str] = &[
+    "instance_id",
+    "activity_hash",
+    "activity_name",
+    "character_class",
+    "start",
+    "duration_seconds",
+    "completed",
+    "failed",
+    "modes",
+    "start_seconds",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn parse(format: &str) -> Result<Self> {
+        match format.to_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "json" => Ok(Self::Json),
+            _ => Err(anyhow!("Unknown export format {}", format)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedActivity {
+    pub instance_id: String,
+    pub activity_hash: usize,
+    pub activity_name: String,
+    pub character_class: Option<String>,
+    pub start: DateTime<Utc>,
+    pub duration_seconds: usize,
+    pub completed: bool,
+    #[serde(default)]
+    pub failed: bool,
+    pub modes: Vec<usize>,
+    #[serde(default)]
+    pub start_seconds: usize,
+}
+
+impl ExportedActivity {
+    fn csv_row(&self) -> Vec<String> {
+        vec![
+            self.instance_id.clone(),
+            self.activity_hash.to_string(),
+            self.activity_name.clone(),
+            self.character_class.clone().unwrap_or_default(),
+            self.start.to_rfc3339(),
+            self.duration_seconds.to_string(),
+            self.completed.to_string(),
+            self.failed.to_string(),
+            self.modes
+                .iter()
+                .map(|m| m.to_string())
+                .collect::<Vec<_>>()
+                .join(" "),
+            self.start_seconds.to_string(),
+        ]
+    }
+}
+
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+pub fn to_csv(rows: &[ExportedActivity]) -> String {
+    let mut csv = CSV_HEADER.join(",");
+    csv.push_str("\r\n");
+
+    for row in rows {
+        let fields: Vec<String> = row.csv_row().iter().map(|f| csv_escape(f)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push_str("\r\n");
+    }
+
+    csv
+}
+
+pub async fn build_export(
+    api: &Api,
+    history: &[CompletedActivity],
+    filter: &HistoryFilter,
+) -> Vec<ExportedActivity> {
+    let mut rows = Vec::new();
+
+    for activity in history.iter().filter(|a| filter.matches(a)) {
+        let activity_name = match api
+            .activity_info_source
+            .lock()
+            .await
+            .get(&activity.activity_hash)
+            .await
+        {
+            Ok(a) => a.name,
+            Err(_) => String::new(),
+        };
+
+        rows.push(ExportedActivity {
+            instance_id: activity.instance_id.clone(),
+            activity_hash: activity.activity_hash,
+            activity_name,
+            character_class: activity.character_class.clone(),
+            start: activity.period,
+            duration_seconds: activity.activity_duration_seconds,
+            completed: activity.completed,
+            failed: activity.failed,
+            modes: activity.modes.clone(),
+            start_seconds: activity.start_seconds,
+        });
+    }
+
+    rows
+}
+
+pub fn write_export(path: &Path, format: ExportFormat, rows: &[ExportedActivity]) -> Result<()> {
+    let contents = match format {
+        ExportFormat::Csv => to_csv(rows),
+        ExportFormat::Json => serde_json::to_string_pretty(rows)?,
+    };
+
+    write(path, contents)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn escapes_csv_fields() {
+        assert_eq!(csv_escape("Vault of Glass"), "Vault of Glass");
+        assert_eq!(csv_escape("King's Fall, Master"), "\"King's Fall, Master\"");
+        assert_eq!(csv_escape("The \"Pit\""), "\"The \"\"Pit\"\"\"");
+        assert_eq!(csv_escape("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn writes_a_csv_row_per_activity() {
+        let row = ExportedActivity {
+            instance_id: "1".to_string(),
+            activity_hash: 3881495763,
+            activity_name: "Vault of Glass, \"Master\"".to_string(),
+            character_class: None,
+            start: Utc.with_ymd_and_hms(2026, 3, 10, 18, 0, 0).unwrap(),
+            duration_seconds: 2712,
+            completed: false,
+            failed: true,
+            modes: vec![4, 82],
+            start_seconds: 600,
+        };
+
+        assert_eq!(
+            to_csv(&[row]),
+            "instance_id,activity_hash,activity_name,character_class,start,duration_seconds,completed,failed,modes,start_seconds\r\n\
+             1,3881495763,\"Vault of Glass, \"\"Master\"\"\",,2026-03-10T18:00:00+00:00,2712,false,true,4 82,600\r\n"
+        );
+    }
+}
@@ -0,0 +1,295 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer};
+
+use crate::{
+    api::responses::CompletedActivity,
+    consts::{
+        DUNGEON_ACTIVITY_MODE, EXCLUDED_ACTIVITY_HASHES, LOSTSECTOR_ACTIVITY_MODE,
+        RAID_ACTIVITY_MODE, STORY_ACTIVITY_MODE, STRIKE_ACTIVITY_MODE,
+    },
+    timerange::range_start,
+};
+
+/// Maps the activity type names of the details window to their activity mode.
+pub fn activity_type_mode(activity_type: &str) -> Option<usize> {
+    match activity_type {
+        "raids" => Some(RAID_ACTIVITY_MODE),
+        "dungeons" => Some(DUNGEON_ACTIVITY_MODE),
+        "strikes" => Some(STRIKE_ACTIVITY_MODE),
+        "lost-sectors" => Some(LOSTSECTOR_ACTIVITY_MODE),
+        "story" => Some(STORY_ACTIVITY_MODE),
+        _ => None,
+    }
+}
+
+/// Mirrors the filters of the details window. Activity types use the same
+/// names as its activity select, specific activities are passed as hashes.
+/// The run type is `full` or `checkpoint`, where a checkpoint run is one the
+/// character joined after the instance had started. Instead of `since`, the
+/// filter can name a timespan of the time range filter, see [`range_start`].
+/// In a query string the activity hashes are comma separated.
+#[derive(Deserialize, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+pub struct HistoryFilter {
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub activity_type: Option<String>,
+    #[serde(deserialize_with = "deserialize_hashes")]
+    pub activity_hashes: Option<Vec<usize>>,
+    pub character_class: Option<String>,
+    pub run_type: Option<String>,
+    pub timespan: Option<String>,
+    pub custom_start_date: Option<String>,
+    pub use_real_time: bool,
+}
+
+fn deserialize_hashes<'de, D>(deserializer: D) -> Result<Option<Vec<usize>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Hashes {
+        List(Vec<usize>),
+        Joined(String),
+    }
+
+    match Option::<Hashes>::deserialize(deserializer)? {
+        Some(Hashes::List(hashes)) => Ok(Some(hashes)),
+        Some(Hashes::Joined(joined)) => joined
+            .split(',')
+            .filter(|h| !h.trim().is_empty())
+            .map(|h| h.trim().parse().map_err(serde::de::Error::custom))
+            .collect::<Result<_, _>>()
+            .map(Some),
+        None => Ok(None),
+    }
+}
+
+impl HistoryFilter {
+    /// Turns the timespan into a start time, unless `since` is already set.
+    pub fn resolve(&self, now: DateTime<Utc>) -> Result<Self> {
+        let mut filter = self.clone();
+
+        if let (None, Some(timespan)) = (filter.since, &filter.timespan) {
+            filter.since = range_start(
+                timespan,
+                filter.custom_start_date.as_deref().unwrap_or_default(),
+                filter.use_real_time,
+                now,
+            )?;
+        }
+
+        Ok(filter)
+    }
+
+    pub fn matches(&self, activity: &CompletedActivity) -> bool {
+        if EXCLUDED_ACTIVITY_HASHES.contains(&activity.activity_hash) {
+            return false;
+        }
+
+        if let Some(since) = self.since {
+            if activity.period < since {
+                return false;
+            }
+        }
+
+        if let Some(until) = self.until {
+            if activity.period >= until {
+                return false;
+            }
+        }
+
+        match self.run_type.as_deref() {
+            Some("full") if activity.start_seconds > 0 => return false,
+            Some("checkpoint") if activity.start_seconds == 0 => return false,
+            _ => (),
+        }
+
+        if let Some(ref hashes) = self.activity_hashes {
+            if !hashes.contains(&activity.activity_hash) {
+                return false;
+            }
+        }
+
+        if let Some(ref activity_type) = self.activity_type {
+            if let Some(mode) = activity_type_mode(activity_type) {
+                if !activity.modes.contains(&mode) {
+                    return false;
+                }
+            }
+        }
+
+        match (&self.character_class, &activity.character_class) {
+            (Some(wanted), Some(class)) => wanted.eq_ignore_ascii_case(class),
+            (Some(_), None) => false,
+            (None, _) => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    use super::*;
+    use crate::api::responses::format_duration;
+
+    const RAID: usize = 1374392663;
+    const DUNGEON: usize = 2032534090;
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, day, hour, 0, 0).unwrap()
+    }
+
+    fn activity(activity_hash: usize, mode: usize, start_seconds: usize) -> CompletedActivity {
+        CompletedActivity {
+            period: at(12, 18),
+            instance_id: "1".to_string(),
+            activity_hash,
+            modes: vec![mode],
+            completed: true,
+            failed: false,
+            activity_duration: format_duration(1800),
+            activity_duration_seconds: 1800,
+            start_seconds,
+            character_class: Some("Hunter".to_string()),
+        }
+    }
+
+    #[test]
+    fn filters_full_and_checkpoint_runs() {
+        let full = activity(RAID, RAID_ACTIVITY_MODE, 0);
+        let checkpoint = activity(RAID, RAID_ACTIVITY_MODE, 600);
+
+        let filter = HistoryFilter {
+            run_type: Some("full".to_string()),
+            ..Default::default()
+        };
+        assert!(filter.matches(&full));
+        assert!(!filter.matches(&checkpoint));
+
+        let filter = HistoryFilter {
+            run_type: Some("checkpoint".to_string()),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&full));
+        assert!(filter.matches(&checkpoint));
+
+        assert!(HistoryFilter::default().matches(&checkpoint));
+    }
+
+    #[test]
+    fn filters_by_character_class() {
+        let hunter = activity(RAID, RAID_ACTIVITY_MODE, 0);
+        let mut unknown = activity(RAID, RAID_ACTIVITY_MODE, 0);
+        unknown.character_class = None;
+
+        let filter = HistoryFilter {
+            character_class: Some("hunter".to_string()),
+            ..Default::default()
+        };
+        assert!(filter.matches(&hunter));
+        assert!(!filter.matches(&unknown));
+
+        let filter = HistoryFilter {
+            character_class: Some("Titan".to_string()),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&hunter));
+
+        assert!(HistoryFilter::default().matches(&unknown));
+    }
+
+    #[test]
+    fn keeps_since_and_drops_until() {
+        let raid = activity(RAID, RAID_ACTIVITY_MODE, 0);
+
+        let filter = HistoryFilter {
+            since: Some(at(12, 18)),
+            until: Some(at(12, 19)),
+            ..Default::default()
+        };
+        assert!(filter.matches(&raid));
+
+        let filter = HistoryFilter {
+            until: Some(at(12, 18)),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&raid));
+
+        let filter = HistoryFilter {
+            since: Some(at(12, 19)),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&raid));
+    }
+
+    #[test]
+    fn filters_by_activity_type_and_hash() {
+        let raid = activity(RAID, RAID_ACTIVITY_MODE, 0);
+        let dungeon = activity(DUNGEON, DUNGEON_ACTIVITY_MODE, 0);
+
+        let filter = HistoryFilter {
+            activity_type: Some("raids".to_string()),
+            ..Default::default()
+        };
+        assert!(filter.matches(&raid));
+        assert!(!filter.matches(&dungeon));
+
+        let filter = HistoryFilter {
+            activity_hashes: Some(vec![DUNGEON]),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&raid));
+        assert!(filter.matches(&dungeon));
+
+        let shooting_range = activity(EXCLUDED_ACTIVITY_HASHES[0], STORY_ACTIVITY_MODE, 0);
+        assert!(!HistoryFilter::default().matches(&shooting_range));
+    }
+
+    #[test]
+    fn resolves_the_timespan_unless_since_is_set() {
+        let filter = HistoryFilter {
+            timespan: Some("1".to_string()),
+            ..Default::default()
+        };
+        let resolved = filter.resolve(at(12, 20)).unwrap();
+        assert_eq!(resolved.since, Some(at(12, 17)));
+
+        let filter = HistoryFilter {
+            since: Some(at(1, 0)),
+            timespan: Some("1".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(filter.resolve(at(12, 20)).unwrap().since, Some(at(1, 0)));
+
+        let filter = HistoryFilter {
+            timespan: Some("365".to_string()),
+            ..Default::default()
+        };
+        assert!(filter.resolve(at(12, 20)).is_err());
+    }
+
+    #[test]
+    fn reads_activity_hashes_as_a_list_or_comma_separated() {
+        let filter: HistoryFilter =
+            serde_json::from_value(json!({ "activityHashes": [RAID, DUNGEON] })).unwrap();
+        assert_eq!(filter.activity_hashes, Some(vec![RAID, DUNGEON]));
+
+        let filter: HistoryFilter =
+            serde_json::from_value(json!({ "activityHashes": format!("{RAID}, {DUNGEON}") }))
+                .unwrap();
+        assert_eq!(filter.activity_hashes, Some(vec![RAID, DUNGEON]));
+
+        let filter: HistoryFilter = serde_json::from_value(json!({})).unwrap();
+        assert_eq!(filter.activity_hashes, None);
+
+        assert!(
+            serde_json::from_value::<HistoryFilter>(json!({ "activityHashes": "raid" })).is_err()
+        );
+    }
+}
@@ -0,0 +1,380 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+
+use crate::{
+    api::{
+        responses::{CompletedActivity, ProfileSnapshot},
+        Api,
+    },
+    config::profiles::Profile,
+    consts::{
+        DUNGEON_ACTIVITY_MODE, EXCLUDED_ACTIVITY_HASHES, HISTORY_WINDOW_DAYS,
+        LOSTSECTOR_ACTIVITY_MODE, RAID_ACTIVITY_MODE, STORY_ACTIVITY_MODE, STRIKE_ACTIVITY_MODE,
+    },
+};
+
+pub fn history_cutoff(now: DateTime<Utc>) -> DateTime<Utc> {
+    now - Duration::days(HISTORY_WINDOW_DAYS)
+}
+
+pub fn is_tracked(activity_hash: usize, modes: &[usize]) -> bool {
+    modes.iter().any(|m| {
+        *m == RAID_ACTIVITY_MODE
+            || *m == DUNGEON_ACTIVITY_MODE
+            || *m == STRIKE_ACTIVITY_MODE
+            || *m == LOSTSECTOR_ACTIVITY_MODE
+            || *m == STORY_ACTIVITY_MODE
+    }) && !EXCLUDED_ACTIVITY_HASHES.contains(&activity_hash)
+}
+
+pub fn is_tracked_activity(activity: &CompletedActivity) -> bool {
+    is_tracked(activity.activity_hash, &activity.modes)
+}
+
+pub async fn fetch_history(
+    api: &Api,
+    profile: &Profile,
+    snapshot: &ProfileSnapshot,
+    cutoff: DateTime<Utc>,
+) -> Result<Vec<CompletedActivity>> {
+    let profile_info = snapshot
+        .profile_info
+        .as_ref()
+        .ok_or(anyhow!("No profile data for profile"))?;
+
+    let character_classes = snapshot.character_classes();
+
+    let mut past_activities: Vec<CompletedActivity> = Vec::new();
+
+    for character_id in profile_info.character_ids.iter() {
+        let character_class = character_classes.get(character_id).cloned();
+        let mut page = 0;
+
+        loop {
+            let history = api
+                .get_activity_history(profile, character_id, page)
+                .await?;
+
+            let activities = match history.activities {
+                Some(a) => a,
+                None => break,
+            };
+
+            let mut includes_past_cutoff = false;
+
+            for activity in activities.into_iter() {
+                if activity.period < cutoff {
+                    includes_past_cutoff = true;
+                } else if is_tracked_activity(&activity) {
+                    let mut activity_with_class = activity;
+                    activity_with_class.character_class = character_class.clone();
+                    past_activities.push(activity_with_class);
+                }
+            }
+
+            if includes_past_cutoff {
+                break;
+            }
+
+            page += 1;
+        }
+    }
+
+    past_activities.sort();
+    past_activities.reverse();
+
+    Ok(past_activities)
+}
+
+pub fn merge_history(
+    last_history: &mut Vec<CompletedActivity>,
+    past_activities: Vec<CompletedActivity>,
+) -> bool {
+    let has_new_data = if last_history.is_empty() {
+        !past_activities.is_empty()
+    } else {
+        let has_new_combo = past_activities.iter().any(|new| {
+            !last_history.iter().any(|old| {
+                old.instance_id == new.instance_id && old.character_class == new.character_class
+            })
+        });
+
+        let has_newer_timestamp = if let (Some(last), Some(new)) =
+            (last_history.iter().max(), past_activities.iter().max())
+        {
+            new > last
+        } else {
+            false
+        };
+
+        has_new_combo || has_newer_timestamp
+    };
+
+    if !has_new_data {
+        return false;
+    }
+
+    let mut merged_activities: Vec<CompletedActivity> = last_history.clone();
+    for new_activity in past_activities {
+        let existing_idx = merged_activities.iter().position(|a| {
+            a.instance_id == new_activity.instance_id
+                && a.character_class == new_activity.character_class
+        });
+
+        if let Some(idx) = existing_idx {
+            merged_activities[idx] = new_activity;
+        } else {
+            merged_activities.push(new_activity);
+        }
+    }
+    merged_activities.sort();
+    merged_activities.reverse();
+    *last_history = merged_activities;
+
+    true
+}
+
+/// Adds imported activities the history doesn't have yet. Activities already
+/// loaded from the API are kept as they are. Returns how many were added.
+pub fn merge_imported(
+    last_history: &mut Vec<CompletedActivity>,
+    imported: Vec<CompletedActivity>,
+) -> usize {
+    let mut added = 0;
+
+    for activity in imported {
+        let exists = last_history.iter().any(|a| {
+            a.instance_id == activity.instance_id && a.character_class == activity.character_class
+        });
+
+        if !exists {
+            last_history.push(activity);
+            added += 1;
+        }
+    }
+
+    if added > 0 {
+        last_history.sort();
+        last_history.reverse();
+    }
+
+    added
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::TimeZone;
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::{
+        api::{
+            fake::FakeBungieClient,
+            requests::BungieRequest,
+            responses::{format_duration, ProfileInfo},
+        },
+        consts::RAID_ACTIVITY_MODE,
+    };
+
+    const RAID: usize = 1374392663;
+    const CHARACTER_ID: &str = "2305843009301234567";
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, day, hour, 0, 0).unwrap()
+    }
+
+    fn activity(instance_id: &str, period: DateTime<Utc>, class: &str) -> CompletedActivity {
+        CompletedActivity {
+            period,
+            instance_id: instance_id.to_string(),
+            activity_hash: RAID,
+            modes: vec![RAID_ACTIVITY_MODE],
+            completed: true,
+            failed: false,
+            activity_duration: format_duration(1800),
+            activity_duration_seconds: 1800,
+            start_seconds: 0,
+            character_class: Some(class.to_string()),
+        }
+    }
+
+    fn profile() -> Profile {
+        Profile {
+            account_platform: 3,
+            account_id: "4611686018467654321".to_string(),
+        }
+    }
+
+    fn snapshot() -> ProfileSnapshot {
+        ProfileSnapshot {
+            profile_info: Some(ProfileInfo {
+                privacy: 1,
+                display_name: "Guardian".to_string(),
+                display_tag: 1234,
+                character_ids: vec![CHARACTER_ID.to_string()],
+            }),
+            characters: None,
+            current_activities: None,
+        }
+    }
+
+    fn history_entry(instance_id: &str, period: DateTime<Utc>, completed: bool) -> Value {
+        json!({
+            "period": period.to_rfc3339(),
+            "activityDetails": {
+                "instanceId": instance_id,
+                "directorActivityHash": RAID,
+                "modes": [RAID_ACTIVITY_MODE],
+            },
+            "values": {
+                "completed": { "basic": { "value": if completed { 1.0 } else { 0.0 } } },
+                "completionReason": { "basic": { "value": 0.0 } },
+                "activityDurationSeconds": { "basic": { "value": 1800.0 } },
+            },
+        })
+    }
+
+    fn respond_history(client: &FakeBungieClient, entries: Vec<Value>) {
+        let profile = profile();
+        let path = BungieRequest::GetActivityHistory {
+            membership_type: profile.account_platform,
+            membership_id: &profile.account_id,
+            character_id: CHARACTER_ID,
+            page: 0,
+        }
+        .path();
+
+        client.respond(&path, json!({ "activities": entries }));
+    }
+
+    #[test]
+    fn merge_history_reports_new_activities() {
+        let mut history = Vec::new();
+
+        assert!(merge_history(
+            &mut history,
+            vec![activity("1", at(1, 18), "Hunter")]
+        ));
+        assert!(!merge_history(
+            &mut history,
+            vec![activity("1", at(1, 18), "Hunter")]
+        ));
+
+        assert!(merge_history(
+            &mut history,
+            vec![
+                activity("2", at(2, 18), "Hunter"),
+                activity("1", at(1, 18), "Hunter"),
+            ]
+        ));
+
+        let ids: Vec<&str> = history.iter().map(|a| a.instance_id.as_str()).collect();
+        assert_eq!(ids, ["2", "1"]);
+    }
+
+    #[test]
+    fn merge_history_keeps_the_same_instance_per_class() {
+        let mut history = vec![activity("1", at(1, 18), "Hunter")];
+
+        assert!(merge_history(
+            &mut history,
+            vec![activity("1", at(1, 18), "Warlock")]
+        ));
+        assert_eq!(history.len(), 2);
+    }
+
+    #[test]
+    fn merge_imported_only_adds_missing_activities() {
+        let mut history = vec![activity("2", at(2, 18), "Hunter")];
+
+        let mut duplicate = activity("2", at(2, 18), "Hunter");
+        duplicate.activity_duration_seconds = 60;
+
+        let added = merge_imported(
+            &mut history,
+            vec![
+                duplicate,
+                activity("3", at(3, 18), "Hunter"),
+                activity("1", at(1, 18), "Hunter"),
+            ],
+        );
+
+        assert_eq!(added, 2);
+
+        let ids: Vec<&str> = history.iter().map(|a| a.instance_id.as_str()).collect();
+        assert_eq!(ids, ["3", "2", "1"]);
+        assert_eq!(history[1].activity_duration_seconds, 1800);
+    }
+
+    #[tokio::test]
+    async fn keeps_activities_on_the_cutoff() {
+        let client = Arc::new(FakeBungieClient::default());
+        let api = Api::new(client.clone());
+        let cutoff = history_cutoff(at(10, 20));
+
+        assert_eq!(at(10, 20) - cutoff, Duration::days(30));
+
+        respond_history(
+            &client,
+            vec![
+                history_entry("2", cutoff, true),
+                history_entry("1", cutoff - Duration::milliseconds(1), true),
+            ],
+        );
+
+        let fetched = fetch_history(&api, &profile(), &snapshot(), cutoff)
+            .await
+            .unwrap();
+
+        let ids: Vec<&str> = fetched.iter().map(|a| a.instance_id.as_str()).collect();
+        assert_eq!(ids, ["2"]);
+        assert_eq!(client.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn detects_a_new_clear_from_the_api() {
+        let client = Arc::new(FakeBungieClient::default());
+        let api = Api::new(client.clone());
+        let cutoff = history_cutoff(at(10, 20));
+
+        // The entry past the cutoff ends the paging after the first page.
+        let old = history_entry("0", at(1, 0) - Duration::days(60), true);
+
+        respond_history(
+            &client,
+            vec![history_entry("1", at(10, 18), true), old.clone()],
+        );
+
+        let mut history = Vec::new();
+        let fetched = fetch_history(&api, &profile(), &snapshot(), cutoff)
+            .await
+            .unwrap();
+        assert!(merge_history(&mut history, fetched));
+
+        let fetched = fetch_history(&api, &profile(), &snapshot(), cutoff)
+            .await
+            .unwrap();
+        assert!(!merge_history(&mut history, fetched));
+
+        respond_history(
+            &client,
+            vec![
+                history_entry("2", at(10, 19), true),
+                history_entry("1", at(10, 18), true),
+                old,
+            ],
+        );
+
+        let fetched = fetch_history(&api, &profile(), &snapshot(), cutoff)
+            .await
+            .unwrap();
+        assert!(merge_history(&mut history, fetched));
+
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].instance_id, "2");
+        assert!(history[0].completed);
+        assert_eq!(client.requests().len(), 3);
+    }
+}
@@ -0,0 +1,567 @@
+use std::{
+    collections::HashMap,
+    fs::{create_dir_all, read_to_string, write},
+    io::ErrorKind,
+    path::Path,
+};
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use serde::Deserialize;
+
+use crate::{
+    api::{
+        responses::{format_duration, CompletedActivity},
+        Api,
+    },
+    config::{data_path, profiles::Profile},
+    consts::{IMPORTED_HISTORY_FILE, MAX_ACTIVITY_DURATION_SECS},
+    export::{ExportFormat, ExportedActivity},
+    history::is_tracked_activity,
+};
+
+const INSTANCE_ID_COLUMNS: &[&str] = &["instance_id", "instanceid", "instance", "pgcr", "pgcr_id"];
+const ACTIVITY_HASH_COLUMNS: &[&str] = &["activity_hash", "activityhash", "hash", "reference_id"];
+const CHARACTER_CLASS_COLUMNS: &[&str] = &["character_class", "characterclass", "class"];
+const START_COLUMNS: &[&str] = &["start", "period", "date", "start_time", "started"];
+const DURATION_COLUMNS: &[&str] = &["duration_seconds", "durationseconds", "duration", "time"];
+const COMPLETED_COLUMNS: &[&str] = &["completed", "cleared", "complete"];
+const FAILED_COLUMNS: &[&str] = &["failed", "wiped"];
+const MODES_COLUMNS: &[&str] = &["modes", "activity_modes"];
+const START_SECONDS_COLUMNS: &[&str] = &["start_seconds", "startseconds"];
+
+/// Column names for a CSV import. Columns that aren't set are looked up by
+/// their usual names, so groundsub's own CSV export needs no mapping.
+#[derive(Deserialize, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+pub struct CsvMapping {
+    pub instance_id: Option<String>,
+    pub activity_hash: Option<String>,
+    pub character_class: Option<String>,
+    pub start: Option<String>,
+    pub duration_seconds: Option<String>,
+    pub completed: Option<String>,
+    pub failed: Option<String>,
+    pub modes: Option<String>,
+    pub start_seconds: Option<String>,
+}
+
+fn normalize_column(name: &str) -> String {
+    name.trim().to_lowercase().replace([' ', '-'], "_")
+}
+
+fn find_column(
+    header: &[String],
+    mapped: &Option<String>,
+    aliases: &[&str],
+) -> Result<Option<usize>> {
+    if let Some(name) = mapped {
+        let name = normalize_column(name);
+
+        return match header.iter().position(|h| *h == name) {
+            Some(i) => Ok(Some(i)),
+            None => Err(anyhow!("Column {} not found", name)),
+        };
+    }
+
+    Ok(aliases
+        .iter()
+        .find_map(|alias| header.iter().position(|h| h == alias)))
+}
+
+fn parse_csv(contents: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = contents.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => row.push(std::mem::take(&mut field)),
+            '\r' if !quoted => (),
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    rows.retain(|r| r.iter().any(|f| !f.trim().is_empty()));
+    rows
+}
+
+fn parse_start(value: &str) -> Result<DateTime<Utc>> {
+    let value = value.trim();
+
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Ok(date.with_timezone(&Utc));
+    }
+
+    ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .map(|date| Utc.from_utc_datetime(&date))
+        .ok_or(anyhow!("Invalid start time {}", value))
+}
+
+/// Accepts plain seconds as well as `h:mm:ss` and `mm:ss`, up to a day.
+fn parse_duration(value: &str) -> Result<usize> {
+    let value = value.trim();
+
+    let seconds = match value.parse::<f64>() {
+        Ok(seconds) if seconds.is_finite() => seconds.max(0.0),
+        Ok(_) => return Err(anyhow!("Invalid duration {}", value)),
+        Err(_) => value
+            .split(':')
+            .try_fold(0.0, |total, part| {
+                part.trim().parse::<u32>().map(|p| total * 60.0 + p as f64)
+            })
+            .map_err(|_| anyhow!("Invalid duration {}", value))?,
+    };
+
+    if seconds > MAX_ACTIVITY_DURATION_SECS as f64 {
+        return Err(anyhow!("Duration {} is longer than a day", value));
+    }
+
+    Ok(seconds as usize)
+}
+
+/// JSON rows skip `parse_duration`, so they get the same limit here.
+fn check_durations(rows: &[ExportedActivity]) -> Result<()> {
+    for (i, row) in rows.iter().enumerate() {
+        if row.duration_seconds > MAX_ACTIVITY_DURATION_SECS
+            || row.start_seconds > MAX_ACTIVITY_DURATION_SECS
+        {
+            return Err(anyhow!("Row {}: Duration is longer than a day", i + 1));
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_completed(value: &str) -> bool {
+    !matches!(
+        value.trim().to_lowercase().as_str(),
+        "false" | "0" | "no" | "n"
+    )
+}
+
+fn parse_failed(value: &str) -> bool {
+    matches!(
+        value.trim().to_lowercase().as_str(),
+        "true" | "1" | "yes" | "y"
+    )
+}
+
+fn parse_modes(value: &str) -> Vec<usize> {
+    value
+        .split(|c: char| c == ' ' || c == ';' || c == '|')
+        .filter_map(|m| m.trim().parse().ok())
+        .collect()
+}
+
+fn from_csv(contents: &str, mapping: &CsvMapping) -> Result<Vec<ExportedActivity>> {
+    // Spreadsheets like to start their CSV exports with a byte order mark.
+    let contents = contents.strip_prefix('\u{feff}').unwrap_or(contents);
+    let mut rows = parse_csv(contents).into_iter();
+
+    let header: Vec<String> = rows
+        .next()
+        .ok_or(anyhow!("CSV file is empty"))?
+        .iter()
+        .map(|h| normalize_column(h))
+        .collect();
+
+    let instance_id = find_column(&header, &mapping.instance_id, INSTANCE_ID_COLUMNS)?
+        .ok_or(anyhow!("CSV file has no instance id column"))?;
+    let activity_hash = find_column(&header, &mapping.activity_hash, ACTIVITY_HASH_COLUMNS)?
+        .ok_or(anyhow!("CSV file has no activity hash column"))?;
+    let start = find_column(&header, &mapping.start, START_COLUMNS)?
+        .ok_or(anyhow!("CSV file has no start time column"))?;
+    let character_class = find_column(&header, &mapping.character_class, CHARACTER_CLASS_COLUMNS)?;
+    let duration = find_column(&header, &mapping.duration_seconds, DURATION_COLUMNS)?;
+    let completed = find_column(&header, &mapping.completed, COMPLETED_COLUMNS)?;
+    let failed = find_column(&header, &mapping.failed, FAILED_COLUMNS)?;
+    let modes = find_column(&header, &mapping.modes, MODES_COLUMNS)?;
+    let start_seconds = find_column(&header, &mapping.start_seconds, START_SECONDS_COLUMNS)?;
+
+    let mut activities = Vec::new();
+
+    for (line, row) in rows.enumerate() {
+        let field = |i: Option<usize>| i.and_then(|i| row.get(i)).map(|f| f.trim());
+        let row_error = |e: anyhow::Error| anyhow!("Row {}: {}", line + 2, e);
+
+        let instance_id = field(Some(instance_id)).unwrap_or_default();
+        if instance_id.is_empty() {
+            return Err(row_error(anyhow!("Missing instance id")));
+        }
+
+        activities.push(ExportedActivity {
+            instance_id: instance_id.to_string(),
+            activity_hash: field(Some(activity_hash))
+                .unwrap_or_default()
+                .parse()
+                .map_err(|_| row_error(anyhow!("Invalid activity hash")))?,
+            activity_name: String::new(),
+            character_class: field(character_class)
+                .filter(|c| !c.is_empty())
+                .map(|c| c.to_string()),
+            start: parse_start(field(Some(start)).unwrap_or_default()).map_err(row_error)?,
+            duration_seconds: match field(duration) {
+                Some(d) if !d.is_empty() => parse_duration(d).map_err(row_error)?,
+                _ => 0,
+            },
+            // Most trackers only log clears, so a missing column means completed.
+            completed: field(completed).map_or(true, parse_completed),
+            failed: field(failed).map_or(false, parse_failed),
+            modes: field(modes).map(parse_modes).unwrap_or_default(),
+            start_seconds: match field(start_seconds) {
+                Some(s) if !s.is_empty() => parse_duration(s).map_err(row_error)?,
+                _ => 0,
+            },
+        });
+    }
+
+    Ok(activities)
+}
+
+pub fn read_import(
+    path: &Path,
+    format: ExportFormat,
+    mapping: &CsvMapping,
+) -> Result<Vec<ExportedActivity>> {
+    let contents = read_to_string(path)?;
+
+    match format {
+        ExportFormat::Csv => from_csv(&contents, mapping),
+        ExportFormat::Json => {
+            let rows: Vec<ExportedActivity> = serde_json::from_str(&contents)?;
+            check_durations(&rows)?;
+
+            Ok(rows)
+        }
+    }
+}
+
+/// Fills in activity modes the file didn't have from the activity definition
+/// and drops everything the history doesn't track.
+pub async fn build_import(api: &Api, rows: Vec<ExportedActivity>) -> Vec<ExportedActivity> {
+    let mut imported = Vec::new();
+
+    for mut row in rows {
+        if row.modes.is_empty() {
+            if let Ok(info) = api
+                .activity_info_source
+                .lock()
+                .await
+                .get(&row.activity_hash)
+                .await
+            {
+                row.modes = info.activity_modes;
+            }
+        }
+
+        if is_tracked_activity(&to_completed_activity(&row)) {
+            imported.push(row);
+        }
+    }
+
+    imported
+}
+
+pub fn to_completed_activity(row: &ExportedActivity) -> CompletedActivity {
+    CompletedActivity {
+        period: row.start,
+        instance_id: row.instance_id.clone(),
+        activity_hash: row.activity_hash,
+        modes: row.modes.clone(),
+        completed: row.completed,
+        failed: row.failed,
+        activity_duration: format_duration(row.duration_seconds),
+        activity_duration_seconds: row.duration_seconds,
+        start_seconds: row.start_seconds,
+        character_class: row.character_class.clone(),
+    }
+}
+
+type ImportedHistory = HashMap<String, Vec<ExportedActivity>>;
+
+fn load_store() -> Result<ImportedHistory> {
+    match read_to_string(data_path(IMPORTED_HISTORY_FILE)?) {
+        Ok(s) => Ok(serde_json::from_str(&s)?),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(ImportedHistory::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Activities imported for the profile, kept outside the API history window.
+pub fn load_imported(profile: &Profile) -> Vec<CompletedActivity> {
+    match load_store() {
+        Ok(mut store) => store
+            .remove(&profile.key())
+            .unwrap_or_default()
+            .iter()
+            .map(to_completed_activity)
+            .collect(),
+        Err(e) => {
+            eprintln!("Failed to read imported history: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Adds the rows to the profile's imported history, keyed by instance and
+/// character like the API history. Returns how many of them were new.
+pub fn save_imported(profile: &Profile, rows: Vec<ExportedActivity>) -> Result<usize> {
+    let mut store = load_store()?;
+    let saved = store.entry(profile.key()).or_default();
+    let mut added = 0;
+
+    for row in rows {
+        let existing = saved.iter().position(|a| {
+            a.instance_id == row.instance_id && a.character_class == row.character_class
+        });
+
+        match existing {
+            Some(idx) => saved[idx] = row,
+            None => {
+                saved.push(row);
+                added += 1;
+            }
+        }
+    }
+
+    let path = data_path(IMPORTED_HISTORY_FILE)?;
+
+    if let Some(dir) = path.parent() {
+        create_dir_all(dir)?;
+    }
+
+    write(path, serde_json::to_string(&store)?)?;
+
+    Ok(added)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::to_csv;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 10, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn parses_seconds_and_clock_durations() {
+        assert_eq!(parse_duration("2712").unwrap(), 2712);
+        assert_eq!(parse_duration(" 2712.9 ").unwrap(), 2712);
+        assert_eq!(parse_duration("-5").unwrap(), 0);
+        assert_eq!(parse_duration("45:12").unwrap(), 2712);
+        assert_eq!(parse_duration("1:00:00").unwrap(), 3600);
+        assert_eq!(
+            parse_duration("24:00:00").unwrap(),
+            MAX_ACTIVITY_DURATION_SECS
+        );
+    }
+
+    #[test]
+    fn rejects_durations_that_are_not_sane() {
+        for value in [
+            "inf",
+            "-inf",
+            "NaN",
+            "1e13",
+            "86401",
+            "24:00:01",
+            "99999999999:00",
+            "1:xx",
+        ] {
+            assert!(parse_duration(value).is_err(), "{value} was accepted");
+        }
+    }
+
+    #[test]
+    fn reports_the_row_with_an_invalid_duration() {
+        let csv = "instance_id,activity_hash,start,duration\n\
+                   1,3881495763,2026-03-10T18:00:00Z,2712\n\
+                   2,3881495763,2026-03-10T19:00:00Z,1e13\n";
+
+        let error = from_csv(csv, &CsvMapping::default()).unwrap_err();
+
+        assert!(error.to_string().starts_with("Row 3:"), "{error}");
+    }
+
+    #[test]
+    fn rejects_json_rows_longer_than_a_day() {
+        let row = |duration_seconds| ExportedActivity {
+            instance_id: "1".to_string(),
+            activity_hash: 3881495763,
+            activity_name: String::new(),
+            character_class: None,
+            start: Utc.with_ymd_and_hms(2026, 3, 10, 18, 0, 0).unwrap(),
+            duration_seconds,
+            completed: true,
+            failed: false,
+            modes: vec![],
+            start_seconds: 0,
+        };
+
+        assert!(check_durations(&[row(2712), row(MAX_ACTIVITY_DURATION_SECS)]).is_ok());
+        assert!(check_durations(&[row(2712), row(usize::MAX)]).is_err());
+    }
+
+    #[test]
+    fn skips_a_byte_order_mark() {
+        let csv = "\u{feff}instance_id,activity_hash,start\n\
+                   1,3881495763,2026-03-10T18:00:00Z\n";
+
+        let rows = from_csv(csv, &CsvMapping::default()).unwrap();
+
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].instance_id, "1");
+        assert_eq!(rows[0].start, at(18, 0));
+        assert!(rows[0].completed);
+        assert!(!rows[0].failed);
+        assert_eq!(rows[0].duration_seconds, 0);
+    }
+
+    #[test]
+    fn reads_quoted_fields() {
+        let csv = "instance_id,activity_hash,character_class,notes,start\n\
+                   1,3881495763,\"Hunter, \"\"main\"\"\",\"two\nlines\",2026-03-10T18:00:00Z\n";
+
+        let rows = from_csv(csv, &CsvMapping::default()).unwrap();
+
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].character_class.as_deref(), Some("Hunter, \"main\""));
+        assert_eq!(rows[0].start, at(18, 0));
+    }
+
+    #[test]
+    fn reads_crlf_line_endings() {
+        let csv = "instance_id,activity_hash,start\r\n\
+                   1,3881495763,2026-03-10T18:00:00Z\r\n\
+                   2,3881495763,2026-03-10T19:00:00Z\r\n\r\n";
+
+        let rows = from_csv(csv, &CsvMapping::default()).unwrap();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1].instance_id, "2");
+        assert_eq!(rows[1].start, at(19, 0));
+    }
+
+    #[test]
+    fn finds_columns_by_their_aliases() {
+        let csv = "PGCR,Hash,Date,Class,Time,Cleared,Activity Modes,Start-Seconds\n\
+                   1,3881495763,2026-03-10 18:00,Warlock,45:12,no,4;82,10:00\n";
+
+        let rows = from_csv(csv, &CsvMapping::default()).unwrap();
+
+        assert_eq!(rows[0].instance_id, "1");
+        assert_eq!(rows[0].activity_hash, 3881495763);
+        assert_eq!(rows[0].start, at(18, 0));
+        assert_eq!(rows[0].character_class.as_deref(), Some("Warlock"));
+        assert_eq!(rows[0].duration_seconds, 2712);
+        assert!(!rows[0].completed);
+        assert_eq!(rows[0].modes, vec![4, 82]);
+        assert_eq!(rows[0].start_seconds, 600);
+    }
+
+    #[test]
+    fn uses_the_mapped_columns() {
+        let csv = "Run,Raid,When,Took,Duration\n\
+                   1,3881495763,2026-03-10T18:00:00Z,2712,1\n";
+        let mapping = CsvMapping {
+            instance_id: Some("Run".to_string()),
+            activity_hash: Some("Raid".to_string()),
+            start: Some("When".to_string()),
+            duration_seconds: Some("Took".to_string()),
+            ..Default::default()
+        };
+
+        let rows = from_csv(csv, &mapping).unwrap();
+
+        assert_eq!(rows[0].instance_id, "1");
+        assert_eq!(rows[0].duration_seconds, 2712);
+
+        let mapping = CsvMapping {
+            start: Some("Started".to_string()),
+            ..mapping
+        };
+        let error = from_csv(csv, &mapping).unwrap_err();
+        assert_eq!(error.to_string(), "Column started not found");
+    }
+
+    #[test]
+    fn imports_its_own_export() {
+        let rows = vec![
+            ExportedActivity {
+                instance_id: "1".to_string(),
+                activity_hash: 3881495763,
+                activity_name: String::new(),
+                character_class: Some("Hunter".to_string()),
+                start: at(18, 0),
+                duration_seconds: 2712,
+                completed: true,
+                failed: false,
+                modes: vec![4, 82],
+                start_seconds: 0,
+            },
+            ExportedActivity {
+                instance_id: "2".to_string(),
+                activity_hash: 1374392663,
+                activity_name: String::new(),
+                character_class: None,
+                start: at(19, 30),
+                duration_seconds: 1200,
+                completed: false,
+                failed: true,
+                modes: vec![4],
+                start_seconds: 600,
+            },
+        ];
+
+        let exported = to_csv(&rows);
+
+        assert!(exported.starts_with("instance_id,"));
+        assert_eq!(from_csv(&exported, &CsvMapping::default()).unwrap(), rows);
+        assert!(to_completed_activity(&rows[1]).failed);
+    }
+
+    #[test]
+    fn reads_back_exported_names_with_quotes_and_commas() {
+        let row = ExportedActivity {
+            instance_id: "1".to_string(),
+            activity_hash: 3881495763,
+            activity_name: "Vault of Glass, \"Master\"".to_string(),
+            character_class: Some("Hunter\r\nmain".to_string()),
+            start: at(18, 0),
+            duration_seconds: 2712,
+            completed: true,
+            failed: false,
+            modes: vec![4],
+            start_seconds: 0,
+        };
+
+        let parsed = parse_csv(&to_csv(&[row]));
+
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[1][2], "Vault of Glass, \"Master\"");
+        assert_eq!(parsed[1][3], "Hunter\r\nmain");
+        assert_eq!(parsed[1][6], "true");
+        assert_eq!(parsed[1][7], "false");
+    }
+}
@@ -0,0 +1,6 @@
+pub mod discord;
+pub mod files;
+pub mod livesplit;
+pub mod mqtt;
+pub mod snapshot;
+pub mod webhooks;
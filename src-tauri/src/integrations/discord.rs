@@ -0,0 +1,358 @@
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use tauri::{
+    async_runtime::{self, JoinHandle},
+    AppHandle, Manager,
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    sync::Mutex,
+    time::{sleep, timeout},
+};
+
+use crate::{
+    config::preferences::Preferences,
+    consts::{
+        BUNGIE_BASE_PATH, DISCORD_CLIENT_ID, DISCORD_IPC_TIMEOUT, DISCORD_RECONNECT_INTERVAL,
+        DISCORD_UPDATE_INTERVAL,
+    },
+    discord_ipc::{connect, read_frame, write_frame, IpcStream, Opcode},
+    filter::HistoryFilter,
+    pollers::playerdata::PlayerData,
+    stats::compute_stats,
+    ClockContainer, PlayerDataPollerContainer,
+};
+
+#[derive(Default)]
+pub struct DiscordPresenceHandle(Mutex<Option<JoinHandle<()>>>);
+
+#[derive(Clone)]
+struct PresenceOptions {
+    show_timer: bool,
+    show_clears: bool,
+    show_image: bool,
+}
+
+/// Builds without a Discord client id can't show a presence, so the preference
+/// is hidden for them.
+#[tauri::command]
+pub fn discord_presence_available() -> bool {
+    DISCORD_CLIENT_ID.is_some()
+}
+
+pub async fn restart_discord_presence(handle: &AppHandle, preferences: &Preferences) {
+    let container = handle.state::<DiscordPresenceHandle>();
+    let mut lock = container.0.lock().await;
+
+    if let Some(h) = lock.take() {
+        h.abort();
+    }
+
+    if !preferences.enable_discord_presence {
+        return;
+    }
+
+    let client_id = match DISCORD_CLIENT_ID {
+        Some(c) => c,
+        None => {
+            eprintln!("Discord presence is unavailable, this build has no Discord client id");
+            return;
+        }
+    };
+
+    let options = PresenceOptions {
+        show_timer: preferences.discord_show_timer,
+        show_clears: preferences.discord_show_clears,
+        show_image: preferences.discord_show_image,
+    };
+    let handle = handle.clone();
+
+    *lock = Some(async_runtime::spawn(async move {
+        discord_presence(handle, client_id, options).await
+    }));
+}
+
+struct DiscordConnection<S = IpcStream> {
+    stream: S,
+    nonce: u64,
+}
+
+impl DiscordConnection {
+    async fn open(client_id: &str) -> Result<Self> {
+        Self::handshake(connect().await?, client_id).await
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> DiscordConnection<S> {
+    async fn handshake(mut stream: S, client_id: &str) -> Result<Self> {
+        write_frame(
+            &mut stream,
+            Opcode::Handshake,
+            &json!({ "v": 1, "client_id": client_id }),
+        )
+        .await?;
+
+        let reply = timeout(DISCORD_IPC_TIMEOUT, read_frame(&mut stream))
+            .await
+            .map_err(|_| anyhow!("Discord did not answer the handshake"))??;
+
+        match reply {
+            (Opcode::Frame, payload) if payload["evt"] == "READY" => Ok(Self { stream, nonce: 0 }),
+            (_, payload) => Err(anyhow!(
+                "Discord refused the handshake: {}",
+                payload["message"]
+            )),
+        }
+    }
+
+    /// Sets the activity, or clears it when `activity` is `None`.
+    async fn set_activity(&mut self, activity: Option<&Value>) -> Result<()> {
+        self.nonce += 1;
+
+        let command = json!({
+            "cmd": "SET_ACTIVITY",
+            "args": {
+                "pid": std::process::id(),
+                "activity": activity,
+            },
+            "nonce": self.nonce.to_string(),
+        });
+
+        write_frame(&mut self.stream, Opcode::Frame, &command).await?;
+
+        timeout(DISCORD_IPC_TIMEOUT, self.wait_for_reply(&command["nonce"]))
+            .await
+            .map_err(|_| anyhow!("Discord did not answer the activity update"))?
+    }
+
+    /// Answers pings until the reply to the command with `nonce` arrives.
+    async fn wait_for_reply(&mut self, nonce: &Value) -> Result<()> {
+        loop {
+            match read_frame(&mut self.stream).await? {
+                (Opcode::Ping, payload) => {
+                    write_frame(&mut self.stream, Opcode::Pong, &payload).await?
+                }
+                (Opcode::Close, payload) => bail!("Discord closed the connection: {}", payload),
+                (Opcode::Frame, payload) if payload["evt"] == "ERROR" => {
+                    bail!(
+                        "Discord rejected the activity: {}",
+                        payload["data"]["message"]
+                    )
+                }
+                (Opcode::Frame, payload) if &payload["nonce"] == nonce => return Ok(()),
+                _ => (),
+            }
+        }
+    }
+}
+
+fn build_activity(
+    playerdata: &PlayerData,
+    options: &PresenceOptions,
+    session_start: DateTime<Utc>,
+) -> Option<Value> {
+    let current = &playerdata.current_activity;
+
+    if current.activity_hash == 0 {
+        return None;
+    }
+
+    let info = current.activity_info.as_ref()?;
+    let mut activity = json!({ "details": info.name });
+
+    if options.show_clears {
+        let session = HistoryFilter {
+            since: Some(session_start),
+            ..HistoryFilter::default()
+        };
+        let clears = compute_stats(&playerdata.activity_history, &session)
+            .overall
+            .clears;
+
+        activity["state"] = json!(format!(
+            "{} {} this session",
+            clears,
+            if clears == 1 { "clear" } else { "clears" }
+        ));
+    }
+
+    if options.show_timer {
+        activity["timestamps"] = json!({ "start": current.start_date.timestamp() });
+    }
+
+    if options.show_image {
+        if let Some(ref image) = info.background_image {
+            activity["assets"] = json!({
+                "large_image": format!("{BUNGIE_BASE_PATH}{image}"),
+                "large_text": info.name,
+            });
+        }
+    }
+
+    Some(activity)
+}
+
+async fn discord_presence(handle: AppHandle, client_id: &str, options: PresenceOptions) {
+    let session_start = handle.state::<ClockContainer>().0.now();
+    let mut connection: Option<DiscordConnection> = None;
+    let mut last_sent: Option<Option<Value>> = None;
+
+    loop {
+        if connection.is_none() {
+            match DiscordConnection::open(client_id).await {
+                Ok(c) => {
+                    connection = Some(c);
+                    last_sent = None;
+                }
+                Err(_) => {
+                    sleep(DISCORD_RECONNECT_INTERVAL).await;
+                    continue;
+                }
+            }
+        }
+
+        let status = {
+            let container = handle.state::<PlayerDataPollerContainer>();
+            let mut lock = container.0.lock().await;
+            lock.get_data()
+        };
+
+        // The poller is mid-update, try again on the next round.
+        let status = match status {
+            Some(s) => s,
+            None => {
+                sleep(DISCORD_UPDATE_INTERVAL).await;
+                continue;
+            }
+        };
+
+        let activity = status
+            .last_update
+            .as_ref()
+            .and_then(|p| build_activity(p, &options, session_start));
+
+        if last_sent.as_ref() != Some(&activity) {
+            let result = match connection.as_mut() {
+                Some(c) => c.set_activity(activity.as_ref()).await,
+                None => continue,
+            };
+
+            if let Err(e) = result {
+                eprintln!("Lost connection to Discord: {}", e);
+                connection = None;
+                continue;
+            }
+
+            last_sent = Some(activity);
+        }
+
+        sleep(DISCORD_UPDATE_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{duplex, DuplexStream};
+
+    use super::*;
+
+    /// Plays Discord's side of the handshake and returns the other end.
+    async fn fake_discord() -> (DiscordConnection<DuplexStream>, DuplexStream) {
+        let (client, mut discord) = duplex(4096);
+
+        let fake = tokio::spawn(async move {
+            let (opcode, handshake) = read_frame(&mut discord).await.unwrap();
+            assert_eq!(opcode, Opcode::Handshake);
+            assert_eq!(handshake, json!({ "v": 1, "client_id": "1234" }));
+
+            write_frame(
+                &mut discord,
+                Opcode::Frame,
+                &json!({ "cmd": "DISPATCH", "evt": "READY" }),
+            )
+            .await
+            .unwrap();
+
+            discord
+        });
+
+        let connection = DiscordConnection::handshake(client, "1234").await.unwrap();
+
+        (connection, fake.await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn sets_the_activity_over_ipc() {
+        let (mut connection, mut discord) = fake_discord().await;
+
+        let fake = tokio::spawn(async move {
+            let (opcode, command) = read_frame(&mut discord).await.unwrap();
+            assert_eq!(opcode, Opcode::Frame);
+
+            write_frame(&mut discord, Opcode::Ping, &json!({ "ping": 1 }))
+                .await
+                .unwrap();
+            assert_eq!(
+                read_frame(&mut discord).await.unwrap(),
+                (Opcode::Pong, json!({ "ping": 1 }))
+            );
+
+            let reply = json!({ "cmd": "SET_ACTIVITY", "evt": null, "nonce": command["nonce"] });
+            write_frame(&mut discord, Opcode::Frame, &reply)
+                .await
+                .unwrap();
+
+            command
+        });
+
+        let activity = json!({ "details": "Vault of Glass" });
+        connection.set_activity(Some(&activity)).await.unwrap();
+
+        let command = fake.await.unwrap();
+        assert_eq!(command["cmd"], "SET_ACTIVITY");
+        assert_eq!(command["args"]["activity"], activity);
+        assert_eq!(command["nonce"], "1");
+    }
+
+    #[tokio::test]
+    async fn reports_a_rejected_activity() {
+        let (mut connection, mut discord) = fake_discord().await;
+
+        tokio::spawn(async move {
+            let (_, command) = read_frame(&mut discord).await.unwrap();
+            let reply = json!({
+                "evt": "ERROR",
+                "nonce": command["nonce"],
+                "data": { "message": "Invalid activity" },
+            });
+
+            write_frame(&mut discord, Opcode::Frame, &reply)
+                .await
+                .unwrap();
+            discord
+        });
+
+        let error = connection.set_activity(None).await.unwrap_err();
+
+        assert!(error.to_string().contains("Invalid activity"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn gives_up_when_discord_stops_answering() {
+        let (mut connection, _discord) = fake_discord().await;
+
+        let error = connection.set_activity(None).await.unwrap_err();
+
+        assert!(error.to_string().contains("did not answer"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn gives_up_on_a_silent_handshake() {
+        let (client, _discord) = duplex(4096);
+
+        let result = DiscordConnection::handshake(client, "1234").await;
+
+        assert!(result.is_err());
+    }
+}
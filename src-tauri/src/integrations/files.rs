@@ -0,0 +1,237 @@
+use std::{
+    collections::HashMap,
+    fs::{create_dir_all, rename, write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use tauri::{
+    async_runtime::{self, JoinHandle},
+    AppHandle, Manager,
+};
+use tokio::{sync::Mutex, time::interval};
+
+use super::snapshot::{Snapshot, SnapshotSource};
+use crate::{
+    api::responses::format_duration, config::preferences::Preferences, consts::FILE_OUTPUT_INTERVAL,
+};
+
+const ACTIVITY_FILE: &str = "activity.txt";
+const TIMER_FILE: &str = "timer.txt";
+const CLEARS_TODAY_FILE: &str = "clears_today.txt";
+const AVERAGE_CLEAR_TIME_FILE: &str = "average_clear_time.txt";
+const NOW_PLAYING_FILE: &str = "now_playing.txt";
+
+#[derive(Default)]
+pub struct FileOutputHandle(Mutex<Option<JoinHandle<()>>>);
+
+pub async fn restart_file_output(handle: &AppHandle, preferences: &Preferences) {
+    let container = handle.state::<FileOutputHandle>();
+    let mut lock = container.0.lock().await;
+
+    if let Some(h) = lock.take() {
+        h.abort();
+    }
+
+    if !preferences.enable_file_output || preferences.file_output_dir.is_empty() {
+        return;
+    }
+
+    let dir = PathBuf::from(&preferences.file_output_dir);
+    let handle = handle.clone();
+
+    *lock = Some(async_runtime::spawn(async move {
+        if let Err(e) = file_output(handle, dir).await {
+            eprintln!("File output stopped: {}", e);
+        }
+    }));
+}
+
+/// Writes to a temporary file first, so readers never see a half-written file.
+fn write_atomic(path: &Path, contents: &str) -> Result<()> {
+    let tmp = path.with_extension("tmp");
+
+    write(&tmp, contents)?;
+    rename(&tmp, path)?;
+
+    Ok(())
+}
+
+fn render(snapshot: &Snapshot) -> Vec<(&'static str, String)> {
+    vec![
+        (ACTIVITY_FILE, snapshot.activity.clone().unwrap_or_default()),
+        (TIMER_FILE, snapshot.timer_text.clone()),
+        (CLEARS_TODAY_FILE, snapshot.clears_today.to_string()),
+        (
+            AVERAGE_CLEAR_TIME_FILE,
+            snapshot
+                .average_clear_seconds
+                .map(format_duration)
+                .unwrap_or_default(),
+        ),
+        (NOW_PLAYING_FILE, snapshot.now_playing.clone()),
+    ]
+}
+
+async fn file_output(handle: AppHandle, dir: PathBuf) -> Result<()> {
+    create_dir_all(&dir)?;
+
+    let mut source = SnapshotSource::new(&handle).await;
+    let mut ticker = interval(FILE_OUTPUT_INTERVAL);
+    let mut written: HashMap<&'static str, String> = HashMap::new();
+
+    loop {
+        ticker.tick().await;
+
+        let outputs = render(&source.take().await);
+
+        for (filename, contents) in outputs {
+            if written.get(filename) == Some(&contents) {
+                continue;
+            }
+
+            if let Err(e) = write_atomic(&dir.join(filename), &contents) {
+                eprintln!("Failed to write {}: {}", filename, e);
+                continue;
+            }
+
+            written.insert(filename, contents);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::read_to_string, process};
+
+    use chrono::{DateTime, TimeZone, Utc};
+
+    use super::*;
+    use crate::{
+        api::responses::{ActivityInfo, CompletedActivity, ProfileInfo},
+        consts::RAID_ACTIVITY_MODE,
+        pollers::{
+            media::MediaInfo,
+            playerdata::{CurrentActivity, PlayerData},
+        },
+    };
+
+    const RAID: usize = 3881495763;
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, day, hour, 0, 0).unwrap()
+    }
+
+    fn raid(period: DateTime<Utc>, seconds: usize) -> CompletedActivity {
+        CompletedActivity {
+            period,
+            instance_id: period.timestamp().to_string(),
+            activity_hash: RAID,
+            modes: vec![RAID_ACTIVITY_MODE],
+            completed: true,
+            failed: false,
+            activity_duration: format_duration(seconds),
+            activity_duration_seconds: seconds,
+            start_seconds: 0,
+            character_class: Some("Hunter".to_string()),
+        }
+    }
+
+    fn playerdata(activity_hash: usize) -> PlayerData {
+        PlayerData {
+            current_activity: CurrentActivity {
+                start_date: at(10, 19),
+                activity_hash,
+                activity_info: Some(ActivityInfo {
+                    name: "Vault of Glass".to_string(),
+                    activity_modes: vec![RAID_ACTIVITY_MODE],
+                    background_image: None,
+                }),
+            },
+            activity_history: vec![raid(at(10, 18), 1800), raid(at(10, 16), 2400)],
+            profile_info: ProfileInfo {
+                privacy: 1,
+                display_name: "Guardian".to_string(),
+                display_tag: 1234,
+                character_ids: vec![],
+            },
+            characters: vec![],
+        }
+    }
+
+    fn file<'a>(outputs: &'a [(&'static str, String)], filename: &str) -> &'a str {
+        outputs
+            .iter()
+            .find(|(f, _)| *f == filename)
+            .map(|(_, contents)| contents.as_str())
+            .unwrap()
+    }
+
+    #[test]
+    fn renders_todays_clears_around_the_reset() {
+        let mut playerdata = playerdata(RAID);
+        let last_clear = playerdata.activity_history.remove(0);
+
+        let before_reset = render(&Snapshot::new(
+            Some(&playerdata),
+            None,
+            at(10, 17) - chrono::Duration::milliseconds(1),
+        ));
+        assert_eq!(file(&before_reset, CLEARS_TODAY_FILE), "1");
+        assert_eq!(
+            file(&before_reset, AVERAGE_CLEAR_TIME_FILE),
+            format_duration(2400)
+        );
+
+        playerdata.activity_history.insert(0, last_clear);
+
+        let after_reset = render(&Snapshot {
+            timer_text: "0:42:00".to_string(),
+            ..Snapshot::new(Some(&playerdata), None, at(10, 20))
+        });
+        assert_eq!(file(&after_reset, ACTIVITY_FILE), "Vault of Glass");
+        assert_eq!(file(&after_reset, TIMER_FILE), "0:42:00");
+        assert_eq!(file(&after_reset, CLEARS_TODAY_FILE), "1");
+        assert_eq!(
+            file(&after_reset, AVERAGE_CLEAR_TIME_FILE),
+            format_duration(1800)
+        );
+    }
+
+    #[test]
+    fn renders_empty_values_in_orbit() {
+        let media = MediaInfo {
+            title: "Hymn".to_string(),
+            artist: "Michael Salvatori".to_string(),
+            has_media: true,
+            ..MediaInfo::default()
+        };
+
+        let outputs = render(&Snapshot::new(
+            Some(&playerdata(0)),
+            Some(&media),
+            at(11, 18),
+        ));
+
+        assert_eq!(file(&outputs, ACTIVITY_FILE), "");
+        assert_eq!(file(&outputs, TIMER_FILE), "");
+        assert_eq!(file(&outputs, CLEARS_TODAY_FILE), "0");
+        assert_eq!(file(&outputs, AVERAGE_CLEAR_TIME_FILE), "");
+        assert_eq!(file(&outputs, NOW_PLAYING_FILE), "Hymn - Michael Salvatori");
+    }
+
+    #[test]
+    fn replaces_files_atomically() {
+        let dir = std::env::temp_dir().join(format!("groundsub-files-{}", process::id()));
+        create_dir_all(&dir).unwrap();
+        let path = dir.join(CLEARS_TODAY_FILE);
+
+        write_atomic(&path, "1").unwrap();
+        write_atomic(&path, "2").unwrap();
+
+        assert_eq!(read_to_string(&path).unwrap(), "2");
+        assert!(!path.with_extension("tmp").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
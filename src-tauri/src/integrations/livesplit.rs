@@ -0,0 +1,227 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
+use tauri::{
+    async_runtime::{self, JoinHandle},
+    AppHandle, Manager,
+};
+use tokio::{
+    sync::{
+        broadcast::{error::RecvError, Receiver},
+        Mutex,
+    },
+    time::{interval, sleep},
+};
+
+use crate::{
+    clock::Clock,
+    config::preferences::Preferences,
+    consts::{LIVESPLIT_RECONNECT_INTERVAL, LIVESPLIT_SYNC_INTERVAL},
+    events::{AppEvent, AppEvents},
+    livesplit_protocol::{LiveSplitClient, LiveSplitCommand},
+    pollers::timer::TimerLifecycle,
+    ClockContainer,
+};
+
+#[derive(Default)]
+pub struct LiveSplitHandle(Mutex<Option<JoinHandle<()>>>);
+
+pub async fn restart_livesplit(handle: &AppHandle, preferences: &Preferences) {
+    let container = handle.state::<LiveSplitHandle>();
+    let mut lock = container.0.lock().await;
+
+    if let Some(h) = lock.take() {
+        h.abort();
+    }
+
+    if !preferences.enable_livesplit {
+        return;
+    }
+
+    let host = preferences.livesplit_host.clone();
+    let port = preferences.livesplit_port;
+    let handle = handle.clone();
+
+    *lock = Some(async_runtime::spawn(async move {
+        livesplit(handle, host, port).await;
+    }));
+}
+
+fn commands_for(lifecycle: TimerLifecycle, now: DateTime<Utc>) -> Vec<LiveSplitCommand> {
+    match lifecycle {
+        TimerLifecycle::Started { start_date } => vec![
+            LiveSplitCommand::Reset,
+            LiveSplitCommand::StartTimer,
+            LiveSplitCommand::InitGameTime,
+            LiveSplitCommand::SetGameTime(game_time(start_date, now)),
+        ],
+        TimerLifecycle::Split => vec![LiveSplitCommand::Split],
+        TimerLifecycle::Completed { duration_seconds } => vec![
+            LiveSplitCommand::SetGameTime(Duration::from_secs(duration_seconds as u64)),
+            LiveSplitCommand::Split,
+        ],
+        TimerLifecycle::Stopped => vec![LiveSplitCommand::Pause],
+        TimerLifecycle::Reset => vec![LiveSplitCommand::Reset],
+    }
+}
+
+/// Game time follows the activity start reported by Bungie rather than when
+/// groundsub noticed it, so it matches the in-game clock.
+fn game_time(start_date: DateTime<Utc>, now: DateTime<Utc>) -> Duration {
+    (now - start_date).to_std().unwrap_or_default()
+}
+
+async fn livesplit(handle: AppHandle, host: String, port: u16) {
+    let clock = handle.state::<ClockContainer>().0.clone();
+    let receiver = handle.state::<AppEvents>().subscribe();
+
+    mirror_timer(clock, receiver, host, port).await;
+}
+
+/// Replays the timer lifecycle events on LiveSplit, reconnecting whenever the
+/// connection drops.
+async fn mirror_timer(
+    clock: Arc<dyn Clock>,
+    mut receiver: Receiver<AppEvent>,
+    host: String,
+    port: u16,
+) {
+    let mut sync = interval(LIVESPLIT_SYNC_INTERVAL);
+    let mut client: Option<LiveSplitClient> = None;
+    let mut running_since: Option<DateTime<Utc>> = None;
+
+    loop {
+        if client.is_none() {
+            match LiveSplitClient::connect(&host, port).await {
+                Ok(c) => client = Some(c),
+                Err(_) => {
+                    sleep(LIVESPLIT_RECONNECT_INTERVAL).await;
+                    continue;
+                }
+            }
+        }
+
+        let commands = tokio::select! {
+            event = receiver.recv() => {
+                let event = match event {
+                    Ok(e) if e.event == "timer_lifecycle" => e,
+                    Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return,
+                };
+
+                let lifecycle: TimerLifecycle = match serde_json::from_value(event.payload) {
+                    Ok(l) => l,
+                    Err(_) => continue,
+                };
+
+                running_since = match lifecycle {
+                    TimerLifecycle::Started { start_date } => Some(start_date),
+                    TimerLifecycle::Split => running_since,
+                    _ => None,
+                };
+
+                commands_for(lifecycle, clock.now())
+            }
+            _ = sync.tick() => match running_since {
+                Some(start_date) => {
+                    vec![LiveSplitCommand::SetGameTime(game_time(start_date, clock.now()))]
+                }
+                None => continue,
+            },
+        };
+
+        let connection = match client.as_mut() {
+            Some(c) => c,
+            None => continue,
+        };
+
+        for command in commands.iter() {
+            if let Err(e) = connection.send(command).await {
+                eprintln!("Lost connection to LiveSplit on {}:{}: {}", host, port, e);
+                client = None;
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use tokio::{
+        io::{AsyncBufReadExt, BufReader, Lines},
+        net::{tcp::OwnedReadHalf, TcpListener},
+        sync::broadcast,
+    };
+
+    use super::*;
+    use crate::clock::SimulatedClock;
+
+    fn lifecycle(lifecycle: TimerLifecycle) -> AppEvent {
+        AppEvent {
+            event: "timer_lifecycle".to_string(),
+            payload: serde_json::to_value(lifecycle).unwrap(),
+        }
+    }
+
+    async fn expect_lines(lines: &mut Lines<BufReader<OwnedReadHalf>>, expected: &[&str]) {
+        for line in expected {
+            assert_eq!(lines.next_line().await.unwrap().as_deref(), Some(*line));
+        }
+    }
+
+    #[tokio::test]
+    async fn mirrors_the_timer_lifecycle() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let start_date = Utc.with_ymd_and_hms(2026, 3, 10, 19, 40, 0).unwrap();
+        let clock = Arc::new(SimulatedClock::new(
+            Utc.with_ymd_and_hms(2026, 3, 10, 20, 0, 0).unwrap(),
+        ));
+        let (sender, receiver) = broadcast::channel(16);
+
+        let task = tokio::spawn(mirror_timer(clock, receiver, "127.0.0.1".to_string(), port));
+
+        let (stream, _) = listener.accept().await.unwrap();
+        let (read, _write) = stream.into_split();
+        let mut lines = BufReader::new(read).lines();
+
+        sender
+            .send(AppEvent {
+                event: "timer-state-update".to_string(),
+                payload: serde_json::json!({ "timeText": "20:00" }),
+            })
+            .unwrap();
+        sender
+            .send(lifecycle(TimerLifecycle::Started { start_date }))
+            .unwrap();
+        expect_lines(
+            &mut lines,
+            &[
+                "reset",
+                "starttimer",
+                "initgametime",
+                "setgametime 0:20:00.00",
+            ],
+        )
+        .await;
+
+        sender.send(lifecycle(TimerLifecycle::Split)).unwrap();
+        expect_lines(&mut lines, &["split"]).await;
+
+        sender
+            .send(lifecycle(TimerLifecycle::Completed {
+                duration_seconds: 2712,
+            }))
+            .unwrap();
+        expect_lines(&mut lines, &["setgametime 0:45:12.00", "split"]).await;
+
+        sender.send(lifecycle(TimerLifecycle::Stopped)).unwrap();
+        sender.send(lifecycle(TimerLifecycle::Reset)).unwrap();
+        expect_lines(&mut lines, &["pause", "reset"]).await;
+
+        drop(sender);
+        task.await.unwrap();
+    }
+}
@@ -0,0 +1,397 @@
+use std::collections::HashMap;
+
+use rumqttc::{AsyncClient, Event, EventLoop, LastWill, MqttOptions, Outgoing, Packet, QoS};
+use tauri::{
+    async_runtime::{self, JoinHandle},
+    AppHandle, Manager,
+};
+use tokio::{
+    sync::{oneshot, Mutex},
+    time::{interval, sleep, timeout},
+};
+
+use super::snapshot::{Snapshot, SnapshotSource};
+use crate::{
+    config::preferences::Preferences,
+    consts::{
+        MQTT_CLIENT_ID, MQTT_KEEP_ALIVE, MQTT_PUBLISH_INTERVAL, MQTT_RECONNECT_INTERVAL,
+        MQTT_SHUTDOWN_TIMEOUT,
+    },
+};
+
+const STATUS_TOPIC: &str = "status";
+const IN_ACTIVITY_TOPIC: &str = "in_activity";
+const ACTIVITY_TOPIC: &str = "activity";
+const TIMER_SECONDS_TOPIC: &str = "timer_seconds";
+const CLEARS_TODAY_TOPIC: &str = "clears_today";
+const NOW_PLAYING_TOPIC: &str = "now_playing";
+
+#[derive(Default)]
+pub struct MqttHandle(Mutex<Option<MqttTask>>);
+
+struct MqttTask {
+    task: JoinHandle<()>,
+    shutdown: oneshot::Sender<()>,
+}
+
+impl MqttTask {
+    /// Lets the task publish `offline` and disconnect cleanly, so the broker
+    /// doesn't have to wait out the keep alive before sending the last will.
+    async fn stop(mut self) {
+        let _ = self.shutdown.send(());
+
+        if timeout(MQTT_SHUTDOWN_TIMEOUT, &mut self.task)
+            .await
+            .is_err()
+        {
+            self.task.abort();
+        }
+    }
+}
+
+pub async fn restart_mqtt(handle: &AppHandle, preferences: &Preferences) {
+    let container = handle.state::<MqttHandle>();
+    let mut lock = container.0.lock().await;
+
+    if let Some(t) = lock.take() {
+        t.stop().await;
+    }
+
+    if !preferences.enable_mqtt || preferences.mqtt_host.is_empty() {
+        return;
+    }
+
+    let prefix = preferences
+        .mqtt_topic_prefix
+        .trim_end_matches('/')
+        .to_string();
+
+    let options = mqtt_options(preferences, &prefix);
+
+    let handle = handle.clone();
+    let (shutdown, shutdown_receiver) = oneshot::channel();
+
+    *lock = Some(MqttTask {
+        task: async_runtime::spawn(async move {
+            mqtt(handle, options, prefix, shutdown_receiver).await;
+        }),
+        shutdown,
+    });
+}
+
+/// Asks the broker to publish `offline` as our status if we go away without
+/// disconnecting.
+fn mqtt_options(preferences: &Preferences, prefix: &str) -> MqttOptions {
+    let mut options = MqttOptions::new(
+        MQTT_CLIENT_ID,
+        preferences.mqtt_host.clone(),
+        preferences.mqtt_port,
+    );
+    options.set_keep_alive(MQTT_KEEP_ALIVE);
+    options.set_last_will(LastWill::new(
+        format!("{prefix}/{STATUS_TOPIC}"),
+        "offline",
+        QoS::AtLeastOnce,
+        true,
+    ));
+
+    if !preferences.mqtt_username.is_empty() {
+        options.set_credentials(
+            preferences.mqtt_username.clone(),
+            preferences.mqtt_password.clone(),
+        );
+    }
+
+    options
+}
+
+fn render(snapshot: &Snapshot) -> Vec<(&'static str, String)> {
+    vec![
+        (STATUS_TOPIC, "online".to_string()),
+        (IN_ACTIVITY_TOPIC, snapshot.activity.is_some().to_string()),
+        (
+            ACTIVITY_TOPIC,
+            snapshot.activity.clone().unwrap_or_default(),
+        ),
+        (
+            TIMER_SECONDS_TOPIC,
+            snapshot
+                .timer_seconds
+                .map(|s| s.to_string())
+                .unwrap_or_default(),
+        ),
+        (CLEARS_TODAY_TOPIC, snapshot.clears_today.to_string()),
+        (NOW_PLAYING_TOPIC, snapshot.now_playing.clone()),
+    ]
+}
+
+/// Publishes the outputs that changed since they were last published,
+/// retained.
+fn publish_changes(
+    client: &AsyncClient,
+    prefix: &str,
+    outputs: Vec<(&'static str, String)>,
+    published: &mut HashMap<&'static str, String>,
+) {
+    for (topic, payload) in outputs {
+        if published.get(topic) == Some(&payload) {
+            continue;
+        }
+
+        let result = client.try_publish(
+            format!("{prefix}/{topic}"),
+            QoS::AtLeastOnce,
+            true,
+            payload.clone(),
+        );
+
+        if result.is_ok() {
+            published.insert(topic, payload);
+        }
+    }
+}
+
+/// Publishes `offline` and disconnects, then drives the event loop until the
+/// disconnect has gone out.
+async fn go_offline(client: &AsyncClient, eventloop: &mut EventLoop, prefix: &str) {
+    let published = client
+        .publish(
+            format!("{prefix}/{STATUS_TOPIC}"),
+            QoS::AtLeastOnce,
+            true,
+            "offline",
+        )
+        .await;
+
+    if published.is_err() || client.disconnect().await.is_err() {
+        return;
+    }
+
+    while let Ok(event) = eventloop.poll().await {
+        if let Event::Outgoing(Outgoing::Disconnect) = event {
+            return;
+        }
+    }
+}
+
+/// Everything is published retained, and only when it changes, so dashboards
+/// that subscribe late still get the current state. The broker publishes the
+/// last will as `offline` if groundsub goes away without disconnecting.
+async fn mqtt(
+    handle: AppHandle,
+    options: MqttOptions,
+    prefix: String,
+    mut shutdown: oneshot::Receiver<()>,
+) {
+    let (client, mut eventloop) = AsyncClient::new(options, 16);
+    let mut source = SnapshotSource::new(&handle).await;
+    let mut ticker = interval(MQTT_PUBLISH_INTERVAL);
+
+    let mut published: HashMap<&'static str, String> = HashMap::new();
+    let mut connected = false;
+
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => {
+                if connected {
+                    go_offline(&client, &mut eventloop, &prefix).await;
+                }
+
+                return;
+            }
+            event = eventloop.poll() => match event {
+                Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                    // Republish everything, the broker has sent our last will
+                    // if this is a reconnect.
+                    connected = true;
+                    published.clear();
+                }
+                Ok(_) => (),
+                Err(e) => {
+                    if connected {
+                        eprintln!("Lost connection to MQTT broker: {}", e);
+                    }
+
+                    connected = false;
+                    sleep(MQTT_RECONNECT_INTERVAL).await;
+                }
+            },
+            _ = ticker.tick(), if connected => {
+                let outputs = render(&source.take().await);
+
+                publish_changes(&client, &prefix, outputs, &mut published);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::BytesMut;
+    use rumqttc::{
+        mqttbytes::{self, v4},
+        ConnAck, ConnectReturnCode,
+    };
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use super::*;
+
+    /// Accepts one client, acknowledges its connection and collects what it
+    /// sends until it disconnects.
+    async fn broker(listener: TcpListener) -> Vec<Packet> {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut buffer = BytesMut::new();
+        let mut packets = vec![];
+
+        loop {
+            match v4::read(&mut buffer, 1024) {
+                Ok(Packet::Disconnect) => {
+                    packets.push(Packet::Disconnect);
+                    return packets;
+                }
+                Ok(packet) => {
+                    if let Packet::Connect(_) = packet {
+                        let mut connack = BytesMut::new();
+                        ConnAck::new(ConnectReturnCode::Success, false)
+                            .write(&mut connack)
+                            .unwrap();
+                        socket.write_all(&connack).await.unwrap();
+                    }
+
+                    packets.push(packet);
+                }
+                Err(mqttbytes::Error::InsufficientBytes(_)) => {
+                    if socket.read_buf(&mut buffer).await.unwrap() == 0 {
+                        return packets;
+                    }
+                }
+                Err(e) => panic!("Broker failed to read a packet: {:?}", e),
+            }
+        }
+    }
+
+    fn retained(packets: &[Packet]) -> Vec<(String, String)> {
+        packets
+            .iter()
+            .filter_map(|p| match p {
+                Packet::Publish(p) if p.retain && p.qos == QoS::AtLeastOnce => Some((
+                    p.topic.clone(),
+                    String::from_utf8(p.payload.to_vec()).unwrap(),
+                )),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn topic<'a>(outputs: &'a [(&'static str, String)], topic: &str) -> &'a str {
+        outputs
+            .iter()
+            .find(|(t, _)| *t == topic)
+            .map(|(_, payload)| payload.as_str())
+            .unwrap()
+    }
+
+    #[test]
+    fn renders_the_current_state() {
+        let outputs = render(&Snapshot {
+            activity: Some("Vault of Glass".to_string()),
+            timer_text: "1:00:00".to_string(),
+            timer_seconds: Some(3600),
+            clears_today: 1,
+            average_clear_seconds: Some(1800),
+            now_playing: "Hymn - Michael Salvatori".to_string(),
+        });
+
+        assert_eq!(topic(&outputs, STATUS_TOPIC), "online");
+        assert_eq!(topic(&outputs, IN_ACTIVITY_TOPIC), "true");
+        assert_eq!(topic(&outputs, ACTIVITY_TOPIC), "Vault of Glass");
+        assert_eq!(topic(&outputs, TIMER_SECONDS_TOPIC), "3600");
+        assert_eq!(topic(&outputs, CLEARS_TODAY_TOPIC), "1");
+        assert_eq!(
+            topic(&outputs, NOW_PLAYING_TOPIC),
+            "Hymn - Michael Salvatori"
+        );
+    }
+
+    #[test]
+    fn renders_empty_values_in_orbit() {
+        let outputs = render(&Snapshot::default());
+
+        assert_eq!(topic(&outputs, STATUS_TOPIC), "online");
+        assert_eq!(topic(&outputs, IN_ACTIVITY_TOPIC), "false");
+        assert_eq!(topic(&outputs, ACTIVITY_TOPIC), "");
+        assert_eq!(topic(&outputs, TIMER_SECONDS_TOPIC), "");
+        assert_eq!(topic(&outputs, CLEARS_TODAY_TOPIC), "0");
+        assert_eq!(topic(&outputs, NOW_PLAYING_TOPIC), "");
+    }
+
+    #[tokio::test]
+    async fn publishes_changes_retained_and_goes_offline() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let preferences = Preferences {
+            enable_mqtt: true,
+            mqtt_host: "127.0.0.1".to_string(),
+            mqtt_port: listener.local_addr().unwrap().port(),
+            ..Preferences::default()
+        };
+        let broker = tokio::spawn(broker(listener));
+
+        let (client, mut eventloop) = AsyncClient::new(mqtt_options(&preferences, "groundsub"), 16);
+
+        loop {
+            if let Event::Incoming(Packet::ConnAck(_)) = eventloop.poll().await.unwrap() {
+                break;
+            }
+        }
+
+        let mut published = HashMap::new();
+        let snapshot = Snapshot {
+            activity: Some("Vault of Glass".to_string()),
+            ..Snapshot::default()
+        };
+        publish_changes(&client, "groundsub", render(&snapshot), &mut published);
+
+        let snapshot = Snapshot {
+            clears_today: 1,
+            ..snapshot
+        };
+        publish_changes(&client, "groundsub", render(&snapshot), &mut published);
+
+        go_offline(&client, &mut eventloop, "groundsub").await;
+        let packets = broker.await.unwrap();
+
+        match packets.first() {
+            Some(Packet::Connect(connect)) => assert_eq!(
+                connect.last_will,
+                Some(LastWill::new(
+                    "groundsub/status",
+                    "offline",
+                    QoS::AtLeastOnce,
+                    true
+                ))
+            ),
+            p => panic!("Expected a connect, got {:?}", p),
+        }
+
+        let expected = [
+            ("groundsub/status", "online"),
+            ("groundsub/in_activity", "true"),
+            ("groundsub/activity", "Vault of Glass"),
+            ("groundsub/timer_seconds", ""),
+            ("groundsub/clears_today", "0"),
+            ("groundsub/now_playing", ""),
+            ("groundsub/clears_today", "1"),
+            ("groundsub/status", "offline"),
+        ];
+        let expected: Vec<(String, String)> = expected
+            .iter()
+            .map(|(t, p)| (t.to_string(), p.to_string()))
+            .collect();
+
+        assert_eq!(retained(&packets), expected);
+        assert_eq!(packets.last(), Some(&Packet::Disconnect));
+    }
+}
@@ -0,0 +1,241 @@
+use chrono::{DateTime, Utc};
+use tauri::{AppHandle, Manager};
+use tokio::sync::broadcast::{error::TryRecvError, Receiver};
+
+use crate::{
+    events::{AppEvent, AppEvents},
+    filter::HistoryFilter,
+    pollers::{
+        media::{get_current_media, MediaInfo},
+        playerdata::PlayerData,
+    },
+    stats::compute_stats,
+    timerange::last_daily_reset,
+    ClockContainer, PlayerDataPollerContainer, TimerPollerContainer,
+};
+
+/// The state the file output and MQTT integrations publish.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Snapshot {
+    /// The name of the current activity, `None` in orbit.
+    pub activity: Option<String>,
+    pub timer_text: String,
+    pub timer_seconds: Option<i64>,
+    pub clears_today: usize,
+    /// `None` until there is a clear since the daily reset.
+    pub average_clear_seconds: Option<usize>,
+    pub now_playing: String,
+}
+
+impl Snapshot {
+    /// Everything but the timer, which the caller fills in.
+    pub fn new(
+        playerdata: Option<&PlayerData>,
+        media: Option<&MediaInfo>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let activity = playerdata
+            .filter(|p| p.current_activity.activity_hash != 0)
+            .and_then(|p| p.current_activity.activity_info.as_ref())
+            .map(|a| a.name.clone());
+
+        let today = HistoryFilter {
+            since: Some(last_daily_reset(now)),
+            ..HistoryFilter::default()
+        };
+        let stats = playerdata
+            .map(|p| compute_stats(&p.activity_history, &today))
+            .unwrap_or_default();
+
+        let average_clear_seconds = if stats.overall.clears == 0 {
+            None
+        } else {
+            Some(stats.overall.average_clear_seconds)
+        };
+
+        let now_playing = match media {
+            Some(m) if m.has_media && m.artist.is_empty() => m.title.clone(),
+            Some(m) if m.has_media => format!("{} - {}", m.title, m.artist),
+            _ => String::new(),
+        };
+
+        Self {
+            activity,
+            timer_text: String::new(),
+            timer_seconds: None,
+            clears_today: stats.overall.clears,
+            average_clear_seconds,
+            now_playing,
+        }
+    }
+}
+
+/// Follows the media updates between ticks, and reads the rest of the state
+/// from the pollers when a snapshot is taken.
+pub struct SnapshotSource {
+    handle: AppHandle,
+    receiver: Receiver<AppEvent>,
+    media: Option<MediaInfo>,
+}
+
+impl SnapshotSource {
+    pub async fn new(handle: &AppHandle) -> Self {
+        Self {
+            handle: handle.clone(),
+            receiver: handle.state::<AppEvents>().subscribe(),
+            media: get_current_media().await.ok(),
+        }
+    }
+
+    pub async fn take(&mut self) -> Snapshot {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(e) if e.event == "media-update" => {
+                    self.media = serde_json::from_value(e.payload).ok();
+                }
+                Ok(_) | Err(TryRecvError::Lagged(_)) => (),
+                Err(TryRecvError::Empty) | Err(TryRecvError::Closed) => break,
+            }
+        }
+
+        let playerdata = {
+            let container = self.handle.state::<PlayerDataPollerContainer>();
+            let lock = container.0.lock().await;
+            lock.get_last_update().await
+        };
+
+        let (timer_state, timer_seconds) = {
+            let container = self.handle.state::<TimerPollerContainer>();
+            let lock = container.0.lock().await;
+            (lock.get_state().await, lock.get_elapsed_seconds().await)
+        };
+
+        let now = self.handle.state::<ClockContainer>().0.now();
+
+        Snapshot {
+            timer_text: timer_state.time_text,
+            timer_seconds,
+            ..Snapshot::new(playerdata.as_ref(), self.media.as_ref(), now)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::{
+        api::responses::{format_duration, ActivityInfo, CompletedActivity, ProfileInfo},
+        consts::RAID_ACTIVITY_MODE,
+        pollers::playerdata::CurrentActivity,
+    };
+
+    const RAID: usize = 3881495763;
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, day, hour, 0, 0).unwrap()
+    }
+
+    fn raid(period: DateTime<Utc>, completed: bool, seconds: usize) -> CompletedActivity {
+        CompletedActivity {
+            period,
+            instance_id: period.timestamp().to_string(),
+            activity_hash: RAID,
+            modes: vec![RAID_ACTIVITY_MODE],
+            completed,
+            failed: !completed,
+            activity_duration: format_duration(seconds),
+            activity_duration_seconds: seconds,
+            start_seconds: 0,
+            character_class: Some("Hunter".to_string()),
+        }
+    }
+
+    fn playerdata(activity_hash: usize, activity_history: Vec<CompletedActivity>) -> PlayerData {
+        PlayerData {
+            current_activity: CurrentActivity {
+                start_date: at(10, 19),
+                activity_hash,
+                activity_info: Some(ActivityInfo {
+                    name: "Vault of Glass".to_string(),
+                    activity_modes: vec![RAID_ACTIVITY_MODE],
+                    background_image: None,
+                }),
+            },
+            activity_history,
+            profile_info: ProfileInfo {
+                privacy: 1,
+                display_name: "Guardian".to_string(),
+                display_tag: 1234,
+                character_ids: vec![],
+            },
+            characters: vec![],
+        }
+    }
+
+    #[test]
+    fn counts_clears_since_the_daily_reset() {
+        let mut playerdata = playerdata(
+            RAID,
+            vec![raid(at(10, 16), true, 2400), raid(at(9, 18), true, 1200)],
+        );
+
+        let before_reset = Snapshot::new(
+            Some(&playerdata),
+            None,
+            at(10, 17) - chrono::Duration::milliseconds(1),
+        );
+        assert_eq!(before_reset.clears_today, 2);
+        assert_eq!(before_reset.average_clear_seconds, Some(1800));
+
+        playerdata.activity_history.splice(
+            0..0,
+            [raid(at(10, 18), true, 1800), raid(at(10, 17), false, 600)],
+        );
+
+        let after_reset = Snapshot::new(Some(&playerdata), None, at(10, 20));
+        assert_eq!(after_reset.activity.as_deref(), Some("Vault of Glass"));
+        assert_eq!(after_reset.clears_today, 1);
+        assert_eq!(after_reset.average_clear_seconds, Some(1800));
+    }
+
+    #[test]
+    fn has_no_activity_or_average_in_orbit() {
+        let snapshot = Snapshot::new(
+            Some(&playerdata(0, vec![raid(at(9, 18), true, 1800)])),
+            None,
+            at(10, 20),
+        );
+
+        assert_eq!(snapshot.activity, None);
+        assert_eq!(snapshot.clears_today, 0);
+        assert_eq!(snapshot.average_clear_seconds, None);
+        assert_eq!(Snapshot::new(None, None, at(10, 20)), Snapshot::default());
+    }
+
+    #[test]
+    fn formats_now_playing() {
+        let mut media = MediaInfo {
+            title: "Hymn".to_string(),
+            has_media: true,
+            ..MediaInfo::default()
+        };
+        assert_eq!(
+            Snapshot::new(None, Some(&media), at(10, 20)).now_playing,
+            "Hymn"
+        );
+
+        media.artist = "Michael Salvatori".to_string();
+        assert_eq!(
+            Snapshot::new(None, Some(&media), at(10, 20)).now_playing,
+            "Hymn - Michael Salvatori"
+        );
+
+        media.has_media = false;
+        assert_eq!(
+            Snapshot::new(None, Some(&media), at(10, 20)).now_playing,
+            ""
+        );
+    }
+}
@@ -0,0 +1,708 @@
+use std::{
+    collections::HashSet,
+    fs::{create_dir_all, read_to_string, write},
+    io::ErrorKind,
+};
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use reqwest::{header::CONTENT_TYPE, Client};
+use serde::{Deserialize, Serialize};
+use tauri::{
+    async_runtime::{self, JoinHandle},
+    AppHandle, Manager,
+};
+use tokio::{
+    sync::{broadcast::error::TryRecvError, Mutex},
+    time::interval,
+};
+
+use crate::{
+    api::{
+        responses::{format_duration, CompletedActivity},
+        Api,
+    },
+    config::{
+        data_path,
+        preferences::{Preferences, Webhook},
+    },
+    consts::{
+        DUNGEON_ACTIVITY_MODE, RAID_ACTIVITY_MODE, USER_AGENT, WEBHOOK_MAX_ATTEMPTS,
+        WEBHOOK_POLL_INTERVAL, WEBHOOK_QUEUE_FILE, WEBHOOK_RETRY_DELAY_SECS, WEBHOOK_TIMEOUT,
+    },
+    events::AppEvents,
+    personal_bests::PersonalBest,
+    pollers::playerdata::PlayerData,
+    ClockContainer, PlayerDataPollerContainer,
+};
+
+const DISCORD_TEMPLATE: &str = r#"{"content": "{{title}}: **{{activity}}** in {{duration}} by {{player}} ({{class}})\n{{link}}"}"#;
+const GENERIC_TEMPLATE: &str = r#"{"event": "{{event}}", "player": "{{player}}", "activity": "{{activity}}", "activityHash": {{activity_hash}}, "instanceId": "{{instance_id}}", "duration": "{{duration}}", "durationSeconds": {{duration_seconds}}, "characterClass": "{{class}}", "link": "{{link}}", "completedAt": "{{completed_at}}"}"#;
+
+#[derive(Default)]
+pub struct WebhooksHandle(Mutex<Option<JoinHandle<()>>>);
+
+/// A pending POST, kept on disk so deliveries survive restarts and outages.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Delivery {
+    url: String,
+    body: String,
+    attempts: u32,
+    next_attempt: DateTime<Utc>,
+}
+
+/// The pending deliveries and when the latest reported clear finished. Both are
+/// kept on disk, so restarting the hooks neither drops deliveries nor reports
+/// clears again.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct WebhookState {
+    last_reported: Option<DateTime<Utc>>,
+    queue: Vec<Delivery>,
+}
+
+struct NewClear {
+    activity: CompletedActivity,
+    personal_best: bool,
+}
+
+#[derive(Default)]
+struct ClearTracker {
+    profile: Option<String>,
+    known: HashSet<(String, Option<String>)>,
+    personal_bests: HashSet<String>,
+}
+
+impl ClearTracker {
+    /// Only clears that finished after `since` are reported, so history
+    /// loading in and clears that were already reported don't fire hooks.
+    /// Records come from `personal_best` events, which are published before
+    /// the clear shows up in the history.
+    fn new_clears(&mut self, playerdata: &PlayerData, since: DateTime<Utc>) -> Vec<NewClear> {
+        let profile = format!(
+            "{}#{}",
+            playerdata.profile_info.display_name, playerdata.profile_info.display_tag
+        );
+
+        if self.profile.as_ref() != Some(&profile) {
+            *self = Self {
+                profile: Some(profile),
+                personal_bests: std::mem::take(&mut self.personal_bests),
+                ..Self::default()
+            };
+        }
+
+        let mut unseen: Vec<&CompletedActivity> = playerdata
+            .activity_history
+            .iter()
+            .filter(|a| {
+                a.completed
+                    && !self
+                        .known
+                        .contains(&(a.instance_id.clone(), a.character_class.clone()))
+            })
+            .collect();
+        unseen.sort();
+
+        let mut clears = Vec::new();
+
+        for activity in unseen {
+            self.known.insert((
+                activity.instance_id.clone(),
+                activity.character_class.clone(),
+            ));
+
+            if finished_at(activity) > since {
+                clears.push(NewClear {
+                    activity: activity.clone(),
+                    personal_best: self.personal_bests.remove(&activity.instance_id),
+                });
+            }
+        }
+
+        clears
+    }
+}
+
+pub async fn restart_webhooks(handle: &AppHandle, preferences: &Preferences) {
+    let container = handle.state::<WebhooksHandle>();
+    let mut lock = container.0.lock().await;
+
+    if let Some(h) = lock.take() {
+        h.abort();
+    }
+
+    let webhooks: Vec<Webhook> = preferences
+        .webhooks
+        .iter()
+        .filter(|w| !w.url.is_empty())
+        .cloned()
+        .collect();
+
+    if !preferences.enable_webhooks || webhooks.is_empty() {
+        forget_reported();
+        return;
+    }
+
+    let link_provider = preferences.raid_link_provider.clone();
+    let handle = handle.clone();
+
+    *lock = Some(async_runtime::spawn(async move {
+        run_webhooks(handle, webhooks, link_provider).await;
+    }));
+}
+
+fn finished_at(activity: &CompletedActivity) -> DateTime<Utc> {
+    activity
+        .period
+        .checked_add_signed(Duration::seconds(activity.activity_duration_seconds as i64))
+        .unwrap_or(DateTime::<Utc>::MAX_UTC)
+}
+
+fn report_link(activity: &CompletedActivity, link_provider: &str) -> String {
+    if activity.modes.contains(&RAID_ACTIVITY_MODE) {
+        format!("https://{}/pgcr/{}", link_provider, activity.instance_id)
+    } else if activity.modes.contains(&DUNGEON_ACTIVITY_MODE) {
+        format!("https://dungeon.report/pgcr/{}", activity.instance_id)
+    } else {
+        format!("https://pgcr.report/{}", activity.instance_id)
+    }
+}
+
+/// Values are escaped as JSON string contents, so templates stay valid JSON
+/// whatever the activity or player name contains.
+fn render_template(template: &str, fields: &[(&str, String)]) -> String {
+    let mut rendered = template.to_string();
+
+    for (key, value) in fields {
+        let quoted = serde_json::to_string(value).unwrap_or_else(|_| "\"\"".to_string());
+        let escaped = &quoted[1..quoted.len() - 1];
+
+        rendered = rendered.replace(&format!("{{{{{}}}}}", key), escaped);
+    }
+
+    rendered
+}
+
+fn build_payload(
+    webhook: &Webhook,
+    clear: &NewClear,
+    activity_name: &str,
+    playerdata: &PlayerData,
+    link_provider: &str,
+) -> Option<String> {
+    let (event, title) = if clear.personal_best && webhook.on_personal_best {
+        ("personal_best", "New personal best")
+    } else if webhook.on_clear {
+        ("clear", "Cleared")
+    } else {
+        return None;
+    };
+
+    let template = match webhook.preset.as_str() {
+        "discord" => DISCORD_TEMPLATE,
+        "custom" if !webhook.template.is_empty() => webhook.template.as_str(),
+        _ => GENERIC_TEMPLATE,
+    };
+
+    let activity = &clear.activity;
+    let profile = &playerdata.profile_info;
+
+    Some(render_template(
+        template,
+        &[
+            ("event", event.to_string()),
+            ("title", title.to_string()),
+            (
+                "player",
+                format!("{}#{:04}", profile.display_name, profile.display_tag),
+            ),
+            ("activity", activity_name.to_string()),
+            ("activity_hash", activity.activity_hash.to_string()),
+            ("instance_id", activity.instance_id.clone()),
+            (
+                "duration",
+                format_duration(activity.activity_duration_seconds),
+            ),
+            (
+                "duration_seconds",
+                activity.activity_duration_seconds.to_string(),
+            ),
+            (
+                "class",
+                activity
+                    .character_class
+                    .clone()
+                    .unwrap_or_else(|| "Unknown".to_string()),
+            ),
+            ("link", report_link(activity, link_provider)),
+            ("completed_at", finished_at(activity).to_rfc3339()),
+        ],
+    ))
+}
+
+async fn activity_name(handle: &AppHandle, activity_hash: usize) -> String {
+    let api = handle.state::<Api>();
+    let info = api
+        .activity_info_source
+        .lock()
+        .await
+        .get(&activity_hash)
+        .await;
+
+    match info {
+        Ok(a) if !a.name.is_empty() => a.name,
+        _ => activity_hash.to_string(),
+    }
+}
+
+fn load_state() -> WebhookState {
+    let path = match data_path(WEBHOOK_QUEUE_FILE) {
+        Ok(p) => p,
+        Err(_) => return WebhookState::default(),
+    };
+
+    match read_to_string(path) {
+        Ok(s) => serde_json::from_str(&s).unwrap_or_else(|e| {
+            eprintln!("Discarding unreadable webhook queue: {}", e);
+            WebhookState::default()
+        }),
+        Err(e) if e.kind() == ErrorKind::NotFound => WebhookState::default(),
+        Err(e) => {
+            eprintln!("Failed to read webhook queue: {}", e);
+            WebhookState::default()
+        }
+    }
+}
+
+fn save_state(state: &WebhookState) {
+    let write_result = (|| -> Result<()> {
+        let path = data_path(WEBHOOK_QUEUE_FILE)?;
+
+        if let Some(dir) = path.parent() {
+            create_dir_all(dir)?;
+        }
+
+        write(path, serde_json::to_string(state)?)?;
+
+        Ok(())
+    })();
+
+    if let Err(e) = write_result {
+        eprintln!("Failed to save webhook queue: {}", e);
+    }
+}
+
+/// While the hooks are off nothing is reported, so turning them back on starts
+/// from that moment instead of catching up on every clear in between.
+fn forget_reported() {
+    let mut state = load_state();
+
+    if state.last_reported.take().is_some() {
+        save_state(&state);
+    }
+}
+
+async fn send(client: &Client, delivery: &Delivery) -> Result<()> {
+    client
+        .post(&delivery.url)
+        .header(CONTENT_TYPE, "application/json")
+        .body(delivery.body.clone())
+        .send()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
+
+/// Sends every delivery that is due, backing off exponentially on failure.
+/// Returns whether the queue changed.
+async fn deliver_due(client: &Client, queue: &mut Vec<Delivery>, now: DateTime<Utc>) -> bool {
+    let mut changed = false;
+    let mut remaining = Vec::new();
+
+    for mut delivery in queue.drain(..) {
+        if delivery.next_attempt > now {
+            remaining.push(delivery);
+            continue;
+        }
+
+        changed = true;
+
+        if let Err(e) = send(client, &delivery).await {
+            delivery.attempts += 1;
+
+            if delivery.attempts >= WEBHOOK_MAX_ATTEMPTS {
+                eprintln!(
+                    "Giving up on webhook delivery after {} attempts: {}",
+                    delivery.attempts, e
+                );
+                continue;
+            }
+
+            let delay = WEBHOOK_RETRY_DELAY_SECS << (delivery.attempts - 1);
+            delivery.next_attempt = now + Duration::seconds(delay);
+            remaining.push(delivery);
+        }
+    }
+
+    *queue = remaining;
+    changed
+}
+
+async fn run_webhooks(handle: AppHandle, webhooks: Vec<Webhook>, link_provider: String) {
+    let clock = handle.state::<ClockContainer>().0.clone();
+    let client = Client::builder()
+        .timeout(WEBHOOK_TIMEOUT)
+        .user_agent(USER_AGENT)
+        .build()
+        .unwrap_or_default();
+
+    let mut state = load_state();
+
+    if state.last_reported.is_none() {
+        state.last_reported = Some(clock.now());
+        save_state(&state);
+    }
+
+    let mut tracker = ClearTracker::default();
+    let mut ticker = interval(WEBHOOK_POLL_INTERVAL);
+    let mut receiver = handle.state::<AppEvents>().subscribe();
+
+    loop {
+        ticker.tick().await;
+
+        loop {
+            match receiver.try_recv() {
+                Ok(e) if e.event == "personal_best" => {
+                    if let Ok(record) = serde_json::from_value::<PersonalBest>(e.payload) {
+                        tracker.personal_bests.insert(record.instance_id);
+                    }
+                }
+                Ok(_) | Err(TryRecvError::Lagged(_)) => (),
+                Err(TryRecvError::Empty) | Err(TryRecvError::Closed) => break,
+            }
+        }
+
+        let playerdata = {
+            let container = handle.state::<PlayerDataPollerContainer>();
+            let mut lock = container.0.lock().await;
+            lock.get_data().and_then(|s| s.last_update)
+        };
+
+        if let Some(ref playerdata) = playerdata {
+            let since = state.last_reported.unwrap_or_else(|| clock.now());
+            let clears = tracker.new_clears(playerdata, since);
+
+            for clear in clears.iter() {
+                let name = activity_name(&handle, clear.activity.activity_hash).await;
+
+                for webhook in webhooks.iter() {
+                    if let Some(body) =
+                        build_payload(webhook, clear, &name, playerdata, &link_provider)
+                    {
+                        state.queue.push(Delivery {
+                            url: webhook.url.clone(),
+                            body,
+                            attempts: 0,
+                            next_attempt: clock.now(),
+                        });
+                    }
+                }
+            }
+
+            if let Some(last) = clears.iter().map(|c| finished_at(&c.activity)).max() {
+                state.last_reported = Some(last.max(since));
+                save_state(&state);
+            }
+        }
+
+        if deliver_due(&client, &mut state.queue, clock.now()).await {
+            save_state(&state);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::SocketAddr,
+        sync::{Arc, Mutex as StdMutex},
+    };
+
+    use axum::{extract::State, http::StatusCode, routing::post, Router};
+    use chrono::TimeZone;
+    use serde_json::Value;
+
+    use super::*;
+    use crate::{api::responses::ProfileInfo, pollers::playerdata::CurrentActivity};
+
+    type Received = Arc<StdMutex<Vec<(String, String)>>>;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 10, hour, minute, 0).unwrap()
+    }
+
+    /// Accepts POSTs on `/ok` and fails them on `/fail`, recording both.
+    async fn listen() -> (String, Received) {
+        async fn record(
+            State(received): State<Received>,
+            uri: axum::http::Uri,
+            body: String,
+        ) -> StatusCode {
+            received
+                .lock()
+                .unwrap()
+                .push((uri.path().to_string(), body));
+
+            match uri.path() {
+                "/ok" => StatusCode::NO_CONTENT,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            }
+        }
+
+        let received = Received::default();
+        let app = Router::new()
+            .route("/ok", post(record))
+            .route("/fail", post(record))
+            .with_state(received.clone());
+
+        let server = axum::Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0)))
+            .serve(app.into_make_service());
+        let base = format!("http://{}", server.local_addr());
+        tokio::spawn(server);
+
+        (base, received)
+    }
+
+    fn delivery(url: String, attempts: u32, next_attempt: DateTime<Utc>) -> Delivery {
+        Delivery {
+            url,
+            body: r#"{"event": "clear"}"#.to_string(),
+            attempts,
+            next_attempt,
+        }
+    }
+
+    fn raid(instance_id: &str, period: DateTime<Utc>) -> CompletedActivity {
+        CompletedActivity {
+            period,
+            instance_id: instance_id.to_string(),
+            activity_hash: 3881495763,
+            modes: vec![RAID_ACTIVITY_MODE],
+            completed: true,
+            failed: false,
+            activity_duration: format_duration(2712),
+            activity_duration_seconds: 2712,
+            start_seconds: 0,
+            character_class: Some("Hunter".to_string()),
+        }
+    }
+
+    fn playerdata(activity_history: Vec<CompletedActivity>) -> PlayerData {
+        PlayerData {
+            current_activity: CurrentActivity {
+                start_date: at(20, 0),
+                activity_hash: 0,
+                activity_info: None,
+            },
+            activity_history,
+            profile_info: ProfileInfo {
+                privacy: 1,
+                display_name: "Guardian \"Quoted\"".to_string(),
+                display_tag: 42,
+                character_ids: vec![],
+            },
+            characters: vec![],
+        }
+    }
+
+    #[test]
+    fn renders_values_as_json_string_contents() {
+        let rendered = render_template(
+            r#"{"player": "{{player}}", "seconds": {{seconds}}, "missing": "{{missing}}"}"#,
+            &[
+                ("player", "Line\nbreak \"quote\" \\ back".to_string()),
+                ("seconds", "2712".to_string()),
+            ],
+        );
+
+        let value: Value = serde_json::from_str(&rendered).unwrap();
+        assert_eq!(value["player"], "Line\nbreak \"quote\" \\ back");
+        assert_eq!(value["seconds"], 2712);
+        assert_eq!(value["missing"], "{{missing}}");
+    }
+
+    #[tokio::test]
+    async fn sends_due_deliveries_and_backs_off_failures() {
+        let (base, received) = listen().await;
+        let client = Client::new();
+        let now = at(20, 0);
+
+        let mut queue = vec![
+            delivery(format!("{base}/ok"), 0, now),
+            delivery(format!("{base}/fail"), 0, now),
+            delivery(format!("{base}/ok"), 0, now + Duration::milliseconds(1)),
+        ];
+
+        assert!(deliver_due(&client, &mut queue, now).await);
+
+        let paths: Vec<String> = received
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|r| r.0.clone())
+            .collect();
+        assert_eq!(paths, ["/ok", "/fail"]);
+
+        assert_eq!(queue.len(), 2);
+        assert!(queue[0].url.ends_with("/fail"));
+        assert_eq!(queue[0].attempts, 1);
+        assert_eq!(
+            queue[0].next_attempt,
+            now + Duration::seconds(WEBHOOK_RETRY_DELAY_SECS)
+        );
+        assert_eq!(queue[1].attempts, 0);
+
+        let mut nothing_due = vec![delivery(
+            format!("{base}/ok"),
+            0,
+            now + Duration::seconds(1),
+        )];
+        assert!(!deliver_due(&client, &mut nothing_due, now).await);
+        assert_eq!(nothing_due.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn doubles_the_delay_until_giving_up() {
+        let (base, received) = listen().await;
+        let client = Client::new();
+        let mut now = at(20, 0);
+
+        let mut queue = vec![delivery(format!("{base}/fail"), 0, now)];
+
+        for attempt in 1..WEBHOOK_MAX_ATTEMPTS {
+            assert!(deliver_due(&client, &mut queue, now).await);
+            assert_eq!(queue[0].attempts, attempt);
+
+            let delay = Duration::seconds(WEBHOOK_RETRY_DELAY_SECS * 2_i64.pow(attempt - 1));
+            assert_eq!(queue[0].next_attempt, now + delay);
+
+            // Not due a millisecond early.
+            assert!(
+                !deliver_due(&client, &mut queue, now + delay - Duration::milliseconds(1)).await
+            );
+
+            now = queue[0].next_attempt;
+        }
+
+        assert!(deliver_due(&client, &mut queue, now).await);
+        assert!(queue.is_empty());
+        assert_eq!(
+            received.lock().unwrap().len(),
+            WEBHOOK_MAX_ATTEMPTS as usize
+        );
+    }
+
+    #[tokio::test]
+    async fn delivers_a_new_clear_end_to_end() {
+        let (base, received) = listen().await;
+        let client = Client::new();
+
+        let old = raid("13700000000", at(12, 0));
+        let new = raid("13700000001", at(18, 0));
+        let playerdata = playerdata(vec![new.clone(), old]);
+
+        let mut tracker = ClearTracker::default();
+        tracker.personal_bests.insert(new.instance_id.clone());
+        let clears = tracker.new_clears(&playerdata, at(17, 0));
+
+        assert_eq!(clears.len(), 1);
+        assert!(clears[0].personal_best);
+
+        let webhooks = [
+            Webhook {
+                url: format!("{base}/ok"),
+                preset: "generic".to_string(),
+                ..Webhook::default()
+            },
+            Webhook {
+                url: format!("{base}/ok"),
+                preset: "discord".to_string(),
+                ..Webhook::default()
+            },
+        ];
+
+        let mut queue: Vec<Delivery> = webhooks
+            .iter()
+            .filter_map(|w| {
+                build_payload(w, &clears[0], "Vault of Glass", &playerdata, "raid.report")
+            })
+            .map(|body| Delivery {
+                url: format!("{base}/ok"),
+                body,
+                attempts: 0,
+                next_attempt: at(20, 0),
+            })
+            .collect();
+
+        assert!(deliver_due(&client, &mut queue, at(20, 0)).await);
+        assert!(queue.is_empty());
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 2);
+
+        let generic: Value = serde_json::from_str(&received[0].1).unwrap();
+        assert_eq!(generic["event"], "personal_best");
+        assert_eq!(generic["player"], "Guardian \"Quoted\"#0042");
+        assert_eq!(generic["activity"], "Vault of Glass");
+        assert_eq!(generic["activityHash"], 3881495763_u64);
+        assert_eq!(generic["durationSeconds"], 2712);
+        assert_eq!(generic["link"], "https://raid.report/pgcr/13700000001");
+        assert_eq!(generic["completedAt"], "2026-03-10T18:45:12+00:00");
+
+        let discord: Value = serde_json::from_str(&received[1].1).unwrap();
+        assert!(discord["content"]
+            .as_str()
+            .unwrap()
+            .starts_with("New personal best: **Vault of Glass**"));
+    }
+
+    #[test]
+    fn a_restarted_tracker_skips_clears_already_reported() {
+        let first = raid("13700000001", at(18, 0));
+        let second = raid("13700000002", at(19, 0));
+
+        let mut tracker = ClearTracker::default();
+        let clears = tracker.new_clears(&playerdata(vec![first.clone()]), at(17, 0));
+        assert_eq!(clears.len(), 1);
+
+        let last_reported = finished_at(&clears[0].activity);
+
+        // A preference save restarts the hooks with a fresh tracker.
+        let mut tracker = ClearTracker::default();
+        let clears = tracker.new_clears(&playerdata(vec![second, first]), last_reported);
+
+        let ids: Vec<&str> = clears
+            .iter()
+            .map(|c| c.activity.instance_id.as_str())
+            .collect();
+        assert_eq!(ids, ["13700000002"]);
+    }
+
+    #[test]
+    fn the_state_keeps_the_last_reported_clear() {
+        let state = WebhookState {
+            last_reported: Some(at(18, 45)),
+            queue: vec![delivery("http://127.0.0.1/ok".to_string(), 2, at(19, 0))],
+        };
+
+        let saved = serde_json::to_string(&state).unwrap();
+        let loaded: WebhookState = serde_json::from_str(&saved).unwrap();
+
+        assert_eq!(loaded.last_reported, Some(at(18, 45)));
+        assert_eq!(loaded.queue.len(), 1);
+        assert_eq!(loaded.queue[0].attempts, 2);
+    }
+}
@@ -0,0 +1,16 @@
+pub mod api;
+pub mod attempts;
+pub mod clock;
+pub mod config;
+pub mod consts;
+pub mod discord_ipc;
+pub mod export;
+pub mod filter;
+pub mod history;
+pub mod import;
+pub mod livesplit_protocol;
+pub mod metrics;
+pub mod personal_bests;
+pub mod sessions;
+pub mod stats;
+pub mod timerange;
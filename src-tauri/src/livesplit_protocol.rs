@@ -0,0 +1,65 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use tokio::{io::AsyncWriteExt, net::TcpStream};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LiveSplitCommand {
+    StartTimer,
+    Split,
+    Pause,
+    Reset,
+    InitGameTime,
+    SetGameTime(Duration),
+}
+
+impl LiveSplitCommand {
+    pub fn to_line(&self) -> String {
+        match self {
+            Self::StartTimer => "starttimer".to_string(),
+            Self::Split => "split".to_string(),
+            Self::Pause => "pause".to_string(),
+            Self::Reset => "reset".to_string(),
+            Self::InitGameTime => "initgametime".to_string(),
+            Self::SetGameTime(time) => format!("setgametime {}", format_game_time(*time)),
+        }
+    }
+}
+
+/// LiveSplit parses game times as .NET time spans, `h:mm:ss.ff`.
+pub fn format_game_time(time: Duration) -> String {
+    let total_seconds = time.as_secs();
+    let hundredths = time.subsec_millis() / 10;
+
+    format!(
+        "{}:{:02}:{:02}.{:02}",
+        total_seconds / 3600,
+        (total_seconds % 3600) / 60,
+        total_seconds % 60,
+        hundredths
+    )
+}
+
+/// The LiveSplit Server component only listens, every command is a single
+/// line and nothing is sent back for the ones used here.
+pub struct LiveSplitClient {
+    stream: TcpStream,
+}
+
+impl LiveSplitClient {
+    pub async fn connect(host: &str, port: u16) -> Result<Self> {
+        let stream = TcpStream::connect((host, port)).await?;
+        stream.set_nodelay(true)?;
+
+        Ok(Self { stream })
+    }
+
+    pub async fn send(&mut self, command: &LiveSplitCommand) -> Result<()> {
+        let line = format!("{}\r\n", command.to_line());
+
+        self.stream.write_all(line.as_bytes()).await?;
+        self.stream.flush().await?;
+
+        Ok(())
+    }
+}
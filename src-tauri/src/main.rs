@@ -3,24 +3,47 @@
     windows_subsystem = "windows"
 )]
 
-use std::io;
+use std::{io, path::Path, sync::Arc};
 
+use chrono::{DateTime, Duration, Utc};
+
+use groundsub::{api, attempts, clock, config, consts, discord_ipc, export, filter, history, import, livesplit_protocol, metrics, personal_bests, sessions, stats, timerange};
 use api::{
-    responses::{ActivityInfo, BungieProfile, ProfileInfo},
+    client::client_from_env,
+    responses::{ActivityInfo, BungieProfile, CharacterInfo, ProfileInfo},
     Api, Source,
 };
+use attempts::{attempt_report, AttemptReport, AttemptTracker};
 use config::{
     preferences::Preferences,
     profiles::{Profile, Profiles},
     ConfigManager,
 };
+use clock::{Clock, SystemClock};
 use consts::{APP_NAME, APP_VER, NAMED_PIPE};
+use export::{build_export, write_export, ExportFormat};
+use filter::HistoryFilter;
+use import::{build_import, read_import, save_imported, to_completed_activity, CsvMapping};
 use pollers::{
+    health::{get_api_health, health_poller, ApiHealth},
     media::{media_poller, get_current_media},
     overlay::overlay_poller,
     playerdata::{PlayerDataPoller, PlayerDataStatus},
-    timer::{TimerPoller, TimerMode},
+    timer::{TimerConfig, TimerPoller, TimerMode},
+};
+use events::{publish_event, AppEvents};
+use integrations::{
+    discord::{discord_presence_available, restart_discord_presence, DiscordPresenceHandle},
+    files::{restart_file_output, FileOutputHandle},
+    livesplit::{restart_livesplit, LiveSplitHandle},
+    mqtt::{restart_mqtt, MqttHandle},
+    webhooks::{restart_webhooks, WebhooksHandle},
 };
+use personal_bests::{PersonalBest, PersonalBestTracker};
+use server::{restart_local_api, LocalApiHandle};
+use sessions::{detect_sessions, PlaySession, SessionTracker};
+use stats::{compute_stats, HistoryStats};
+use timerange::range_start;
 use tauri::{
     async_runtime::{self, JoinHandle},
     AppHandle, CustomMenuItem, Manager, RunEvent, State, SystemTray, SystemTrayEvent,
@@ -31,19 +54,31 @@ use tokio::{
     sync::Mutex,
 };
 
-mod api;
-mod config;
-mod consts;
+mod events;
+mod integrations;
 mod pollers;
+mod server;
 
 pub struct ConfigContainer(Mutex<ConfigManager>);
 
+/// Where everything outside of the pollers reads the time from, shared with
+/// the pollers so they all agree on it.
+pub struct ClockContainer(Arc<dyn Clock>);
+
 #[derive(Default)]
+pub struct ApiHealthContainer(Mutex<ApiHealth>);
+
 struct PlayerDataPollerContainer(Mutex<PlayerDataPoller>);
 
-#[derive(Default)]
 struct TimerPollerContainer(Mutex<TimerPoller>);
 
+struct PersonalBestsContainer(Mutex<PersonalBestTracker>);
+
+#[derive(Default)]
+struct SessionsContainer(Mutex<SessionTracker>);
+
+struct AttemptsContainer(Mutex<AttemptTracker>);
+
 #[derive(Default)]
 struct OverlayPollerHandle(Mutex<Option<JoinHandle<()>>>);
 
@@ -73,14 +108,65 @@ async fn set_preferences(
     poller_handle: State<'_, OverlayPollerHandle>,
 ) -> Result<(), ()> {
     let mut lock = container.0.lock().await;
+
+    let old = lock.get_preferences().clone();
+    let mut preferences = preferences;
+    preferences.ensure_local_api_token(&old.local_api_token);
     lock.set_preferences(preferences.clone()).unwrap();
 
+    if old.enable_local_api != preferences.enable_local_api
+        || old.local_api_port != preferences.local_api_port
+        || old.local_api_token != preferences.local_api_token
+    {
+        restart_local_api(&handle, &preferences).await;
+    }
+
+    if old.enable_file_output != preferences.enable_file_output
+        || old.file_output_dir != preferences.file_output_dir
+    {
+        restart_file_output(&handle, &preferences).await;
+    }
+
+    if old.enable_discord_presence != preferences.enable_discord_presence
+        || old.discord_show_timer != preferences.discord_show_timer
+        || old.discord_show_clears != preferences.discord_show_clears
+        || old.discord_show_image != preferences.discord_show_image
+    {
+        restart_discord_presence(&handle, &preferences).await;
+    }
+
+    if old.enable_livesplit != preferences.enable_livesplit
+        || old.livesplit_host != preferences.livesplit_host
+        || old.livesplit_port != preferences.livesplit_port
+    {
+        restart_livesplit(&handle, &preferences).await;
+    }
+
+    if old.enable_webhooks != preferences.enable_webhooks
+        || old.webhooks != preferences.webhooks
+        || old.raid_link_provider != preferences.raid_link_provider
+    {
+        restart_webhooks(&handle, &preferences).await;
+    }
+
+    if old.enable_mqtt != preferences.enable_mqtt
+        || old.mqtt_host != preferences.mqtt_host
+        || old.mqtt_port != preferences.mqtt_port
+        || old.mqtt_username != preferences.mqtt_username
+        || old.mqtt_password != preferences.mqtt_password
+        || old.mqtt_topic_prefix != preferences.mqtt_topic_prefix
+    {
+        restart_mqtt(&handle, &preferences).await;
+    }
+
     for window in handle.windows().values() {
         if let Err(e) = window.emit("preferences_update", &preferences) {
             eprintln!("Failed to send preferences update to window {}: {}", window.label(), e);
         }
     }
 
+    publish_event(&handle, "preferences_update", &preferences.redacted());
+
     if let Some(o) = handle.get_window("overlay") {
         if !preferences.enable_overlay {
             if let Some(h) = poller_handle.0.lock().await.as_ref() {
@@ -154,12 +240,25 @@ async fn get_activity_info(
         .map_err(|e| e.to_string())?)
 }
 
+#[tauri::command]
+async fn get_characters(
+    profile: Profile,
+    api: State<'_, Api>,
+) -> Result<Vec<CharacterInfo>, String> {
+    Ok(api
+        .get_characters(&profile)
+        .await
+        .map_err(|e| e.to_string())?)
+}
+
 #[tauri::command]
 async fn search_profile(
     display_name: String,
     display_name_code: usize,
+    api: State<'_, Api>,
 ) -> Result<Vec<BungieProfile>, String> {
-    Ok(Api::search_profile(&display_name, display_name_code)
+    Ok(api
+        .search_profile(&display_name, display_name_code)
         .await
         .map_err(|e| e.to_string())?)
 }
@@ -233,6 +332,180 @@ async fn clear_timer(
     Ok(())
 }
 
+#[tauri::command]
+async fn export_history(
+    path: String,
+    format: String,
+    filter: HistoryFilter,
+    playerdata_container: State<'_, PlayerDataPollerContainer>,
+    api: State<'_, Api>,
+    clock: State<'_, ClockContainer>,
+) -> Result<usize, String> {
+    let format = ExportFormat::parse(&format).map_err(|e| e.to_string())?;
+
+    let history = playerdata_container
+        .0
+        .lock()
+        .await
+        .get_data()
+        .and_then(|s| s.last_update)
+        .map(|d| d.activity_history)
+        .ok_or("No activity history loaded yet")?;
+
+    let filter = filter.resolve(clock.0.now()).map_err(|e| e.to_string())?;
+    let rows = build_export(&api, &history, &filter).await;
+
+    write_export(Path::new(&path), format, &rows).map_err(|e| e.to_string())?;
+
+    Ok(rows.len())
+}
+
+#[tauri::command]
+async fn get_personal_bests(
+    config_container: State<'_, ConfigContainer>,
+    personal_bests_container: State<'_, PersonalBestsContainer>,
+) -> Result<Vec<PersonalBest>, String> {
+    let profile = config_container
+        .0
+        .lock()
+        .await
+        .get_profiles()
+        .selected_profile
+        .clone()
+        .ok_or("No profile set")?;
+
+    Ok(personal_bests_container.0.lock().await.records.history(&profile))
+}
+
+#[tauri::command]
+async fn get_stats(
+    filter: HistoryFilter,
+    playerdata_container: State<'_, PlayerDataPollerContainer>,
+    clock: State<'_, ClockContainer>,
+) -> Result<HistoryStats, String> {
+    let history = playerdata_container
+        .0
+        .lock()
+        .await
+        .get_history()
+        .await
+        .ok_or("No activity history loaded yet")?;
+    let filter = filter.resolve(clock.0.now()).map_err(|e| e.to_string())?;
+
+    Ok(compute_stats(&history, &filter))
+}
+
+#[tauri::command]
+async fn get_sessions(
+    config_container: State<'_, ConfigContainer>,
+    playerdata_container: State<'_, PlayerDataPollerContainer>,
+    sessions_container: State<'_, SessionsContainer>,
+    clock: State<'_, ClockContainer>,
+) -> Result<Vec<PlaySession>, String> {
+    let gap = config_container.0.lock().await.get_preferences().session_gap_minutes;
+
+    let history = playerdata_container
+        .0
+        .lock()
+        .await
+        .get_history()
+        .await
+        .ok_or("No activity history loaded yet")?;
+
+    let tracker = sessions_container.0.lock().await;
+
+    Ok(detect_sessions(&history, &tracker, Duration::minutes(gap as i64), clock.0.now()))
+}
+
+#[tauri::command]
+async fn get_attempts(
+    filter: HistoryFilter,
+    config_container: State<'_, ConfigContainer>,
+    playerdata_container: State<'_, PlayerDataPollerContainer>,
+    attempts_container: State<'_, AttemptsContainer>,
+    clock: State<'_, ClockContainer>,
+) -> Result<AttemptReport, String> {
+    let profile = config_container
+        .0
+        .lock()
+        .await
+        .get_profiles()
+        .selected_profile
+        .clone()
+        .ok_or("No profile set")?;
+
+    let history = playerdata_container
+        .0
+        .lock()
+        .await
+        .get_history()
+        .await
+        .ok_or("No activity history loaded yet")?;
+    let filter = filter.resolve(clock.0.now()).map_err(|e| e.to_string())?;
+
+    let tracker = attempts_container.0.lock().await;
+
+    Ok(attempt_report(&history, tracker.observed(&profile), &filter, clock.0.now()))
+}
+
+#[tauri::command]
+fn get_range_start(
+    timespan: String,
+    custom_start_date: String,
+    use_real_time: bool,
+    clock: State<'_, ClockContainer>,
+) -> Result<Option<DateTime<Utc>>, String> {
+    range_start(&timespan, &custom_start_date, use_real_time, clock.0.now())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn import_history(
+    handle: AppHandle,
+    path: String,
+    format: String,
+    mapping: Option<CsvMapping>,
+    config_container: State<'_, ConfigContainer>,
+    playerdata_container: State<'_, PlayerDataPollerContainer>,
+    api: State<'_, Api>,
+) -> Result<usize, String> {
+    let format = ExportFormat::parse(&format).map_err(|e| e.to_string())?;
+
+    let profile = config_container
+        .0
+        .lock()
+        .await
+        .get_profiles()
+        .selected_profile
+        .clone()
+        .ok_or("No profile set")?;
+
+    let rows = read_import(Path::new(&path), format, &mapping.unwrap_or_default())
+        .map_err(|e| e.to_string())?;
+    let rows = build_import(&api, rows).await;
+    let activities = rows.iter().map(to_completed_activity).collect();
+
+    let added = save_imported(&profile, rows).map_err(|e| e.to_string())?;
+
+    playerdata_container
+        .0
+        .lock()
+        .await
+        .merge_imported(&handle, activities)
+        .await;
+
+    Ok(added)
+}
+
+#[tauri::command]
+async fn split_timer(
+    handle: AppHandle,
+    timer_container: State<'_, TimerPollerContainer>,
+) -> Result<(), ()> {
+    timer_container.0.lock().await.split_timer(&handle).await;
+    Ok(())
+}
+
 #[tauri::command]
 async fn clear_and_restart_timer(
     handle: AppHandle,
@@ -356,13 +629,30 @@ async fn main() -> anyhow::Result<()> {
 
     tauri::async_runtime::set(tokio::runtime::Handle::current());
 
+    let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+
     tauri::Builder::new()
         .manage(ConfigContainer(Mutex::new(ConfigManager::load()?)))
-        .manage(Api::default())
-        .manage(PlayerDataPollerContainer::default())
-        .manage(TimerPollerContainer::default())
+        .manage(ClockContainer(clock.clone()))
+        .manage(Api::new(client_from_env()?))
+        .manage(PlayerDataPollerContainer(Mutex::new(PlayerDataPoller::new(clock.clone()))))
+        .manage(TimerPollerContainer(Mutex::new(TimerPoller::new(
+            TimerConfig::default(),
+            clock.clone(),
+        ))))
+        .manage(PersonalBestsContainer(Mutex::new(PersonalBestTracker::new(clock.now()))))
+        .manage(SessionsContainer::default())
+        .manage(AttemptsContainer(Mutex::new(AttemptTracker::load())))
         .manage(OverlayPollerHandle::default())
         .manage(MediaPollerHandle::default())
+        .manage(ApiHealthContainer::default())
+        .manage(AppEvents::default())
+        .manage(LocalApiHandle::default())
+        .manage(FileOutputHandle::default())
+        .manage(DiscordPresenceHandle::default())
+        .manage(LiveSplitHandle::default())
+        .manage(WebhooksHandle::default())
+        .manage(MqttHandle::default())
         .system_tray(
             SystemTray::new().with_menu(
                 SystemTrayMenu::new()
@@ -399,12 +689,23 @@ async fn main() -> anyhow::Result<()> {
             set_profiles,
             get_profile_info,
             get_activity_info,
+            get_characters,
             search_profile,
             get_playerdata,
             set_timer_mode,
             clear_timer,
             clear_and_restart_timer,
+            split_timer,
+            export_history,
+            get_stats,
+            get_range_start,
+            get_sessions,
+            get_attempts,
+            get_personal_bests,
+            import_history,
             get_current_media,
+            get_api_health,
+            discord_presence_available,
         ])
         .setup(|app| {
             let handle = app.handle();
@@ -416,6 +717,13 @@ async fn main() -> anyhow::Result<()> {
                 let config_container = handle.state::<ConfigContainer>();
                 let lock = config_container.0.lock().await;
 
+                restart_local_api(&handle, lock.get_preferences()).await;
+                restart_file_output(&handle, lock.get_preferences()).await;
+                restart_discord_presence(&handle, lock.get_preferences()).await;
+                restart_livesplit(&handle, lock.get_preferences()).await;
+                restart_webhooks(&handle, lock.get_preferences()).await;
+                restart_mqtt(&handle, lock.get_preferences()).await;
+
                 if lock.get_profiles().selected_profile.is_none() {
                     open_profiles_window(&handle).unwrap();
                 } else {
@@ -426,6 +734,12 @@ async fn main() -> anyhow::Result<()> {
                     open_details_window(&handle, false).unwrap();
                 }
 
+                {
+                    let health_handle = handle.clone();
+                    let clock = handle.state::<ClockContainer>().0.clone();
+                    async_runtime::spawn(async move { health_poller(health_handle, clock).await });
+                }
+
                 let poller_container = handle.state::<PlayerDataPollerContainer>();
                 let handle_clone = handle.clone();
 
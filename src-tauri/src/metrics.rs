@@ -0,0 +1,295 @@
+use std::{collections::BTreeMap, fmt::Write, sync::Mutex, time::Duration};
+
+pub const API_REQUESTS: &str = "groundsub_api_requests_total";
+pub const API_ERRORS: &str = "groundsub_api_errors_total";
+pub const API_THROTTLES: &str = "groundsub_api_throttle_events_total";
+pub const API_LATENCY: &str = "groundsub_api_request_duration_seconds";
+pub const POLL_CYCLES: &str = "groundsub_poll_cycles_total";
+
+const LATENCY_BUCKETS: &[f64] = &[0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+const DESCRIPTIONS: &[(&str, &str)] = &[
+    (API_REQUESTS, "Bungie API requests by request type."),
+    (
+        API_ERRORS,
+        "Failed Bungie API requests by request type and error kind.",
+    ),
+    (
+        API_THROTTLES,
+        "Bungie API responses asking groundsub to back off.",
+    ),
+    (API_LATENCY, "Bungie API request latency by request type."),
+    (POLL_CYCLES, "Completed poll cycles by poller."),
+];
+
+pub type Labels = Vec<(&'static str, String)>;
+
+#[derive(Default)]
+struct Histogram {
+    buckets: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+struct Registry {
+    counters: BTreeMap<&'static str, BTreeMap<Labels, u64>>,
+    histograms: BTreeMap<&'static str, BTreeMap<Labels, Histogram>>,
+}
+
+/// Process-wide, since requests are made from both the app and its pollers.
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry::new());
+
+fn to_labels(labels: &[(&'static str, &str)]) -> Labels {
+    labels.iter().map(|(k, v)| (*k, v.to_string())).collect()
+}
+
+pub fn increment(name: &'static str, labels: &[(&'static str, &str)]) {
+    if let Ok(mut registry) = REGISTRY.lock() {
+        registry.increment(name, labels);
+    }
+}
+
+pub fn observe(name: &'static str, labels: &[(&'static str, &str)], value: Duration) {
+    if let Ok(mut registry) = REGISTRY.lock() {
+        registry.observe(name, labels, value);
+    }
+}
+
+/// Renders every counter and histogram in the Prometheus text format.
+pub fn render(out: &mut String) {
+    if let Ok(registry) = REGISTRY.lock() {
+        registry.render(out);
+    }
+}
+
+impl Registry {
+    const fn new() -> Self {
+        Self {
+            counters: BTreeMap::new(),
+            histograms: BTreeMap::new(),
+        }
+    }
+
+    fn increment(&mut self, name: &'static str, labels: &[(&'static str, &str)]) {
+        *self
+            .counters
+            .entry(name)
+            .or_default()
+            .entry(to_labels(labels))
+            .or_default() += 1;
+    }
+
+    fn observe(&mut self, name: &'static str, labels: &[(&'static str, &str)], value: Duration) {
+        let seconds = value.as_secs_f64();
+
+        let histogram = self
+            .histograms
+            .entry(name)
+            .or_default()
+            .entry(to_labels(labels))
+            .or_default();
+
+        if histogram.buckets.is_empty() {
+            histogram.buckets = vec![0; LATENCY_BUCKETS.len()];
+        }
+
+        for (bucket, bound) in histogram.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if seconds <= *bound {
+                *bucket += 1;
+            }
+        }
+
+        histogram.sum += seconds;
+        histogram.count += 1;
+    }
+
+    fn render(&self, out: &mut String) {
+        for (name, series) in self.counters.iter() {
+            write_header(out, name, "counter", description(name));
+
+            for (labels, value) in series {
+                let _ = writeln!(out, "{}{} {}", name, format_labels(labels), value);
+            }
+        }
+
+        for (name, series) in self.histograms.iter() {
+            write_header(out, name, "histogram", description(name));
+
+            for (labels, histogram) in series {
+                for (count, bound) in histogram.buckets.iter().zip(LATENCY_BUCKETS) {
+                    let mut bucket_labels = labels.clone();
+                    bucket_labels.push(("le", bound.to_string()));
+
+                    let _ = writeln!(
+                        out,
+                        "{}_bucket{} {}",
+                        name,
+                        format_labels(&bucket_labels),
+                        count
+                    );
+                }
+
+                let mut inf_labels = labels.clone();
+                inf_labels.push(("le", "+Inf".to_string()));
+
+                let _ = writeln!(
+                    out,
+                    "{}_bucket{} {}",
+                    name,
+                    format_labels(&inf_labels),
+                    histogram.count
+                );
+                let _ = writeln!(
+                    out,
+                    "{}_sum{} {}",
+                    name,
+                    format_labels(labels),
+                    histogram.sum
+                );
+                let _ = writeln!(
+                    out,
+                    "{}_count{} {}",
+                    name,
+                    format_labels(labels),
+                    histogram.count
+                );
+            }
+        }
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn format_labels(labels: &[(&str, String)]) -> String {
+    if labels.is_empty() {
+        return String::new();
+    }
+
+    let pairs: Vec<String> = labels
+        .iter()
+        .map(|(k, v)| format!("{}=\"{}\"", k, escape(v)))
+        .collect();
+
+    format!("{{{}}}", pairs.join(","))
+}
+
+fn write_header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// Gauges are read from the app state on every scrape, so they are written
+/// straight into the output instead of being kept in the registry.
+pub fn write_gauge(out: &mut String, name: &str, help: &str, samples: &[(Labels, f64)]) {
+    write_header(out, name, "gauge", help);
+
+    for (labels, value) in samples {
+        let _ = writeln!(out, "{}{} {}", name, format_labels(labels), value);
+    }
+}
+
+fn description(name: &str) -> &'static str {
+    DESCRIPTIONS
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, help)| *help)
+        .unwrap_or("")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_counters() {
+        let mut registry = Registry::new();
+        registry.increment(API_REQUESTS, &[("request", "GetProfile")]);
+        registry.increment(API_REQUESTS, &[("request", "GetProfile")]);
+        registry.increment(API_REQUESTS, &[("request", "GetActivityHistory")]);
+
+        let mut out = String::new();
+        registry.render(&mut out);
+
+        assert_eq!(
+            out,
+            "# HELP groundsub_api_requests_total Bungie API requests by request type.\n\
+             # TYPE groundsub_api_requests_total counter\n\
+             groundsub_api_requests_total{request=\"GetActivityHistory\"} 1\n\
+             groundsub_api_requests_total{request=\"GetProfile\"} 2\n"
+        );
+    }
+
+    #[test]
+    fn renders_cumulative_histogram_buckets() {
+        let mut registry = Registry::new();
+        let labels = [("request", "GetProfile")];
+        registry.observe(API_LATENCY, &labels, Duration::from_millis(250));
+        registry.observe(API_LATENCY, &labels, Duration::from_millis(1000));
+        registry.observe(API_LATENCY, &labels, Duration::from_secs(30));
+
+        let mut out = String::new();
+        registry.render(&mut out);
+
+        let name = "groundsub_api_request_duration_seconds";
+        let lines: Vec<&str> = out.lines().collect();
+
+        assert_eq!(lines[1], format!("# TYPE {} histogram", name));
+        assert_eq!(
+            lines[2..].to_vec(),
+            vec![
+                format!("{}_bucket{{request=\"GetProfile\",le=\"0.1\"}} 0", name),
+                format!("{}_bucket{{request=\"GetProfile\",le=\"0.25\"}} 1", name),
+                format!("{}_bucket{{request=\"GetProfile\",le=\"0.5\"}} 1", name),
+                format!("{}_bucket{{request=\"GetProfile\",le=\"1\"}} 2", name),
+                format!("{}_bucket{{request=\"GetProfile\",le=\"2.5\"}} 2", name),
+                format!("{}_bucket{{request=\"GetProfile\",le=\"5\"}} 2", name),
+                format!("{}_bucket{{request=\"GetProfile\",le=\"10\"}} 2", name),
+                format!("{}_bucket{{request=\"GetProfile\",le=\"+Inf\"}} 3", name),
+                format!("{}_sum{{request=\"GetProfile\"}} 31.25", name),
+                format!("{}_count{{request=\"GetProfile\"}} 3", name),
+            ]
+        );
+    }
+
+    #[test]
+    fn escapes_label_values() {
+        let mut out = String::new();
+        write_gauge(
+            &mut out,
+            "groundsub_activity_clears",
+            "Clears per activity in the loaded history.",
+            &[(
+                vec![("activity", "King's \"Fall\"\\\nMaster".to_string())],
+                2.0,
+            )],
+        );
+
+        assert_eq!(
+            out.lines().nth(2),
+            Some(r#"groundsub_activity_clears{activity="King's \"Fall\"\\\nMaster"} 2"#)
+        );
+    }
+
+    #[test]
+    fn writes_gauges_without_labels() {
+        let mut out = String::new();
+        write_gauge(
+            &mut out,
+            "groundsub_timer_active",
+            "Whether the timer is running.",
+            &[(vec![], 1.0)],
+        );
+
+        assert_eq!(
+            out,
+            "# HELP groundsub_timer_active Whether the timer is running.\n\
+             # TYPE groundsub_timer_active gauge\n\
+             groundsub_timer_active 1\n"
+        );
+    }
+}
@@ -0,0 +1,358 @@
+use std::{
+    collections::HashMap,
+    fs::{create_dir_all, read_to_string, write},
+    io::ErrorKind,
+};
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    api::responses::CompletedActivity,
+    config::{data_path, profiles::Profile},
+    consts::PERSONAL_BESTS_FILE,
+    history::is_tracked_activity,
+};
+
+/// A clear that set a new record. Records are kept per activity, and per
+/// character class and for full runs only when those options are on, so
+/// every combination has its own history.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PersonalBest {
+    pub activity_hash: usize,
+    pub character_class: Option<String>,
+    pub full_runs_only: bool,
+    pub instance_id: String,
+    pub period: DateTime<Utc>,
+    pub duration_seconds: usize,
+    pub previous_seconds: Option<usize>,
+}
+
+impl PersonalBest {
+    fn same_record(
+        &self,
+        activity_hash: usize,
+        class: &Option<String>,
+        full_runs_only: bool,
+    ) -> bool {
+        self.activity_hash == activity_hash
+            && self.character_class == *class
+            && self.full_runs_only == full_runs_only
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct PersonalBests {
+    profiles: HashMap<String, Vec<PersonalBest>>,
+}
+
+impl PersonalBests {
+    pub fn load() -> Self {
+        let read = || -> Result<Self> {
+            match read_to_string(data_path(PERSONAL_BESTS_FILE)?) {
+                Ok(s) => Ok(serde_json::from_str(&s)?),
+                Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+                Err(e) => Err(e.into()),
+            }
+        };
+
+        read().unwrap_or_else(|e| {
+            eprintln!("Failed to read personal bests: {}", e);
+            Self::default()
+        })
+    }
+
+    fn save(&self) -> Result<()> {
+        let path = data_path(PERSONAL_BESTS_FILE)?;
+
+        if let Some(dir) = path.parent() {
+            create_dir_all(dir)?;
+        }
+
+        write(path, serde_json::to_string(self)?)?;
+
+        Ok(())
+    }
+
+    /// Every record the profile has set, oldest first.
+    pub fn history(&self, profile: &Profile) -> Vec<PersonalBest> {
+        let mut history = self
+            .profiles
+            .get(&profile.key())
+            .cloned()
+            .unwrap_or_default();
+
+        history.sort_by_key(|r| r.period);
+        history
+    }
+
+    /// Checks the clears in the history against the records and returns the
+    /// records they set, oldest first.
+    pub fn update(
+        &mut self,
+        profile: &Profile,
+        history: &[CompletedActivity],
+        per_class: bool,
+        full_runs_only: bool,
+    ) -> Vec<PersonalBest> {
+        let records = self.profiles.entry(profile.key()).or_default();
+
+        let mut clears: Vec<&CompletedActivity> = history
+            .iter()
+            .filter(|a| {
+                a.completed
+                    && a.activity_duration_seconds > 0
+                    && (!full_runs_only || a.start_seconds == 0)
+                    && is_tracked_activity(a)
+            })
+            .collect();
+        clears.sort();
+
+        let mut new_records = Vec::new();
+
+        for activity in clears {
+            let class = if per_class {
+                activity.character_class.clone()
+            } else {
+                None
+            };
+
+            let best = records
+                .iter()
+                .filter(|r| r.same_record(activity.activity_hash, &class, full_runs_only))
+                .map(|r| r.duration_seconds)
+                .min();
+
+            if best.map_or(false, |b| activity.activity_duration_seconds >= b) {
+                continue;
+            }
+
+            let record = PersonalBest {
+                activity_hash: activity.activity_hash,
+                character_class: class,
+                full_runs_only,
+                instance_id: activity.instance_id.clone(),
+                period: activity.period,
+                duration_seconds: activity.activity_duration_seconds,
+                previous_seconds: best,
+            };
+
+            records.push(record.clone());
+            new_records.push(record);
+        }
+
+        new_records
+    }
+}
+
+pub struct PersonalBestTracker {
+    pub records: PersonalBests,
+    started_at: DateTime<Utc>,
+}
+
+impl PersonalBestTracker {
+    pub fn new(started_at: DateTime<Utc>) -> Self {
+        Self {
+            records: PersonalBests::load(),
+            started_at,
+        }
+    }
+
+    /// Records and saves every new best, but only returns the ones worth
+    /// announcing: beating an earlier record with a clear that finished after
+    /// groundsub started, so loading or importing history stays quiet.
+    pub fn update(
+        &mut self,
+        profile: &Profile,
+        history: &[CompletedActivity],
+        per_class: bool,
+        full_runs_only: bool,
+    ) -> Vec<PersonalBest> {
+        let records = self
+            .records
+            .update(profile, history, per_class, full_runs_only);
+
+        if !records.is_empty() {
+            if let Err(e) = self.records.save() {
+                eprintln!("Failed to save personal bests: {}", e);
+            }
+        }
+
+        self.announced(records)
+    }
+
+    fn announced(&self, records: Vec<PersonalBest>) -> Vec<PersonalBest> {
+        let started_at = self.started_at;
+
+        records
+            .into_iter()
+            .filter(|r| {
+                r.previous_seconds.is_some()
+                    && r.period
+                        .checked_add_signed(Duration::seconds(r.duration_seconds as i64))
+                        .unwrap_or(DateTime::<Utc>::MAX_UTC)
+                        >= started_at
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::{api::responses::format_duration, consts::RAID_ACTIVITY_MODE};
+
+    const RAID: usize = 1374392663;
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, day, hour, 0, 0).unwrap()
+    }
+
+    fn profile() -> Profile {
+        Profile {
+            account_platform: 3,
+            account_id: "4611686018467654321".to_string(),
+        }
+    }
+
+    fn clear(instance_id: &str, period: DateTime<Utc>, seconds: usize) -> CompletedActivity {
+        CompletedActivity {
+            period,
+            instance_id: instance_id.to_string(),
+            activity_hash: RAID,
+            modes: vec![RAID_ACTIVITY_MODE],
+            completed: true,
+            failed: false,
+            activity_duration: format_duration(seconds),
+            activity_duration_seconds: seconds,
+            start_seconds: 0,
+            character_class: Some("Hunter".to_string()),
+        }
+    }
+
+    fn checkpoint(instance_id: &str, period: DateTime<Utc>, seconds: usize) -> CompletedActivity {
+        CompletedActivity {
+            start_seconds: 900,
+            ..clear(instance_id, period, seconds)
+        }
+    }
+
+    fn with_class(activity: CompletedActivity, class: &str) -> CompletedActivity {
+        CompletedActivity {
+            character_class: Some(class.to_string()),
+            ..activity
+        }
+    }
+
+    fn durations(records: &[PersonalBest]) -> Vec<(usize, Option<usize>)> {
+        records
+            .iter()
+            .map(|r| (r.duration_seconds, r.previous_seconds))
+            .collect()
+    }
+
+    #[test]
+    fn sets_records_in_chronological_order() {
+        let mut bests = PersonalBests::default();
+        let history = vec![
+            clear("3", at(12, 18), 1500),
+            clear("1", at(10, 18), 2000),
+            clear("4", at(13, 18), 1600),
+            clear("2", at(11, 18), 1800),
+        ];
+
+        let records = bests.update(&profile(), &history, false, false);
+
+        assert_eq!(
+            durations(&records),
+            vec![(2000, None), (1800, Some(2000)), (1500, Some(1800))]
+        );
+        assert_eq!(records[2].instance_id, "3");
+        assert!(bests.update(&profile(), &history, false, false).is_empty());
+        assert_eq!(bests.history(&profile()).len(), 3);
+    }
+
+    #[test]
+    fn does_not_count_ties() {
+        let mut bests = PersonalBests::default();
+        let history = vec![clear("1", at(10, 18), 1800), clear("2", at(11, 18), 1800)];
+
+        let records = bests.update(&profile(), &history, false, false);
+
+        assert_eq!(durations(&records), vec![(1800, None)]);
+        assert_eq!(records[0].instance_id, "1");
+    }
+
+    #[test]
+    fn keeps_per_class_records_apart() {
+        let mut bests = PersonalBests::default();
+        let history = vec![
+            clear("1", at(10, 18), 1800),
+            with_class(clear("2", at(11, 18), 2000), "Warlock"),
+        ];
+
+        let records = bests.update(&profile(), &history, true, false);
+        assert_eq!(durations(&records), vec![(1800, None), (2000, None)]);
+        assert_eq!(records[1].character_class.as_deref(), Some("Warlock"));
+
+        let records = bests.update(&profile(), &history, false, false);
+        assert_eq!(durations(&records), vec![(1800, None)]);
+        assert_eq!(records[0].character_class, None);
+    }
+
+    #[test]
+    fn keeps_full_run_records_apart_and_skips_checkpoints() {
+        let mut bests = PersonalBests::default();
+        let history = vec![
+            clear("1", at(10, 18), 1800),
+            checkpoint("2", at(11, 18), 900),
+        ];
+
+        let records = bests.update(&profile(), &history, false, true);
+        assert_eq!(durations(&records), vec![(1800, None)]);
+        assert!(records[0].full_runs_only);
+
+        let records = bests.update(&profile(), &history, false, false);
+        assert_eq!(durations(&records), vec![(1800, None), (900, Some(1800))]);
+        assert!(!records[0].full_runs_only);
+
+        let faster = vec![checkpoint("3", at(12, 18), 600)];
+        assert!(bests.update(&profile(), &faster, false, true).is_empty());
+    }
+
+    #[test]
+    fn only_announces_records_beaten_after_startup() {
+        let started_at = at(12, 20);
+        let mut tracker = PersonalBestTracker {
+            records: PersonalBests::default(),
+            started_at,
+        };
+
+        // Loading the history at startup and clears imported later stay quiet.
+        let loaded = vec![clear("1", at(10, 18), 2000), clear("2", at(11, 18), 1800)];
+        let records = tracker.records.update(&profile(), &loaded, false, false);
+        assert_eq!(records.len(), 2);
+        assert!(tracker.announced(records).is_empty());
+
+        let imported = vec![clear("3", at(9, 18), 1700)];
+        let records = tracker.records.update(&profile(), &imported, false, false);
+        assert_eq!(records.len(), 1);
+        assert!(tracker.announced(records).is_empty());
+
+        let before = vec![clear("4", started_at - Duration::seconds(1601), 1600)];
+        let records = tracker.records.update(&profile(), &before, false, false);
+        assert_eq!(durations(&records), vec![(1600, Some(1700))]);
+        assert!(tracker.announced(records).is_empty());
+
+        // A run that started before startup but finished with it counts.
+        let after = vec![clear("5", started_at - Duration::seconds(1500), 1500)];
+        let records = tracker.records.update(&profile(), &after, false, false);
+        let announced = tracker.announced(records);
+        assert_eq!(durations(&announced), vec![(1500, Some(1600))]);
+    }
+}
@@ -0,0 +1,173 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    api::{responses::GlobalAlert, Api},
+    clock::Clock,
+    consts::{APP_NAME, APP_VER, DESTINY2_SYSTEM, HEALTH_POLL_INTERVAL},
+    events::publish_event,
+    metrics::{self, POLL_CYCLES},
+    ApiHealthContainer,
+};
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    Healthy,
+    Alerts,
+    Disabled,
+    Unreachable,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiHealth {
+    pub status: HealthStatus,
+    pub destiny2_enabled: bool,
+    pub alerts: Vec<GlobalAlert>,
+    pub error: Option<String>,
+    pub last_checked: Option<DateTime<Utc>>,
+}
+
+impl Default for ApiHealth {
+    fn default() -> Self {
+        Self {
+            status: HealthStatus::Healthy,
+            destiny2_enabled: true,
+            alerts: Vec::new(),
+            error: None,
+            last_checked: None,
+        }
+    }
+}
+
+impl ApiHealth {
+    pub fn summary(&self) -> &'static str {
+        match self.status {
+            HealthStatus::Healthy => "Bungie API is online",
+            HealthStatus::Alerts => "Bungie API has active alerts",
+            HealthStatus::Disabled => "Bungie API is down for maintenance",
+            HealthStatus::Unreachable => "Bungie API is unreachable",
+        }
+    }
+
+    fn same_state(&self, other: &Self) -> bool {
+        self.status == other.status
+            && self.destiny2_enabled == other.destiny2_enabled
+            && self.alerts == other.alerts
+            && self.error == other.error
+    }
+}
+
+pub async fn health_poller(handle: AppHandle, clock: Arc<dyn Clock>) {
+    loop {
+        let health = check_health(&handle.state::<Api>(), &*clock).await;
+
+        let changed = {
+            let container = handle.state::<ApiHealthContainer>();
+            let mut lock = container.0.lock().await;
+            let changed = !lock.same_state(&health);
+            *lock = health.clone();
+            changed
+        };
+
+        if changed {
+            send_health_update(&handle, &health);
+        }
+
+        metrics::increment(POLL_CYCLES, &[("poller", "health")]);
+        clock.sleep(HEALTH_POLL_INTERVAL).await;
+    }
+}
+
+async fn check_health(api: &Api, clock: &dyn Clock) -> ApiHealth {
+    let now = Some(clock.now());
+
+    let settings = match api.get_common_settings().await {
+        Ok(s) => s,
+        Err(e) => {
+            return ApiHealth {
+                status: HealthStatus::Unreachable,
+                error: Some(e.to_string()),
+                last_checked: now,
+                ..Default::default()
+            }
+        }
+    };
+
+    let alerts = match api.get_global_alerts().await {
+        Ok(a) => a,
+        Err(e) => {
+            eprintln!("Failed to fetch global alerts: {}", e);
+            Vec::new()
+        }
+    };
+
+    let destiny2_enabled = settings.is_enabled(DESTINY2_SYSTEM);
+
+    let status = if !destiny2_enabled {
+        HealthStatus::Disabled
+    } else if !alerts.is_empty() {
+        HealthStatus::Alerts
+    } else {
+        HealthStatus::Healthy
+    };
+
+    ApiHealth {
+        status,
+        destiny2_enabled,
+        alerts,
+        error: None,
+        last_checked: now,
+    }
+}
+
+fn send_health_update(handle: &AppHandle, health: &ApiHealth) {
+    for label in ["details", "overlay"] {
+        if let Some(w) = handle.get_window(label) {
+            if let Err(e) = w.emit("health_update", health) {
+                eprintln!("Failed to send health update to window {}: {}", label, e);
+            }
+        }
+    }
+
+    publish_event(handle, "health_update", health);
+
+    let tooltip = format!("{APP_NAME} v{APP_VER}\n{}", health.summary());
+
+    if let Err(e) = handle.tray_handle().set_tooltip(&tooltip) {
+        eprintln!("Failed to update tray tooltip: {}", e);
+    }
+}
+
+#[tauri::command]
+pub async fn get_api_health(container: tauri::State<'_, ApiHealthContainer>) -> Result<ApiHealth, ()> {
+    Ok(container.0.lock().await.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::{api::fixtures::FixtureClient, clock::SimulatedClock};
+
+    #[tokio::test]
+    async fn replays_a_health_check() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/replay");
+        let api = Api::new(Arc::new(FixtureClient::from_dir(&dir).unwrap()));
+        let now = Utc.with_ymd_and_hms(2026, 3, 10, 20, 0, 0).unwrap();
+
+        let health = check_health(&api, &SimulatedClock::new(now)).await;
+
+        assert_eq!(health.status, HealthStatus::Alerts);
+        assert!(health.destiny2_enabled);
+        assert_eq!(health.alerts.len(), 1);
+        assert_eq!(health.last_checked, Some(now));
+    }
+}
@@ -1,9 +1,11 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Manager};
 use tokio::time::{sleep, Duration};
 use windows::Media::Control::GlobalSystemMediaTransportControlsSessionManager;
 
-#[derive(Serialize, Clone, Debug, Default)]
+use crate::events::publish_event;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct MediaInfo {
     pub title: String,
@@ -28,24 +30,19 @@ pub async fn media_poller(app_handle: AppHandle) {
                     });
 
                     if changed {
-                        if let Err(e) = app_handle.emit_all("media-update", &info) {
-                        }
+                        send_media_update(&app_handle, &info);
                         last_info = Some(info);
                     }
                 } else if last_info.as_ref().map_or(false, |l| l.has_media) {
                     let empty = MediaInfo::default();
-                    if let Err(e) = app_handle.emit_all("media-update", &empty) {
-                        eprintln!("Failed to emit media update: {}", e);
-                    }
+                    send_media_update(&app_handle, &empty);
                     last_info = Some(empty);
                 }
             }
             Err(_) => {
                 if last_info.as_ref().map_or(false, |l| l.has_media) {
                     let empty = MediaInfo::default();
-                    if let Err(e) = app_handle.emit_all("media-update", &empty) {
-                        eprintln!("Failed to emit media update: {}", e);
-                    }
+                    send_media_update(&app_handle, &empty);
                     last_info = Some(empty);
                 }
             }
@@ -55,6 +52,14 @@ pub async fn media_poller(app_handle: AppHandle) {
     }
 }
 
+fn send_media_update(app_handle: &AppHandle, info: &MediaInfo) {
+    if let Err(e) = app_handle.emit_all("media-update", info) {
+        eprintln!("Failed to emit media update: {}", e);
+    }
+
+    publish_event(app_handle, "media-update", info);
+}
+
 async fn get_current_media_info() -> Result<MediaInfo, String> {
     let manager = GlobalSystemMediaTransportControlsSessionManager::RequestAsync()
         .map_err(|e| e.to_string())?
@@ -11,13 +11,20 @@ use tokio::sync::Mutex;
 
 use crate::{
     api::{
-        requests::BungieResponseError,
-        responses::{ActivityInfo, CompletedActivity, LatestCharacterActivity, ProfileInfo},
+        requests::{BungieErrorKind, BungieResponseError},
+        responses::{ActivityInfo, CharacterInfo, CompletedActivity, ProfileInfo, ProfileSnapshot},
         Api, ApiError, Source,
     },
+    clock::{Clock, SystemClock},
     config::profiles::Profile,
-    consts::{DUNGEON_ACTIVITY_MODE, EXCLUDED_ACTIVITY_HASHES, RAID_ACTIVITY_MODE, STRIKE_ACTIVITY_MODE, LOSTSECTOR_ACTIVITY_MODE, STORY_ACTIVITY_MODE},
-    ConfigContainer,
+    consts::{PLAYERDATA_POLL_INTERVAL, PROFILE_SNAPSHOT_COMPONENTS},
+    events::publish_event,
+    history::{fetch_history, history_cutoff, is_tracked_activity, merge_history, merge_imported},
+    import::load_imported,
+    metrics::{self, POLL_CYCLES},
+    sessions::detect_sessions,
+    ApiHealthContainer, AttemptsContainer, ConfigContainer, PersonalBestsContainer,
+    SessionsContainer,
 };
 
 #[derive(Serialize, Clone, Deserialize)]
@@ -26,6 +33,7 @@ pub struct PlayerData {
     pub current_activity: CurrentActivity,
     pub activity_history: Vec<CompletedActivity>,
     pub profile_info: ProfileInfo,
+    pub characters: Vec<CharacterInfo>,
 }
 
 #[derive(Serialize, Default, Clone, Deserialize)]
@@ -50,13 +58,27 @@ pub struct CurrentActivity {
     pub activity_info: Option<ActivityInfo>,
 }
 
-#[derive(Default)]
 pub struct PlayerDataPoller {
     task_handle: Option<JoinHandle<()>>,
     current_playerdata: Arc<Mutex<PlayerDataStatus>>,
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for PlayerDataPoller {
+    fn default() -> Self {
+        Self::new(Arc::new(SystemClock))
+    }
 }
 
 impl PlayerDataPoller {
+    pub fn new(clock: Arc<dyn Clock>) -> Self {
+        Self {
+            task_handle: None,
+            current_playerdata: Arc::default(),
+            clock,
+        }
+    }
+
     pub async fn reset(&mut self, app_handle: AppHandle) {
         if let Some(t) = self.task_handle.as_ref() {
             t.abort();
@@ -77,6 +99,7 @@ impl PlayerDataPoller {
         }
 
         let playerdata_clone = self.current_playerdata.clone();
+        let clock = self.clock.clone();
 
         self.task_handle = Some(async_runtime::spawn(async move {
             let profile = {
@@ -87,25 +110,41 @@ impl PlayerDataPoller {
                     Some(p) => p.clone(),
                     None => {
                         let mut lock = playerdata_clone.lock().await;
-                        handle_error(&mut lock, "No profile set".to_string(), &app_handle);
+                        handle_error(&mut lock, "No profile set".to_string(), true, &app_handle);
                         send_data_update(&app_handle, lock.clone()).await;
                         return;
                     }
                 }
             };
 
+            let api = app_handle.state::<Api>();
             let mut count = 0;
 
             loop {
-                tokio::time::sleep(Duration::from_secs(2)).await;
+                clock.sleep(PLAYERDATA_POLL_INTERVAL).await;
+                metrics::increment(POLL_CYCLES, &[("poller", "playerdata")]);
+
+                let health = app_handle.state::<ApiHealthContainer>().0.lock().await.clone();
+
+                if !health.destiny2_enabled {
+                    let mut lock = playerdata_clone.lock().await;
+
+                    if lock.error.is_none() {
+                        handle_error(&mut lock, health.summary().to_string(), true, &app_handle);
+                        send_data_update(&app_handle, lock.clone()).await;
+                    }
+
+                    continue;
+                }
 
                 if playerdata_clone.lock().await.last_update.is_none() {
                     let startup_result = async {
-                        let profile_info = {
-                            let api = app_handle.state::<Api>();
-                            let mut lock = api.profile_info_source.lock().await;
-                            lock.get(&profile).await?
-                        };
+                        let snapshot = get_snapshot(&api, &profile).await?;
+
+                        let profile_info = snapshot
+                            .profile_info
+                            .clone()
+                            .ok_or(anyhow!("No profile data for profile"))?;
 
                         let mut current_activity = CurrentActivity {
                             start_date: DateTime::<Utc>::MIN_UTC,
@@ -113,12 +152,13 @@ impl PlayerDataPoller {
                             activity_info: None,
                         };
 
-                        update_current(&app_handle, &mut current_activity, &profile).await?;
+                        update_current(&api, &mut current_activity, &snapshot).await?;
 
                         let playerdata = PlayerData {
                             current_activity,
                             activity_history: Vec::new(),
                             profile_info,
+                            characters: snapshot.characters.clone().unwrap_or_default(),
                         };
 
                         {
@@ -128,19 +168,36 @@ impl PlayerDataPoller {
                             send_data_update(&app_handle, lock.clone()).await;
                         }
 
-                        load_history_incremental(&app_handle, &playerdata_clone, &profile).await?;
+                        load_history_incremental(&app_handle, &api, &*clock, &playerdata_clone, &profile, &snapshot).await?;
 
                         let mut lock = playerdata_clone.lock().await;
+                        if let Some(ref mut last_update) = lock.last_update {
+                            merge_imported(&mut last_update.activity_history, load_imported(&profile));
+                        }
+                        let history = lock.last_update.as_ref().map(|l| l.activity_history.clone()).unwrap_or_default();
                         handle_success(&mut lock);
                         send_data_update(&app_handle, lock.clone()).await;
+                        drop(lock);
+
+                        track_personal_bests(&app_handle, &profile, &history).await;
+                        track_session(&app_handle, &profile, &playerdata, clock.now(), false).await;
+                        track_attempts(&app_handle, &profile, &playerdata.current_activity, clock.now()).await;
 
                         Ok::<(), anyhow::Error>(())
                     }.await;
 
                     if let Err(e) = startup_result {
-                        let mut lock = playerdata_clone.lock().await;
-                        handle_error(&mut lock, e.to_string(), &app_handle);
-                        send_data_update(&app_handle, lock.clone()).await;
+                        let backoff = {
+                            let mut lock = playerdata_clone.lock().await;
+                            let backoff = report_error(&mut lock, &e, &app_handle);
+                            send_data_update(&app_handle, lock.clone()).await;
+                            backoff
+                        };
+
+                        if let Some(b) = backoff {
+                            clock.sleep(b).await;
+                        }
+
                         continue;
                     }
 
@@ -148,33 +205,70 @@ impl PlayerDataPoller {
                     continue;
                 }
 
-                let mut last_update = match playerdata_clone.lock().await.last_update.clone() {
+                let mut polled = match playerdata_clone.lock().await.last_update.clone() {
                     Some(l) => l,
                     None => continue,
                 };
 
-                let res = if count < 5 {
-                    update_current(&app_handle, &mut last_update.current_activity, &profile).await
-                } else {
-                    count = 0;
-                    update_history(&app_handle, &mut last_update.activity_history, &profile).await
+                let mut history_updated = false;
+
+                let res = match get_snapshot(&api, &profile).await {
+                    Ok(snapshot) => {
+                        let characters_changed = update_characters(&mut polled.characters, &snapshot);
+
+                        history_updated = count >= 5;
+
+                        let res = if count < 5 {
+                            update_current(&api, &mut polled.current_activity, &snapshot).await
+                        } else {
+                            count = 0;
+                            update_history(&api, &*clock, &mut polled.activity_history, &profile, &snapshot).await
+                        };
+
+                        res.map(|changed| changed || characters_changed)
+                    }
+                    Err(e) => Err(e),
                 };
 
                 match res {
-                    Ok(true) => {
-                        let mut lock = playerdata_clone.lock().await;
-                        handle_success(&mut lock);
-                        lock.last_update = Some(last_update);
+                    Ok(changed) => {
+                        let last_update = {
+                            let mut lock = playerdata_clone.lock().await;
 
-                        send_data_update(&app_handle, lock.clone()).await;
+                            match lock.last_update {
+                                Some(ref mut live) => {
+                                    apply_poll(live, polled, history_updated);
+                                    live.clone()
+                                }
+                                None => continue,
+                            }
+                        };
+
+                        track_session(&app_handle, &profile, &last_update, clock.now(), history_updated).await;
+                        track_attempts(&app_handle, &profile, &last_update.current_activity, clock.now()).await;
+
+                        if changed {
+                            track_personal_bests(&app_handle, &profile, &last_update.activity_history).await;
+
+                            let mut lock = playerdata_clone.lock().await;
+                            handle_success(&mut lock);
+
+                            send_data_update(&app_handle, lock.clone()).await;
+                        }
                     }
                     Err(e) => {
-                        let mut lock = playerdata_clone.lock().await;
-                        handle_error(&mut lock, e.to_string(), &app_handle);
+                        let backoff = {
+                            let mut lock = playerdata_clone.lock().await;
+                            let backoff = report_error(&mut lock, &e, &app_handle);
 
-                        send_data_update(&app_handle, lock.clone()).await;
+                            send_data_update(&app_handle, lock.clone()).await;
+                            backoff
+                        };
+
+                        if let Some(b) = backoff {
+                            clock.sleep(b).await;
+                        }
                     }
-                    _ => (),
                 }
 
                 count += 1;
@@ -182,6 +276,39 @@ impl PlayerDataPoller {
         }));
     }
 
+    /// Adds imported activities to the loaded history. Before the history has
+    /// loaded there is nothing to merge into, it picks them up on startup.
+    pub async fn merge_imported(&self, handle: &AppHandle, activities: Vec<CompletedActivity>) -> usize {
+        let mut lock = self.current_playerdata.lock().await;
+
+        let added = match lock.last_update {
+            Some(ref mut last_update) => merge_imported(&mut last_update.activity_history, activities),
+            None => 0,
+        };
+
+        if added > 0 {
+            send_data_update(handle, lock.clone()).await;
+        }
+
+        added
+    }
+
+    /// Waits for the poller to finish an update instead of giving up like
+    /// `get_data`, for callers that need an answer straight after an update.
+    pub async fn get_history(&self) -> Option<Vec<CompletedActivity>> {
+        self.current_playerdata
+            .lock()
+            .await
+            .last_update
+            .as_ref()
+            .map(|d| d.activity_history.clone())
+    }
+
+    /// Like `get_history`, with the rest of the last update.
+    pub async fn get_last_update(&self) -> Option<PlayerData> {
+        self.current_playerdata.lock().await.last_update.clone()
+    }
+
     pub fn get_data(&mut self) -> Option<PlayerDataStatus> {
         return match &self.current_playerdata.try_lock() {
             Ok(p) => Some((*p).clone()),
@@ -190,14 +317,122 @@ impl PlayerDataPoller {
     }
 }
 
+/// Runs before the update is sent, so the overlay already knows about a new
+/// record when it shows the clear.
+async fn track_personal_bests(handle: &AppHandle, profile: &Profile, history: &[CompletedActivity]) {
+    let (per_class, full_runs_only) = {
+        let container = handle.state::<ConfigContainer>();
+        let lock = container.0.lock().await;
+        let preferences = lock.get_preferences();
+        (preferences.personal_best_per_class, preferences.personal_best_full_runs_only)
+    };
+
+    let records = {
+        let container = handle.state::<PersonalBestsContainer>();
+        let mut lock = container.0.lock().await;
+        lock.update(profile, history, per_class, full_runs_only)
+    };
+
+    for record in records {
+        for label in ["overlay", "details"] {
+            if let Some(w) = handle.get_window(label) {
+                w.emit("personal_best", &record).unwrap();
+            }
+        }
+
+        publish_event(handle, "personal_best", &record);
+    }
+}
+
+async fn track_attempts(handle: &AppHandle, profile: &Profile, current: &CurrentActivity, now: DateTime<Utc>) {
+    let current = current
+        .activity_info
+        .as_ref()
+        .map(|info| (current.activity_hash, info.activity_modes.as_slice(), current.start_date));
+
+    let container = handle.state::<AttemptsContainer>();
+    let mut lock = container.0.lock().await;
+
+    if lock.observe(profile, current, now) {
+        lock.save();
+    }
+}
+
+/// Follows the game opening and closing. The summary of a session waits for
+/// the history update after the game closed, so its last activity is in it.
+async fn track_session(
+    handle: &AppHandle,
+    profile: &Profile,
+    playerdata: &PlayerData,
+    now: DateTime<Utc>,
+    history_updated: bool,
+) {
+    let container = handle.state::<SessionsContainer>();
+
+    let summary = {
+        let mut lock = container.0.lock().await;
+        lock.observe(&profile.key(), playerdata.current_activity.activity_info.is_some(), now);
+        history_updated && lock.take_summary()
+    };
+
+    if !summary {
+        return;
+    }
+
+    let gap = {
+        let container = handle.state::<ConfigContainer>();
+        let lock = container.0.lock().await;
+        lock.get_preferences().session_gap_minutes
+    };
+
+    let session = {
+        let lock = container.0.lock().await;
+        detect_sessions(&playerdata.activity_history, &lock, chrono::Duration::minutes(gap as i64), now)
+            .into_iter()
+            .next()
+    };
+
+    if let Some(session) = session {
+        if let Some(w) = handle.get_window("details") {
+            w.emit("session_summary", &session).unwrap();
+        }
+
+        publish_event(handle, "session_summary", &session);
+    }
+}
+
+fn report_error(
+    lock: &mut PlayerDataStatus,
+    error: &anyhow::Error,
+    app_handle: &AppHandle,
+) -> Option<Duration> {
+    let api_error = error.downcast_ref::<ApiError>();
+
+    let immediate = match api_error {
+        Some(ApiError::ResponseError(e)) => {
+            !e.is_retryable() || e.kind() == Some(BungieErrorKind::SystemDisabled)
+        }
+        Some(e) => !e.is_retryable(),
+        None => false,
+    };
+
+    handle_error(lock, error.to_string(), immediate, app_handle);
+
+    match api_error {
+        Some(ApiError::ResponseError(e)) => e.retry_after(),
+        _ => None,
+    }
+}
+
 fn handle_error(
     lock: &mut PlayerDataStatus,
     error_message: String,
+    immediate: bool,
     app_handle: &AppHandle,
 ) {
     lock.last_error = Some(error_message.clone());
 
-    if lock.is_startup {
+    if lock.is_startup || immediate {
         lock.error = Some(error_message);
     } else {
         lock.consecutive_error_count += 1;
@@ -224,6 +459,8 @@ async fn send_data_update(handle: &AppHandle, data: PlayerDataStatus) {
         o.emit("playerdata_update", data.clone()).unwrap();
     }
 
+    publish_event(handle, "playerdata_update", &data);
+
     if let Some(ref player_data) = data.last_update {
         if let Some(timer_container) = handle.try_state::<crate::TimerPollerContainer>() {
             let handle_clone = handle.clone();
@@ -235,24 +472,48 @@ async fn send_data_update(handle: &AppHandle, data: PlayerDataStatus) {
     }
 }
 
+async fn get_snapshot(api: &Api, profile: &Profile) -> Result<ProfileSnapshot> {
+    let snapshot = api.get_profile_snapshot(profile, PROFILE_SNAPSHOT_COMPONENTS).await?;
+
+    if let Some(ref profile_info) = snapshot.profile_info {
+        api.profile_info_source
+            .lock()
+            .await
+            .set_profile_info(profile, profile_info.clone());
+    }
+
+    Ok(snapshot)
+}
+
+fn update_characters(last_characters: &mut Vec<CharacterInfo>, snapshot: &ProfileSnapshot) -> bool {
+    match &snapshot.characters {
+        Some(c) if *c != *last_characters => {
+            *last_characters = c.clone();
+            true
+        }
+        _ => false,
+    }
+}
+
 async fn update_current(
-    handle: &AppHandle,
+    api: &Api,
     last_activity: &mut CurrentActivity,
-    profile: &Profile,
+    snapshot: &ProfileSnapshot,
 ) -> Result<bool> {
-    let current_activities = Api::get_profile_activities(profile).await?;
+    let current_activities = snapshot
+        .current_activities
+        .as_ref()
+        .ok_or(anyhow!("No activity data for profile"))?;
 
-    let activities = match current_activities.activities {
+    let activities = match &current_activities.activities {
         Some(a) => a,
         None => bail!("Profile is private"),
     };
 
-    let (characters, activities): (Vec<String>, Vec<LatestCharacterActivity>) =
-        activities.into_iter().unzip();
-
     let latest_activity = activities
-        .into_iter()
+        .values()
         .max()
+        .cloned()
         .ok_or(anyhow!("No character data for profile"))?;
 
     match last_activity
@@ -274,13 +535,6 @@ async fn update_current(
         std::cmp::Ordering::Greater => return Ok(false),
     }
 
-    let api = handle.state::<Api>();
-
-    api.profile_info_source
-        .lock()
-        .await
-        .set_characters(profile, characters);
-
     if latest_activity.current_activity_hash == 0 {
         last_activity.activity_info = None;
         return Ok(true);
@@ -317,21 +571,24 @@ async fn update_current(
 
 async fn load_history_incremental(
     handle: &AppHandle,
+    api: &Api,
+    clock: &dyn Clock,
     playerdata_clone: &Arc<Mutex<PlayerDataStatus>>,
     profile: &Profile,
+    snapshot: &ProfileSnapshot,
 ) -> Result<()> {
-    let api = handle.state::<Api>();
+    let profile_info = snapshot
+        .profile_info
+        .as_ref()
+        .ok_or(anyhow!("No profile data for profile"))?;
 
-
-    let profile_info = api.profile_info_source.lock().await.get(profile).await?;
-
-    let character_classes = Api::get_character_classes(profile).await?;
+    let character_classes = snapshot.character_classes();
 
     let mut all_activities: Vec<CompletedActivity> = Vec::new();
     let mut master_list: Vec<CompletedActivity> = Vec::new();
     let mut activities_sent = 0;
     let mut total_api_calls = 0;
-    let cutoff = Utc::now() - chrono::Duration::days(30);
+    let cutoff = history_cutoff(clock.now());
 
     const INSTANT_COUNT: usize = 20;
     const BATCH_SIZE: usize = 50;
@@ -346,7 +603,7 @@ async fn load_history_incremental(
             total_api_calls += 1;
 
             let api_start = std::time::Instant::now();
-            let history = Api::get_activity_history(profile, character_id, page).await?;
+            let history = api.get_activity_history(profile, character_id, page).await?;
             let api_duration = api_start.elapsed();
 
             let activities = match history.activities {
@@ -364,13 +621,7 @@ async fn load_history_incremental(
             for activity in activities.into_iter() {
                 if activity.period < cutoff {
                     includes_past_cutoff = true;
-                } else if activity.modes.iter().any(|m| {
-                    *m == RAID_ACTIVITY_MODE ||
-                    *m == DUNGEON_ACTIVITY_MODE ||
-                    *m == STRIKE_ACTIVITY_MODE ||
-                    *m == LOSTSECTOR_ACTIVITY_MODE ||
-                    *m == STORY_ACTIVITY_MODE
-                }) && !EXCLUDED_ACTIVITY_HASHES.contains(&activity.activity_hash) {
+                } else if is_tracked_activity(&activity) {
                     let mut activity_with_class = activity;
                     activity_with_class.character_class = character_class.clone();
                     all_activities.push(activity_with_class);
@@ -427,7 +678,7 @@ async fn load_history_incremental(
                 }
 
                 if activities_sent < master_list.len() {
-                    tokio::time::sleep(Duration::from_millis(200)).await;
+                    clock.sleep(Duration::from_millis(200)).await;
                 }
             }
 
@@ -456,7 +707,7 @@ async fn load_history_incremental(
         }
         activities_sent += send_count;
         if activities_sent < master_list.len() {
-            tokio::time::sleep(Duration::from_millis(200)).await;
+            clock.sleep(Duration::from_millis(200)).await;
         }
     }
 
@@ -470,102 +721,126 @@ async fn load_history_incremental(
     Ok(())
 }
 
+/// Copies what a poll fetched into the live data. The poll works on a copy
+/// while it waits on the API, so activities imported in the meantime are only
+/// in the live history and have to be kept.
+fn apply_poll(live: &mut PlayerData, polled: PlayerData, history_updated: bool) {
+    live.current_activity = polled.current_activity;
+    live.characters = polled.characters;
+
+    if history_updated {
+        merge_history(&mut live.activity_history, polled.activity_history);
+    }
+}
+
 async fn update_history(
-    handle: &AppHandle,
+    api: &Api,
+    clock: &dyn Clock,
     last_history: &mut Vec<CompletedActivity>,
     profile: &Profile,
+    snapshot: &ProfileSnapshot,
 ) -> Result<bool> {
-    let api = handle.state::<Api>();
+    let past_activities = fetch_history(api, profile, snapshot, history_cutoff(clock.now())).await?;
 
-    let profile_info = api.profile_info_source.lock().await.get(profile).await?;
+    Ok(merge_history(last_history, past_activities))
+}
 
-    let character_classes = Api::get_character_classes(profile).await?;
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
 
-    let mut past_activities: Vec<CompletedActivity> = Vec::new();
+    use chrono::TimeZone;
 
-    let cutoff = {
-        Utc::now() - chrono::Duration::days(30)
+    use super::*;
+    use crate::{
+        api::{fixtures::FixtureClient, responses::format_duration},
+        clock::SimulatedClock,
     };
 
-    for character_id in profile_info.character_ids.iter() {
-        let character_class = character_classes.get(character_id).cloned();
-        let mut page = 0;
+    fn replay_api() -> Api {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/replay");
 
-        loop {
-            let history = Api::get_activity_history(profile, character_id, page).await?;
-
-            let activities = match history.activities {
-                Some(a) => a,
-                None => break,
-            };
+        Api::new(Arc::new(FixtureClient::from_dir(&dir).unwrap()))
+    }
 
-            let mut includes_past_cutoff = false;
+    #[tokio::test]
+    async fn replays_a_poll_cycle() {
+        let api = replay_api();
+        let clock = SimulatedClock::new(Utc.with_ymd_and_hms(2026, 3, 10, 20, 0, 0).unwrap());
+        let profile = Profile {
+            account_platform: 3,
+            account_id: "4611686018467654321".to_string(),
+        };
 
-            for activity in activities.into_iter() {
-                if activity.period < cutoff {
-                    includes_past_cutoff = true;
-                } else if activity.modes.iter().any(|m| {
-                    *m == RAID_ACTIVITY_MODE ||
-                    *m == DUNGEON_ACTIVITY_MODE ||
-                    *m == STRIKE_ACTIVITY_MODE ||
-                    *m == LOSTSECTOR_ACTIVITY_MODE ||
-                    *m == STORY_ACTIVITY_MODE
-                }) && !EXCLUDED_ACTIVITY_HASHES.contains(&activity.activity_hash) {
-                    let mut activity_with_class = activity;
-                    activity_with_class.character_class = character_class.clone();
-                    past_activities.push(activity_with_class);
-                }
-            }
+        let snapshot = get_snapshot(&api, &profile).await.unwrap();
 
-            if includes_past_cutoff {
-                break;
-            }
+        let mut characters = Vec::new();
+        assert!(update_characters(&mut characters, &snapshot));
+        assert!(!update_characters(&mut characters, &snapshot));
 
-            page += 1;
-        }
+        let mut current = CurrentActivity {
+            start_date: DateTime::<Utc>::MIN_UTC,
+            activity_hash: 0,
+            activity_info: None,
+        };
+        assert!(update_current(&api, &mut current, &snapshot).await.unwrap());
+        assert!(!update_current(&api, &mut current, &snapshot).await.unwrap());
+        assert_eq!(current.activity_hash, 3881495763);
+        assert_eq!(current.activity_info.unwrap().name, "Vault of Glass");
+
+        let mut history = Vec::new();
+        assert!(update_history(&api, &clock, &mut history, &profile, &snapshot).await.unwrap());
+        assert!(!update_history(&api, &clock, &mut history, &profile, &snapshot).await.unwrap());
+        assert_eq!(history.len(), 2);
     }
 
-    let has_new_data = if last_history.is_empty() {
-        !past_activities.is_empty()
-    } else {
-        let has_new_combo = past_activities.iter().any(|new| {
-            !last_history.iter().any(|old| {
-                old.instance_id == new.instance_id && old.character_class == new.character_class
-            })
-        });
-        
-        let has_newer_timestamp = if let (Some(last), Some(new)) = 
-            (last_history.iter().max(), past_activities.iter().max()) {
-            new > last
-        } else {
-            false
+    #[tokio::test]
+    async fn keeps_activities_imported_during_a_poll() {
+        let api = replay_api();
+        let clock = SimulatedClock::new(Utc.with_ymd_and_hms(2026, 3, 10, 20, 0, 0).unwrap());
+        let profile = Profile {
+            account_platform: 3,
+            account_id: "4611686018467654321".to_string(),
         };
-        
-        has_new_combo || has_newer_timestamp
-    };
-    
-    if !has_new_data {
-        return Ok(false);
-    }
 
-    past_activities.sort();
-    past_activities.reverse();
-
-    let mut merged_activities: Vec<CompletedActivity> = last_history.clone();
-    for new_activity in past_activities {
-        let existing_idx = merged_activities.iter().position(|a| {
-            a.instance_id == new_activity.instance_id && a.character_class == new_activity.character_class
-        });
-        
-        if let Some(idx) = existing_idx {
-            merged_activities[idx] = new_activity;
-        } else {
-            merged_activities.push(new_activity);
-        }
-    }
-    merged_activities.sort();
-    merged_activities.reverse();
-    *last_history = merged_activities;
+        let snapshot = get_snapshot(&api, &profile).await.unwrap();
+
+        let mut live = PlayerData {
+            current_activity: CurrentActivity {
+                start_date: DateTime::<Utc>::MIN_UTC,
+                activity_hash: 0,
+                activity_info: None,
+            },
+            activity_history: Vec::new(),
+            profile_info: snapshot.profile_info.clone().unwrap(),
+            characters: Vec::new(),
+        };
 
-    Ok(true)
+        let mut polled = live.clone();
+
+        let imported = CompletedActivity {
+            period: Utc.with_ymd_and_hms(2026, 3, 8, 18, 0, 0).unwrap(),
+            instance_id: "imported".to_string(),
+            activity_hash: 3881495763,
+            modes: vec![4],
+            completed: true,
+            failed: false,
+            activity_duration: format_duration(3600),
+            activity_duration_seconds: 3600,
+            start_seconds: 0,
+            character_class: None,
+        };
+        assert_eq!(merge_imported(&mut live.activity_history, vec![imported]), 1);
+
+        update_characters(&mut polled.characters, &snapshot);
+        update_current(&api, &mut polled.current_activity, &snapshot).await.unwrap();
+        update_history(&api, &clock, &mut polled.activity_history, &profile, &snapshot).await.unwrap();
+
+        apply_poll(&mut live, polled, true);
+
+        assert_eq!(live.activity_history.len(), 3);
+        assert!(live.activity_history.iter().any(|a| a.instance_id == "imported"));
+        assert_eq!(live.current_activity.activity_hash, 3881495763);
+        assert!(!live.characters.is_empty());
+    }
 }
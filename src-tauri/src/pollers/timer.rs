@@ -1,12 +1,14 @@
 use std::{sync::Arc, time::Duration};
 use chrono::{DateTime, Utc};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Manager};
 use tokio::sync::Mutex;
 
 use crate::{
     api::responses::CompletedActivity,
-    consts::EXCLUDED_ACTIVITY_HASHES,
+    clock::{Clock, SystemClock},
+    consts::{EXCLUDED_ACTIVITY_HASHES, PERSISTENT_START_BUFFER_SECS},
+    events::publish_event,
     pollers::playerdata::{CurrentActivity, PlayerData},
 };
 
@@ -26,6 +28,20 @@ pub enum TimerMode {
     Persistent,
 }
 
+/// Published on the event bus as `timer_lifecycle` whenever the timer starts,
+/// splits, finishes or is cleared, for integrations that mirror the timer.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TimerLifecycle {
+    #[serde(rename_all = "camelCase")]
+    Started { start_date: DateTime<Utc> },
+    Split,
+    #[serde(rename_all = "camelCase")]
+    Completed { duration_seconds: usize },
+    Stopped,
+    Reset,
+}
+
 #[derive(Clone)]
 pub struct TimerConfig {
     pub display_milliseconds: bool,
@@ -44,6 +60,7 @@ impl Default for TimerConfig {
 pub struct Timer {
     state: Arc<Mutex<TimerState>>,
     config: TimerConfig,
+    clock: Arc<dyn Clock>,
     start_time: Option<DateTime<Utc>>,
     end_time: Option<DateTime<Utc>>,
     last_activity: Option<CurrentActivity>,
@@ -53,7 +70,7 @@ pub struct Timer {
 }
 
 impl Timer {
-    pub fn new(config: TimerConfig) -> Self {
+    pub fn new(config: TimerConfig, clock: Arc<dyn Clock>) -> Self {
         Self {
             state: Arc::new(Mutex::new(TimerState {
                 time_text: String::new(),
@@ -62,6 +79,7 @@ impl Timer {
                 mode: TimerMode::Default,
             })),
             config,
+            clock,
             start_time: None,
             end_time: None,
             last_activity: None,
@@ -87,11 +105,17 @@ impl Timer {
 
     pub async fn start_activity(&mut self, activity: &CurrentActivity, activity_history: &[CompletedActivity], app_handle: &AppHandle) {
         if self.should_start_activity(activity, activity_history).await {
+            let is_new_start = self.start_time != Some(activity.start_date) || self.end_time.is_some();
+
             self.last_activity = Some(activity.clone());
             self.start_time = Some(activity.start_date);
             self.end_time = None;
 
             self.start_timer_interval(app_handle).await;
+
+            if is_new_start {
+                publish_event(app_handle, "timer_lifecycle", &TimerLifecycle::Started { start_date: activity.start_date });
+            }
         }
     }
 
@@ -105,13 +129,8 @@ impl Timer {
                 }
             }
 
-            if !activity_history.is_empty() {
-                let last_activity_in_history = &activity_history[0];
-                let last_activity_end_time = last_activity_in_history.period + chrono::Duration::seconds(last_activity_in_history.activity_duration_seconds as i64);
-                let new_activity_start_time = activity.start_date;
-                let buffer_time = chrono::Duration::seconds(40);
-
-                if new_activity_start_time <= (last_activity_end_time - buffer_time) {
+            if let Some(last_activity_in_history) = activity_history.first() {
+                if !starts_after_buffer(activity, last_activity_in_history) {
                     return false;
                 }
             }
@@ -127,50 +146,56 @@ impl Timer {
             return;
         }
 
+        drop(state);
+
         let most_recent_activity = &activity_history[0];
-        let completion_key = format!("{}_{}", most_recent_activity.activity_hash, most_recent_activity.period);
 
-        if self.known_completions.contains(&completion_key) {
+        if !self.record_completion(most_recent_activity) {
             return;
         }
 
-        self.known_completions.insert(completion_key.clone());
-        self.last_completed_activity_hash = Some(most_recent_activity.activity_hash);
+        if let Some(handle) = self.interval_handle.take() {
+            handle.abort();
+        }
 
-        if let Some(ref last_activity) = self.last_activity {
-            if most_recent_activity.activity_hash == last_activity.activity_hash {
-                drop(state);
+        self.end_time = Some(self.clock.now());
+        {
+            let mut state = self.state.lock().await;
+            state.is_active = false;
+        }
 
-                if let Some(handle) = self.interval_handle.take() {
-                    handle.abort();
-                }
+        self.emit_state_update(app_handle).await;
+        publish_event(app_handle, "timer_lifecycle", &TimerLifecycle::Completed { duration_seconds: most_recent_activity.activity_duration_seconds });
 
-                self.end_time = Some(Utc::now());
-                {
-                    let mut state = self.state.lock().await;
-                    state.is_active = false;
-                }
+        let state_clone = self.state.clone();
+        let app_handle_clone = app_handle.clone();
+        let clock = self.clock.clone();
+        tokio::spawn(async move {
+            clock.sleep(Duration::from_secs(1)).await;
+            let mut state = state_clone.lock().await;
+            state.time_text.clear();
+            state.ms_text.clear();
 
-                self.emit_state_update(app_handle).await;
+            send_timer_state(&app_handle_clone, &state);
+        });
 
-                let state_clone = self.state.clone();
-                let app_handle_clone = app_handle.clone();
-                tokio::spawn(async move {
-                    tokio::time::sleep(Duration::from_secs(1)).await;
-                    let mut state = state_clone.lock().await;
-                    state.time_text.clear();
-                    state.ms_text.clear();
+        self.last_activity = None;
+    }
 
-                    if let Some(window) = app_handle_clone.get_window("details") {
-                        let _ = window.emit("timer-state-update", &*state);
-                    }
-                    if let Some(window) = app_handle_clone.get_window("overlay") {
-                        let _ = window.emit("timer-state-update", &*state);
-                    }
-                });
+    /// Remembers a completion so it is only handled once, and reports whether it
+    /// finishes the activity the timer is currently tracking.
+    fn record_completion(&mut self, activity: &CompletedActivity) -> bool {
+        let completion_key = format!("{}_{}", activity.activity_hash, activity.period);
 
-                self.last_activity = None;
-            }
+        if !self.known_completions.insert(completion_key) {
+            return false;
+        }
+
+        self.last_completed_activity_hash = Some(activity.activity_hash);
+
+        match self.last_activity {
+            Some(ref last_activity) => last_activity.activity_hash == activity.activity_hash,
+            None => false,
         }
     }
 
@@ -192,6 +217,10 @@ impl Timer {
                 state.ms_text.clear();
             }
         } else {
+            if self.start_time.is_some() {
+                publish_event(app_handle, "timer_lifecycle", &TimerLifecycle::Stopped);
+            }
+
             let mut state = self.state.lock().await;
             state.is_active = false;
             state.time_text.clear();
@@ -222,6 +251,7 @@ impl Timer {
         self.known_completions.clear();
 
         self.emit_state_update(app_handle).await;
+        publish_event(app_handle, "timer_lifecycle", &TimerLifecycle::Reset);
     }
 
     pub async fn clear_activity(&mut self, app_handle: &AppHandle) {
@@ -241,6 +271,7 @@ impl Timer {
         self.known_completions.clear();
 
         self.emit_state_update(app_handle).await;
+        publish_event(app_handle, "timer_lifecycle", &TimerLifecycle::Reset);
     }
 
     pub async fn clear_and_restart_activity(&mut self, player_data: &PlayerData, app_handle: &AppHandle) {
@@ -277,6 +308,13 @@ impl Timer {
         self.end_time = None;
 
         self.start_timer_interval(app_handle).await;
+        publish_event(app_handle, "timer_lifecycle", &TimerLifecycle::Started { start_date: activity.start_date });
+    }
+
+    pub fn split(&self, app_handle: &AppHandle) {
+        if self.start_time.is_some() && self.end_time.is_none() {
+            publish_event(app_handle, "timer_lifecycle", &TimerLifecycle::Split);
+        }
     }
 
     pub fn is_tracking_activity(&self, activity_hash: usize) -> bool {
@@ -317,6 +355,7 @@ impl Timer {
         let start_time = self.start_time;
         let update_rate = self.config.update_rate;
         let app_handle_clone = app_handle.clone();
+        let clock = self.clock.clone();
 
         {
             let mut state = state_clone.lock().await;
@@ -327,7 +366,7 @@ impl Timer {
             let mut tick_count = 0u64;
 
             loop {
-                tokio::time::sleep(Duration::from_millis(1000 / update_rate)).await;
+                clock.sleep(Duration::from_millis(1000 / update_rate)).await;
                 tick_count += 1;
 
                 if tick_count > 3600 * update_rate {
@@ -337,27 +376,7 @@ impl Timer {
                 }
 
                 if let Some(start_time) = start_time {
-                    let elapsed = Utc::now() - start_time;
-                    let millis = elapsed.num_milliseconds() as u64;
-
-                    let (time_text, ms_text) = if millis > 0 {
-                        let total_seconds = millis / 1000;
-                        let hours = total_seconds / 3600;
-                        let minutes = (total_seconds % 3600) / 60;
-                        let seconds = total_seconds % 60;
-                        let remaining_millis = millis % 1000;
-
-                        let time_text = if hours > 0 {
-                            format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
-                        } else {
-                            format!("{:02}:{:02}", minutes, seconds)
-                        };
-
-                        let ms_text = format!(":{:02}", remaining_millis / 10);
-                        (time_text, ms_text)
-                    } else {
-                        (String::new(), String::new())
-                    };
+                    let (time_text, ms_text) = format_elapsed(clock.now() - start_time);
 
                     {
                         let mut state = state_clone.lock().await;
@@ -366,12 +385,7 @@ impl Timer {
                     }
 
                     if let Ok(state_guard) = state_clone.try_lock() {
-                        if let Some(window) = app_handle_clone.get_window("details") {
-                            let _ = window.emit("timer-state-update", &*state_guard);
-                        }
-                        if let Some(window) = app_handle_clone.get_window("overlay") {
-                            let _ = window.emit("timer-state-update", &*state_guard);
-                        }
+                        send_timer_state(&app_handle_clone, &state_guard);
                     }
                 } else {
                     break;
@@ -384,12 +398,7 @@ impl Timer {
 
     async fn emit_state_update(&self, app_handle: &AppHandle) {
         if let Ok(state) = self.state.try_lock() {
-            if let Some(window) = app_handle.get_window("details") {
-                let _ = window.emit("timer-state-update", &*state);
-            }
-            if let Some(window) = app_handle.get_window("overlay") {
-                let _ = window.emit("timer-state-update", &*state);
-            }
+            send_timer_state(app_handle, &state);
         }
     }
 
@@ -397,6 +406,61 @@ impl Timer {
         let state = self.state.lock().await;
         state.clone()
     }
+
+    pub fn elapsed_seconds(&self) -> Option<i64> {
+        let start_time = self.start_time?;
+        let end_time = self.end_time.unwrap_or_else(|| self.clock.now());
+
+        Some((end_time - start_time).num_seconds().max(0))
+    }
+}
+
+/// Formats elapsed time as `MM:SS` (or `HH:MM:SS` past the hour) plus the
+/// hundredths as `:cc`. Nothing is shown until the first millisecond passes.
+fn format_elapsed(elapsed: chrono::Duration) -> (String, String) {
+    let millis = elapsed.num_milliseconds();
+
+    if millis <= 0 {
+        return (String::new(), String::new());
+    }
+
+    let total_seconds = millis / 1000;
+    let hours = total_seconds / 3600;
+    let minutes = (total_seconds % 3600) / 60;
+    let seconds = total_seconds % 60;
+    let remaining_millis = millis % 1000;
+
+    let time_text = if hours > 0 {
+        format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{:02}:{:02}", minutes, seconds)
+    };
+
+    (time_text, format!(":{:02}", remaining_millis / 10))
+}
+
+/// In persistent mode a new activity only starts the timer once it begins
+/// later than the buffer before the previous activity's end, so the tail of a
+/// run that was just reported doesn't restart it.
+fn starts_after_buffer(activity: &CurrentActivity, last_completed: &CompletedActivity) -> bool {
+    let last_end_time = last_completed
+        .period
+        .checked_add_signed(chrono::Duration::seconds(last_completed.activity_duration_seconds as i64))
+        .unwrap_or(DateTime::<Utc>::MAX_UTC);
+    let buffer_time = chrono::Duration::seconds(PERSISTENT_START_BUFFER_SECS);
+
+    activity.start_date > last_end_time - buffer_time
+}
+
+fn send_timer_state(app_handle: &AppHandle, state: &TimerState) {
+    if let Some(window) = app_handle.get_window("details") {
+        let _ = window.emit("timer-state-update", state);
+    }
+    if let Some(window) = app_handle.get_window("overlay") {
+        let _ = window.emit("timer-state-update", state);
+    }
+
+    publish_event(app_handle, "timer-state-update", state);
 }
 
 pub struct TimerPoller {
@@ -406,14 +470,14 @@ pub struct TimerPoller {
 
 impl Default for TimerPoller {
     fn default() -> Self {
-        Self::new(TimerConfig::default())
+        Self::new(TimerConfig::default(), Arc::new(SystemClock))
     }
 }
 
 impl TimerPoller {
-    pub fn new(config: TimerConfig) -> Self {
+    pub fn new(config: TimerConfig, clock: Arc<dyn Clock>) -> Self {
         Self {
-            timer: Arc::new(Mutex::new(Timer::new(config))),
+            timer: Arc::new(Mutex::new(Timer::new(config, clock))),
             task_handle: None,
         }
     }
@@ -461,8 +525,178 @@ impl TimerPoller {
         timer.clear_and_restart_activity(player_data, app_handle).await;
     }
 
+    pub async fn get_state(&self) -> TimerState {
+        let timer = self.timer.lock().await;
+        timer.get_state().await
+    }
+
+    pub async fn get_elapsed_seconds(&self) -> Option<i64> {
+        let timer = self.timer.lock().await;
+        timer.elapsed_seconds()
+    }
+
+    pub async fn split_timer(&self, app_handle: &AppHandle) {
+        let timer = self.timer.lock().await;
+        timer.split(app_handle);
+    }
+
     pub async fn reset_timer(&self, app_handle: &AppHandle) {
         let mut timer = self.timer.lock().await;
         timer.reset(app_handle).await;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::{
+        api::responses::format_duration, clock::SimulatedClock, consts::RAID_ACTIVITY_MODE,
+    };
+
+    const RAID: usize = 3881495763;
+
+    fn ms(millis: i64) -> chrono::Duration {
+        chrono::Duration::milliseconds(millis)
+    }
+
+    fn current(start_date: DateTime<Utc>) -> CurrentActivity {
+        CurrentActivity {
+            start_date,
+            activity_hash: RAID,
+            activity_info: None,
+        }
+    }
+
+    fn completed(period: DateTime<Utc>, duration_seconds: usize) -> CompletedActivity {
+        CompletedActivity {
+            period,
+            instance_id: "13700000001".to_string(),
+            activity_hash: RAID,
+            modes: vec![RAID_ACTIVITY_MODE],
+            completed: true,
+            failed: false,
+            activity_duration: format_duration(duration_seconds),
+            activity_duration_seconds: duration_seconds,
+            start_seconds: 0,
+            character_class: Some("Hunter".to_string()),
+        }
+    }
+
+    async fn persistent_timer(clock: Arc<SimulatedClock>) -> Timer {
+        let timer = Timer::new(TimerConfig::default(), clock);
+        timer.state.lock().await.mode = TimerMode::Persistent;
+        timer
+    }
+
+    #[test]
+    fn formats_elapsed_time_to_the_hundredth() {
+        let text = |millis| {
+            let (time, ms_text) = format_elapsed(ms(millis));
+            format!("{time}{ms_text}")
+        };
+
+        assert_eq!(text(-1), "");
+        assert_eq!(text(0), "");
+        assert_eq!(text(1), "00:00:00");
+        assert_eq!(text(9), "00:00:00");
+        assert_eq!(text(10), "00:00:01");
+        assert_eq!(text(999), "00:00:99");
+        assert_eq!(text(1_000), "00:01:00");
+        assert_eq!(text(59_999), "00:59:99");
+        assert_eq!(text(3_599_999), "59:59:99");
+        assert_eq!(text(3_600_000), "01:00:00:00");
+    }
+
+    #[test]
+    fn formats_raids_longer_than_a_day() {
+        let (time, ms_text) = format_elapsed(chrono::Duration::hours(26) + ms(61_005));
+
+        assert_eq!(time, "26:01:01");
+        assert_eq!(ms_text, ":00");
+    }
+
+    #[tokio::test]
+    async fn persistent_mode_waits_out_the_start_buffer() {
+        let start = Utc.with_ymd_and_hms(2026, 3, 10, 18, 0, 0).unwrap();
+        let clock = Arc::new(SimulatedClock::new(start));
+        let timer = persistent_timer(clock).await;
+
+        let history = [completed(start, 2712)];
+        let end = start + chrono::Duration::seconds(2712);
+        let buffer = chrono::Duration::seconds(PERSISTENT_START_BUFFER_SECS);
+
+        let on_buffer = current(end - buffer);
+        let after_buffer = current(end - buffer + ms(1));
+
+        assert!(!timer.should_start_activity(&on_buffer, &history).await);
+        assert!(timer.should_start_activity(&after_buffer, &history).await);
+        assert!(timer.should_start_activity(&on_buffer, &[]).await);
+    }
+
+    #[tokio::test]
+    async fn default_mode_ignores_the_start_buffer() {
+        let start = Utc.with_ymd_and_hms(2026, 3, 10, 18, 0, 0).unwrap();
+        let timer = Timer::new(TimerConfig::default(), Arc::new(SimulatedClock::new(start)));
+
+        let history = [completed(start, 2712)];
+
+        assert!(timer.should_start_activity(&current(start), &history).await);
+    }
+
+    #[tokio::test]
+    async fn records_each_completion_once() {
+        let start = Utc.with_ymd_and_hms(2026, 3, 10, 18, 0, 0).unwrap();
+        let clock = Arc::new(SimulatedClock::new(start));
+        let mut timer = persistent_timer(clock).await;
+        timer.last_activity = Some(current(start));
+
+        let clear = completed(start, 2712);
+        let next_clear = completed(start + ms(1), 2712);
+
+        assert!(timer.record_completion(&clear));
+        assert!(!timer.record_completion(&clear));
+        assert!(timer.was_recently_completed(RAID));
+
+        timer.last_activity = None;
+        assert!(!timer.record_completion(&next_clear));
+        assert_eq!(timer.known_completions.len(), 2);
+    }
+
+    #[test]
+    fn elapsed_seconds_counts_across_midnight() {
+        let start = Utc.with_ymd_and_hms(2026, 3, 10, 23, 59, 59).unwrap() + ms(500);
+        let clock = Arc::new(SimulatedClock::new(start));
+        let mut timer = Timer::new(TimerConfig::default(), clock.clone());
+
+        assert_eq!(timer.elapsed_seconds(), None);
+
+        timer.start_time = Some(start);
+        assert_eq!(timer.elapsed_seconds(), Some(0));
+
+        clock.advance(Duration::from_millis(999));
+        assert_eq!(timer.elapsed_seconds(), Some(0));
+
+        clock.advance(Duration::from_millis(1));
+        assert_eq!(timer.elapsed_seconds(), Some(1));
+
+        // A raid that runs well past midnight keeps counting from its start.
+        clock.set(Utc.with_ymd_and_hms(2026, 3, 11, 2, 30, 0).unwrap());
+        assert_eq!(timer.elapsed_seconds(), Some(9000));
+
+        timer.end_time = Some(start + ms(1_500));
+        assert_eq!(timer.elapsed_seconds(), Some(1));
+    }
+
+    #[test]
+    fn elapsed_seconds_never_goes_negative() {
+        let start = Utc.with_ymd_and_hms(2026, 3, 10, 20, 0, 0).unwrap();
+        let clock = Arc::new(SimulatedClock::new(start - ms(1)));
+        let mut timer = Timer::new(TimerConfig::default(), clock);
+
+        timer.start_time = Some(start);
+
+        assert_eq!(timer.elapsed_seconds(), Some(0));
+    }
+}
@@ -0,0 +1,533 @@
+use std::{collections::HashMap, net::SocketAddr, sync::Arc};
+
+use anyhow::Result;
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Query, State,
+    },
+    http::{
+        header::{AUTHORIZATION, CONTENT_TYPE, HOST, ORIGIN},
+        HeaderMap, Request, StatusCode,
+    },
+    middleware::{self, Next},
+    response::{Html, IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use chrono::{DateTime, Duration, Utc};
+use tauri::{
+    async_runtime::{self, JoinHandle},
+    AppHandle, Manager,
+};
+use tokio::sync::{broadcast, Mutex};
+
+use crate::{
+    api::{responses::CompletedActivity, Api},
+    attempts::{attempt_report, AttemptReport},
+    config::preferences::Preferences,
+    events::{AppEvent, AppEvents},
+    filter::HistoryFilter,
+    metrics::{self, Labels},
+    personal_bests::PersonalBest,
+    pollers::{
+        health::ApiHealth,
+        media::{get_current_media, MediaInfo},
+        playerdata::PlayerDataStatus,
+        timer::TimerState,
+    },
+    sessions::{detect_sessions, PlaySession},
+    stats::{compute_stats, HistoryStats},
+    ApiHealthContainer, AttemptsContainer, ClockContainer, ConfigContainer, PersonalBestsContainer,
+    PlayerDataPollerContainer, SessionsContainer, TimerPollerContainer,
+};
+
+const BROWSER_OVERLAY: &str = include_str!("../assets/browser-overlay.html");
+
+#[derive(Default)]
+pub struct LocalApiHandle(Mutex<Option<JoinHandle<()>>>);
+
+struct ServerState {
+    handle: AppHandle,
+    token: String,
+    port: u16,
+}
+
+impl ServerState {
+    fn now(&self) -> DateTime<Utc> {
+        self.handle.state::<ClockContainer>().0.now()
+    }
+}
+
+type HandlerResult<T> = Result<Json<T>, (StatusCode, String)>;
+
+pub async fn restart_local_api(handle: &AppHandle, preferences: &Preferences) {
+    let container = handle.state::<LocalApiHandle>();
+    let mut lock = container.0.lock().await;
+
+    if let Some(h) = lock.take() {
+        h.abort();
+    }
+
+    if !preferences.enable_local_api {
+        return;
+    }
+
+    if preferences.local_api_token.is_empty() {
+        eprintln!("Not starting the local API without a token");
+        return;
+    }
+
+    let port = preferences.local_api_port;
+    let state = Arc::new(ServerState {
+        handle: handle.clone(),
+        token: preferences.local_api_token.clone(),
+        port,
+    });
+
+    *lock = Some(async_runtime::spawn(async move {
+        if let Err(e) = serve(state, port).await {
+            eprintln!("Local API server on port {} stopped: {}", port, e);
+        }
+    }));
+}
+
+async fn serve(state: Arc<ServerState>, port: u16) -> Result<()> {
+    let app = Router::new()
+        .route("/api/playerdata", get(playerdata))
+        .route("/api/timer", get(timer))
+        .route("/api/media", get(media))
+        .route("/api/stats", get(stats))
+        .route("/api/personal-bests", get(personal_bests))
+        .route("/api/sessions", get(sessions))
+        .route("/api/attempts", get(attempts))
+        .route("/api/health", get(health))
+        .route("/api/preferences", get(preferences))
+        .route("/api/events", get(events))
+        .route("/metrics", get(prometheus_metrics))
+        .route_layer(middleware::from_fn_with_state(state.clone(), authorize))
+        .route("/overlay", get(overlay))
+        .layer(middleware::from_fn_with_state(state.clone(), check_host))
+        .with_state(state);
+
+    let addr = SocketAddr::from(([127, 0, 0, 1], port));
+
+    axum::Server::try_bind(&addr)?
+        .serve(app.into_make_service())
+        .await?;
+
+    Ok(())
+}
+
+/// Only answers requests addressed to the loopback address or `localhost`, so a
+/// page on another domain can't reach the API through DNS rebinding.
+async fn check_host<B>(
+    State(state): State<Arc<ServerState>>,
+    req: Request<B>,
+    next: Next<B>,
+) -> Result<Response, StatusCode> {
+    let host = req.headers().get(HOST).and_then(|h| h.to_str().ok());
+
+    match host {
+        Some(h) if allowed_host(h, state.port) => Ok(next.run(req).await),
+        _ => Err(StatusCode::FORBIDDEN),
+    }
+}
+
+fn allowed_host(host: &str, port: u16) -> bool {
+    host == format!("127.0.0.1:{port}") || host == format!("localhost:{port}")
+}
+
+/// Browsers always send an origin with a WebSocket handshake, and only the app's
+/// own windows and the browser overlay served from here may open one. Clients
+/// that aren't browsers send none and still need the token.
+fn allowed_origin(origin: Option<&str>, port: u16) -> bool {
+    match origin {
+        None => true,
+        Some(o) => {
+            o == "tauri://localhost"
+                || o == "https://tauri.localhost"
+                || o == format!("http://127.0.0.1:{port}")
+                || o == format!("http://localhost:{port}")
+        }
+    }
+}
+
+/// Accepts the token either as a bearer token or as a `token` query parameter,
+/// since browser WebSocket clients can't set headers.
+async fn authorize<B>(
+    State(state): State<Arc<ServerState>>,
+    Query(query): Query<HashMap<String, String>>,
+    req: Request<B>,
+    next: Next<B>,
+) -> Result<Response, StatusCode> {
+    let header_token = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "));
+    let query_token = query.get("token").map(String::as_str);
+
+    let authorized =
+        tokens_match(&state.token, header_token) || tokens_match(&state.token, query_token);
+
+    if !authorized {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    Ok(next.run(req).await)
+}
+
+/// Compares every byte regardless of where the first difference is, so the
+/// response time doesn't give the token away. An empty token never matches.
+fn tokens_match(expected: &str, given: Option<&str>) -> bool {
+    let given = match given {
+        Some(g) => g.as_bytes(),
+        None => return false,
+    };
+    let expected = expected.as_bytes();
+
+    if expected.is_empty() || given.len() != expected.len() {
+        return false;
+    }
+
+    expected
+        .iter()
+        .zip(given)
+        .fold(0, |diff, (a, b)| diff | (a ^ b))
+        == 0
+}
+
+async fn current_playerdata(handle: &AppHandle) -> Option<PlayerDataStatus> {
+    let container = handle.state::<PlayerDataPollerContainer>();
+    let mut lock = container.0.lock().await;
+    lock.get_data()
+}
+
+/// Waits for the poller instead of giving up while it holds the data, so a
+/// request during an update doesn't look like there is no history.
+async fn current_history(
+    handle: &AppHandle,
+) -> Result<Vec<CompletedActivity>, (StatusCode, String)> {
+    let container = handle.state::<PlayerDataPollerContainer>();
+    let history = container.0.lock().await.get_history().await;
+
+    history.ok_or((
+        StatusCode::SERVICE_UNAVAILABLE,
+        "No player data yet".to_string(),
+    ))
+}
+
+async fn playerdata(
+    State(state): State<Arc<ServerState>>,
+) -> HandlerResult<Option<PlayerDataStatus>> {
+    Ok(Json(current_playerdata(&state.handle).await))
+}
+
+async fn timer(State(state): State<Arc<ServerState>>) -> HandlerResult<TimerState> {
+    let container = state.handle.state::<TimerPollerContainer>();
+    let timer_state = container.0.lock().await.get_state().await;
+
+    Ok(Json(timer_state))
+}
+
+async fn media() -> HandlerResult<MediaInfo> {
+    get_current_media()
+        .await
+        .map(Json)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
+}
+
+async fn stats(
+    State(state): State<Arc<ServerState>>,
+    Query(filter): Query<HistoryFilter>,
+) -> HandlerResult<HistoryStats> {
+    let history = current_history(&state.handle).await?;
+    let filter = filter
+        .resolve(state.now())
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+
+    Ok(Json(compute_stats(&history, &filter)))
+}
+
+async fn personal_bests(State(state): State<Arc<ServerState>>) -> HandlerResult<Vec<PersonalBest>> {
+    let profile = {
+        let container = state.handle.state::<ConfigContainer>();
+        let lock = container.0.lock().await;
+        lock.get_profiles().selected_profile.clone()
+    }
+    .ok_or((
+        StatusCode::SERVICE_UNAVAILABLE,
+        "No profile set".to_string(),
+    ))?;
+
+    let container = state.handle.state::<PersonalBestsContainer>();
+    let history = container.0.lock().await.records.history(&profile);
+
+    Ok(Json(history))
+}
+
+async fn sessions(State(state): State<Arc<ServerState>>) -> HandlerResult<Vec<PlaySession>> {
+    let gap = {
+        let container = state.handle.state::<ConfigContainer>();
+        let lock = container.0.lock().await;
+        lock.get_preferences().session_gap_minutes
+    };
+
+    let history = current_history(&state.handle).await?;
+
+    let container = state.handle.state::<SessionsContainer>();
+    let tracker = container.0.lock().await;
+
+    Ok(Json(detect_sessions(
+        &history,
+        &tracker,
+        Duration::minutes(gap as i64),
+        state.now(),
+    )))
+}
+
+async fn attempts(
+    State(state): State<Arc<ServerState>>,
+    Query(filter): Query<HistoryFilter>,
+) -> HandlerResult<AttemptReport> {
+    let profile = {
+        let container = state.handle.state::<ConfigContainer>();
+        let lock = container.0.lock().await;
+        lock.get_profiles().selected_profile.clone()
+    }
+    .ok_or((
+        StatusCode::SERVICE_UNAVAILABLE,
+        "No profile set".to_string(),
+    ))?;
+
+    let history = current_history(&state.handle).await?;
+    let filter = filter
+        .resolve(state.now())
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+
+    let container = state.handle.state::<AttemptsContainer>();
+    let tracker = container.0.lock().await;
+
+    Ok(Json(attempt_report(
+        &history,
+        tracker.observed(&profile),
+        &filter,
+        state.now(),
+    )))
+}
+
+async fn health(State(state): State<Arc<ServerState>>) -> HandlerResult<ApiHealth> {
+    let container = state.handle.state::<ApiHealthContainer>();
+    let health = container.0.lock().await.clone();
+
+    Ok(Json(health))
+}
+
+async fn preferences(State(state): State<Arc<ServerState>>) -> HandlerResult<Preferences> {
+    let container = state.handle.state::<ConfigContainer>();
+    let preferences = container.0.lock().await.get_preferences().redacted();
+
+    Ok(Json(preferences))
+}
+
+/// Only uses names that are already cached, a scrape shouldn't wait on the
+/// Bungie API.
+async fn activity_name(handle: &AppHandle, activity_hash: usize) -> String {
+    let api = handle.state::<Api>();
+    let source = api.activity_info_source.lock().await;
+
+    match source.cached(activity_hash) {
+        Some(a) if !a.name.is_empty() => a.name.clone(),
+        _ => activity_hash.to_string(),
+    }
+}
+
+async fn prometheus_metrics(State(state): State<Arc<ServerState>>) -> impl IntoResponse {
+    let mut out = String::new();
+    metrics::render(&mut out);
+
+    let playerdata = {
+        let container = state.handle.state::<PlayerDataPollerContainer>();
+        let lock = container.0.lock().await;
+        lock.get_last_update().await
+    };
+
+    let (timer_state, elapsed) = {
+        let container = state.handle.state::<TimerPollerContainer>();
+        let lock = container.0.lock().await;
+        (lock.get_state().await, lock.get_elapsed_seconds().await)
+    };
+
+    metrics::write_gauge(
+        &mut out,
+        "groundsub_timer_active",
+        "Whether the timer is running.",
+        &[(vec![], timer_state.is_active as u8 as f64)],
+    );
+    metrics::write_gauge(
+        &mut out,
+        "groundsub_timer_elapsed_seconds",
+        "Seconds on the timer, 0 when it is off.",
+        &[(vec![], elapsed.unwrap_or_default() as f64)],
+    );
+
+    // Until the first update there is nothing to report, and reporting zeros
+    // would look like the history emptied.
+    let playerdata = match playerdata {
+        Some(p) => p,
+        None => return ([(CONTENT_TYPE, "text/plain; version=0.0.4")], out),
+    };
+
+    metrics::write_gauge(
+        &mut out,
+        "groundsub_in_activity",
+        "Whether the player is currently in an activity.",
+        &[(
+            vec![],
+            (playerdata.current_activity.activity_hash != 0) as u8 as f64,
+        )],
+    );
+
+    let history = playerdata.activity_history;
+
+    metrics::write_gauge(
+        &mut out,
+        "groundsub_history_activities",
+        "Activities in the loaded history.",
+        &[(vec![], history.len() as f64)],
+    );
+
+    let stats = compute_stats(&history, &HistoryFilter::default());
+
+    let mut attempts = Vec::new();
+    let mut clears = Vec::new();
+    let mut seconds = Vec::new();
+
+    for activity in stats.activities {
+        let labels: Labels = vec![
+            ("activity_hash", activity.activity_hash.to_string()),
+            (
+                "activity",
+                activity_name(&state.handle, activity.activity_hash).await,
+            ),
+        ];
+
+        attempts.push((labels.clone(), activity.stats.attempts as f64));
+        clears.push((labels.clone(), activity.stats.clears as f64));
+        seconds.push((labels, activity.stats.played_seconds as f64));
+    }
+
+    metrics::write_gauge(
+        &mut out,
+        "groundsub_activity_attempts",
+        "Attempts per activity in the loaded history.",
+        &attempts,
+    );
+    metrics::write_gauge(
+        &mut out,
+        "groundsub_activity_clears",
+        "Clears per activity in the loaded history.",
+        &clears,
+    );
+    metrics::write_gauge(
+        &mut out,
+        "groundsub_activity_played_seconds",
+        "Seconds played per activity in the loaded history.",
+        &seconds,
+    );
+
+    ([(CONTENT_TYPE, "text/plain; version=0.0.4")], out)
+}
+
+/// The page itself is static, it reads the token from its own URL and passes it
+/// along to the API calls it makes.
+async fn overlay() -> Html<&'static str> {
+    Html(BROWSER_OVERLAY)
+}
+
+async fn events(
+    ws: WebSocketUpgrade,
+    headers: HeaderMap,
+    State(state): State<Arc<ServerState>>,
+) -> Response {
+    let origin = headers.get(ORIGIN).and_then(|h| h.to_str().ok());
+
+    if !allowed_origin(origin, state.port) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    let receiver = state.handle.state::<AppEvents>().subscribe();
+
+    ws.on_upgrade(move |socket| stream_events(socket, receiver))
+}
+
+async fn stream_events(mut socket: WebSocket, mut receiver: broadcast::Receiver<AppEvent>) {
+    loop {
+        tokio::select! {
+            event = receiver.recv() => {
+                let event = match event {
+                    Ok(e) => e,
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => return,
+                };
+
+                let text = match serde_json::to_string(&event) {
+                    Ok(t) => t,
+                    Err(_) => continue,
+                };
+
+                if socket.send(Message::Text(text)).await.is_err() {
+                    return;
+                }
+            }
+            message = socket.recv() => {
+                match message {
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                    _ => (),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_loopback_hosts_are_allowed() {
+        assert!(allowed_host("127.0.0.1:7120", 7120));
+        assert!(allowed_host("localhost:7120", 7120));
+
+        assert!(!allowed_host("localhost:7121", 7120));
+        assert!(!allowed_host("localhost", 7120));
+        assert!(!allowed_host("attacker.example:7120", 7120));
+        assert!(!allowed_host("127.0.0.1.attacker.example:7120", 7120));
+    }
+
+    #[test]
+    fn only_the_app_and_overlay_origins_are_allowed() {
+        assert!(allowed_origin(None, 7120));
+        assert!(allowed_origin(Some("tauri://localhost"), 7120));
+        assert!(allowed_origin(Some("https://tauri.localhost"), 7120));
+        assert!(allowed_origin(Some("http://127.0.0.1:7120"), 7120));
+        assert!(allowed_origin(Some("http://localhost:7120"), 7120));
+
+        assert!(!allowed_origin(Some("http://localhost:7121"), 7120));
+        assert!(!allowed_origin(Some("https://attacker.example"), 7120));
+        assert!(!allowed_origin(Some("null"), 7120));
+    }
+
+    #[test]
+    fn tokens_must_match_exactly() {
+        assert!(tokens_match("secret", Some("secret")));
+
+        assert!(!tokens_match("secret", Some("secreT")));
+        assert!(!tokens_match("secret", Some("secret2")));
+        assert!(!tokens_match("secret", Some("")));
+        assert!(!tokens_match("secret", None));
+        assert!(!tokens_match("", Some("")));
+        assert!(!tokens_match("", None));
+    }
+}
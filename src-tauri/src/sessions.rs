@@ -0,0 +1,400 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+use crate::{api::responses::CompletedActivity, filter::HistoryFilter, stats::consolidate};
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionActivity {
+    pub activity_hash: usize,
+    pub attempts: usize,
+    pub clears: usize,
+    pub activity_seconds: usize,
+}
+
+/// Activities played close enough together to count as one sitting. Time in
+/// the session that isn't spent in a tracked activity counts as orbit, which
+/// includes loading, patrols and anything else the history doesn't track.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaySession {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub ongoing: bool,
+    pub total_seconds: usize,
+    pub activity_seconds: usize,
+    pub orbit_seconds: usize,
+    pub attempts: usize,
+    pub clears: usize,
+    pub activities: Vec<SessionActivity>,
+}
+
+#[derive(Clone, Copy)]
+struct Span {
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+}
+
+/// Keeps track of when the game was running, from the current activity of the
+/// profile. This covers the time in orbit before the first and after the last
+/// activity, and activities that haven't made it into the history yet.
+#[derive(Default)]
+pub struct SessionTracker {
+    profile: Option<String>,
+    online: Vec<Span>,
+    online_since: Option<DateTime<Utc>>,
+    summary_pending: bool,
+}
+
+impl SessionTracker {
+    /// Feeds the current activity state of the profile.
+    pub fn observe(&mut self, profile: &str, online: bool, now: DateTime<Utc>) {
+        if self.profile.as_deref() != Some(profile) {
+            *self = Self {
+                profile: Some(profile.to_string()),
+                ..Self::default()
+            };
+        }
+
+        match (self.online_since, online) {
+            (None, true) => self.online_since = Some(now),
+            (Some(start), false) => {
+                self.online.push(Span { start, end: now });
+                self.online_since = None;
+                self.summary_pending = true;
+            }
+            _ => (),
+        }
+    }
+
+    /// Whether the game closed since the last time this was asked.
+    pub fn take_summary(&mut self) -> bool {
+        std::mem::take(&mut self.summary_pending)
+    }
+
+    pub fn is_online(&self) -> bool {
+        self.online_since.is_some()
+    }
+
+    fn spans(&self, now: DateTime<Utc>) -> Vec<Span> {
+        let mut spans = self.online.clone();
+
+        if let Some(start) = self.online_since {
+            spans.push(Span { start, end: now });
+        }
+
+        spans
+    }
+}
+
+/// Total length of the spans, counting overlapping time once.
+fn covered_seconds(mut spans: Vec<Span>) -> usize {
+    spans.sort_by_key(|s| s.start);
+
+    let mut total = Duration::zero();
+    let mut current: Option<Span> = None;
+
+    for span in spans {
+        match current {
+            Some(ref mut c) if span.start <= c.end => c.end = c.end.max(span.end),
+            _ => {
+                if let Some(c) = current.replace(span) {
+                    total = total + (c.end - c.start);
+                }
+            }
+        }
+    }
+
+    if let Some(c) = current {
+        total = total + (c.end - c.start);
+    }
+
+    total.num_seconds().max(0) as usize
+}
+
+fn summarize(spans: &[(Span, Option<&CompletedActivity>)], ongoing: bool) -> PlaySession {
+    let start = spans.iter().map(|(s, _)| s.start).min().unwrap();
+    let end = spans.iter().map(|(s, _)| s.end).max().unwrap();
+    let total_seconds = (end - start).num_seconds().max(0) as usize;
+
+    let attempts: Vec<(Span, &CompletedActivity)> = spans
+        .iter()
+        .filter_map(|(span, activity)| activity.map(|a| (*span, a)))
+        .collect();
+
+    let activity_seconds =
+        covered_seconds(attempts.iter().map(|(span, _)| *span).collect()).min(total_seconds);
+
+    let mut per_activity: BTreeMap<usize, SessionActivity> = BTreeMap::new();
+
+    for (_, activity) in attempts.iter() {
+        let entry = per_activity
+            .entry(activity.activity_hash)
+            .or_insert(SessionActivity {
+                activity_hash: activity.activity_hash,
+                attempts: 0,
+                clears: 0,
+                activity_seconds: 0,
+            });
+
+        entry.attempts += 1;
+        entry.activity_seconds += activity.activity_duration_seconds;
+
+        if activity.completed {
+            entry.clears += 1;
+        }
+    }
+
+    let mut activities: Vec<SessionActivity> = per_activity.into_values().collect();
+    activities.sort_by(|a, b| b.activity_seconds.cmp(&a.activity_seconds));
+
+    PlaySession {
+        start,
+        end,
+        ongoing,
+        total_seconds,
+        activity_seconds,
+        orbit_seconds: total_seconds - activity_seconds,
+        attempts: attempts.len(),
+        clears: attempts.iter().filter(|(_, a)| a.completed).count(),
+        activities,
+    }
+}
+
+/// Groups the history and the time the game was seen running into sessions,
+/// starting a new one whenever nothing happened for longer than the gap.
+/// Returns the latest session first.
+pub fn detect_sessions(
+    history: &[CompletedActivity],
+    tracker: &SessionTracker,
+    gap: Duration,
+    now: DateTime<Utc>,
+) -> Vec<PlaySession> {
+    let filter = HistoryFilter::default();
+
+    let mut spans: Vec<(Span, Option<&CompletedActivity>)> =
+        consolidate(history.iter().filter(|a| filter.matches(a)))
+            .into_iter()
+            .map(|a| {
+                let span = Span {
+                    start: a.period,
+                    end: a
+                        .period
+                        .checked_add_signed(Duration::seconds(a.activity_duration_seconds as i64))
+                        .unwrap_or(DateTime::<Utc>::MAX_UTC),
+                };
+                (span, Some(a))
+            })
+            .chain(tracker.spans(now).into_iter().map(|span| (span, None)))
+            .collect();
+
+    spans.sort_by_key(|(span, _)| span.start);
+
+    let mut sessions = Vec::new();
+    let mut current: Vec<(Span, Option<&CompletedActivity>)> = Vec::new();
+    let mut current_end = DateTime::<Utc>::MIN_UTC;
+
+    for (span, activity) in spans {
+        if !current.is_empty() && span.start - current_end > gap {
+            sessions.push(summarize(&current, false));
+            current.clear();
+        }
+
+        current_end = if current.is_empty() {
+            span.end
+        } else {
+            current_end.max(span.end)
+        };
+        current.push((span, activity));
+    }
+
+    if !current.is_empty() {
+        sessions.push(summarize(&current, tracker.is_online()));
+    }
+
+    sessions.reverse();
+    sessions
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::{api::responses::format_duration, consts::RAID_ACTIVITY_MODE};
+
+    const RAID: usize = 1374392663;
+    const DUNGEON: usize = 2032534090;
+    const PROFILE: &str = "3/4611686018467654321";
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 14, hour, minute, 0).unwrap()
+    }
+
+    fn activity(
+        instance_id: &str,
+        activity_hash: usize,
+        period: DateTime<Utc>,
+        minutes: usize,
+        completed: bool,
+    ) -> CompletedActivity {
+        CompletedActivity {
+            period,
+            instance_id: instance_id.to_string(),
+            activity_hash,
+            modes: vec![RAID_ACTIVITY_MODE],
+            completed,
+            failed: !completed,
+            activity_duration: format_duration(minutes * 60),
+            activity_duration_seconds: minutes * 60,
+            start_seconds: 0,
+            character_class: None,
+        }
+    }
+
+    fn online(start: DateTime<Utc>, end: DateTime<Utc>) -> SessionTracker {
+        let mut tracker = SessionTracker::default();
+        tracker.observe(PROFILE, true, start);
+        tracker.observe(PROFILE, false, end);
+        tracker
+    }
+
+    #[test]
+    fn splits_sessions_on_gaps() {
+        let history = vec![
+            activity("1", RAID, at(18, 0), 30, true),
+            activity("2", RAID, at(18, 40), 30, false),
+            activity("3", DUNGEON, at(22, 0), 20, true),
+        ];
+
+        let sessions = detect_sessions(
+            &history,
+            &SessionTracker::default(),
+            Duration::hours(1),
+            at(23, 0),
+        );
+
+        assert_eq!(sessions.len(), 2);
+
+        assert_eq!(sessions[0].start, at(22, 0));
+        assert_eq!(sessions[0].end, at(22, 20));
+        assert_eq!(sessions[0].attempts, 1);
+
+        assert_eq!(sessions[1].start, at(18, 0));
+        assert_eq!(sessions[1].end, at(19, 10));
+        assert_eq!(sessions[1].total_seconds, 70 * 60);
+        assert_eq!(sessions[1].activity_seconds, 60 * 60);
+        assert_eq!(sessions[1].orbit_seconds, 10 * 60);
+        assert_eq!(sessions[1].attempts, 2);
+        assert_eq!(sessions[1].clears, 1);
+    }
+
+    #[test]
+    fn keeps_sessions_together_on_the_gap() {
+        let history = vec![
+            activity("1", RAID, at(18, 0), 30, true),
+            activity("2", RAID, at(19, 30), 30, true),
+        ];
+
+        let sessions = detect_sessions(
+            &history,
+            &SessionTracker::default(),
+            Duration::hours(1),
+            at(23, 0),
+        );
+
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].total_seconds, 120 * 60);
+        assert_eq!(sessions[0].orbit_seconds, 60 * 60);
+    }
+
+    #[test]
+    fn counts_time_online_outside_activities_as_orbit() {
+        let history = vec![
+            activity("1", RAID, at(18, 0), 30, true),
+            activity("2", RAID, at(18, 15), 30, false),
+            activity("3", DUNGEON, at(19, 0), 20, true),
+        ];
+        let tracker = online(at(17, 30), at(20, 0));
+
+        let sessions = detect_sessions(&history, &tracker, Duration::hours(1), at(23, 0));
+
+        assert_eq!(sessions.len(), 1);
+
+        let session = &sessions[0];
+        assert_eq!(session.start, at(17, 30));
+        assert_eq!(session.end, at(20, 0));
+        assert!(!session.ongoing);
+        assert_eq!(session.total_seconds, 150 * 60);
+        // The overlapping raid attempts count once towards the activity time.
+        assert_eq!(session.activity_seconds, 65 * 60);
+        assert_eq!(session.orbit_seconds, 85 * 60);
+        assert_eq!(session.attempts, 3);
+        assert_eq!(session.clears, 2);
+
+        assert_eq!(session.activities.len(), 2);
+        assert_eq!(session.activities[0].activity_hash, RAID);
+        assert_eq!(session.activities[0].attempts, 2);
+        assert_eq!(session.activities[0].clears, 1);
+        assert_eq!(session.activities[0].activity_seconds, 60 * 60);
+        assert_eq!(session.activities[1].activity_hash, DUNGEON);
+        assert_eq!(session.activities[1].attempts, 1);
+        assert_eq!(session.activities[1].clears, 1);
+        assert_eq!(session.activities[1].activity_seconds, 20 * 60);
+    }
+
+    #[test]
+    fn counts_an_instance_once_across_characters() {
+        let history = vec![
+            activity("1", RAID, at(18, 0), 30, false),
+            activity("1", RAID, at(18, 0), 30, true),
+        ];
+
+        let sessions = detect_sessions(
+            &history,
+            &SessionTracker::default(),
+            Duration::hours(1),
+            at(23, 0),
+        );
+
+        assert_eq!(sessions[0].attempts, 1);
+        assert_eq!(sessions[0].clears, 1);
+        assert_eq!(sessions[0].activity_seconds, 30 * 60);
+    }
+
+    #[test]
+    fn marks_the_latest_session_ongoing_while_online() {
+        let history = vec![activity("1", RAID, at(18, 10), 30, true)];
+        let mut tracker = SessionTracker::default();
+        tracker.observe(PROFILE, true, at(18, 0));
+
+        let sessions = detect_sessions(&history, &tracker, Duration::hours(1), at(19, 0));
+
+        assert_eq!(sessions.len(), 1);
+        assert!(sessions[0].ongoing);
+        assert_eq!(sessions[0].end, at(19, 0));
+        assert_eq!(sessions[0].total_seconds, 60 * 60);
+        assert_eq!(sessions[0].orbit_seconds, 30 * 60);
+        assert!(!tracker.take_summary());
+    }
+
+    #[test]
+    fn asks_for_a_summary_once_the_game_closes() {
+        let mut tracker = online(at(18, 0), at(19, 0));
+
+        assert!(!tracker.is_online());
+        assert!(tracker.take_summary());
+        assert!(!tracker.take_summary());
+    }
+
+    #[test]
+    fn forgets_online_time_when_the_profile_changes() {
+        let mut tracker = online(at(18, 0), at(19, 0));
+        tracker.observe("3/4611686018400000000", false, at(19, 30));
+
+        assert!(!tracker.take_summary());
+        assert!(detect_sessions(&[], &tracker, Duration::hours(1), at(20, 0)).is_empty());
+    }
+}
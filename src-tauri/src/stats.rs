@@ -0,0 +1,356 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+use crate::{
+    api::responses::CompletedActivity, filter::HistoryFilter, timerange::last_weekly_reset,
+};
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TrendPoint {
+    pub week_start: DateTime<Utc>,
+    pub attempts: usize,
+    pub clears: usize,
+    pub average_clear_seconds: usize,
+}
+
+#[derive(Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ClearStats {
+    pub attempts: usize,
+    pub clears: usize,
+    pub completion_rate: f64,
+    pub average_clear_seconds: usize,
+    pub fastest_clear_seconds: Option<usize>,
+    pub median_clear_seconds: Option<usize>,
+    pub p90_clear_seconds: Option<usize>,
+    pub played_seconds: usize,
+    pub trend: Vec<TrendPoint>,
+}
+
+#[derive(Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityStats {
+    pub activity_hash: usize,
+    #[serde(flatten)]
+    pub stats: ClearStats,
+}
+
+#[derive(Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryStats {
+    #[serde(flatten)]
+    pub overall: ClearStats,
+    pub activities: Vec<ActivityStats>,
+}
+
+fn average(durations: &[usize]) -> usize {
+    if durations.is_empty() {
+        return 0;
+    }
+
+    durations.iter().sum::<usize>() / durations.len()
+}
+
+/// Expects the durations sorted.
+fn median(durations: &[usize]) -> Option<usize> {
+    let mid = durations.len() / 2;
+
+    match durations.len() {
+        0 => None,
+        n if n % 2 == 0 => Some((durations[mid - 1] + durations[mid]) / 2),
+        _ => Some(durations[mid]),
+    }
+}
+
+/// Nearest rank percentile, expects the durations sorted.
+fn percentile(durations: &[usize], p: f64) -> Option<usize> {
+    if durations.is_empty() {
+        return None;
+    }
+
+    let rank = (p * durations.len() as f64).ceil() as usize;
+    durations.get(rank.clamp(1, durations.len()) - 1).copied()
+}
+
+/// The same instance shows up once for every character that was in it. Those
+/// count as one attempt, which is a clear if any of the characters cleared it.
+pub fn consolidate<'a>(
+    activities: impl Iterator<Item = &'a CompletedActivity>,
+) -> Vec<&'a CompletedActivity> {
+    let mut by_instance: HashMap<&str, &CompletedActivity> = HashMap::new();
+
+    for activity in activities {
+        by_instance
+            .entry(activity.instance_id.as_str())
+            .and_modify(|existing| {
+                if (activity.completed, activity.period) > (existing.completed, existing.period) {
+                    *existing = activity;
+                }
+            })
+            .or_insert(activity);
+    }
+
+    let mut consolidated: Vec<&CompletedActivity> = by_instance.into_values().collect();
+    consolidated.sort();
+    consolidated.reverse();
+    consolidated
+}
+
+/// Weekly attempts and clears, from the first week with an attempt to the
+/// last, including the empty weeks in between.
+fn trend(attempts: &[&CompletedActivity]) -> Vec<TrendPoint> {
+    let mut weeks: BTreeMap<DateTime<Utc>, Vec<&CompletedActivity>> = BTreeMap::new();
+
+    for &activity in attempts {
+        weeks
+            .entry(last_weekly_reset(activity.period))
+            .or_default()
+            .push(activity);
+    }
+
+    let (first, last) = match (weeks.keys().next(), weeks.keys().next_back()) {
+        (Some(first), Some(last)) => (*first, *last),
+        _ => return Vec::new(),
+    };
+
+    let mut points = Vec::new();
+    let mut week_start = first;
+
+    while week_start <= last {
+        let week = weeks
+            .get(&week_start)
+            .map(|w| w.as_slice())
+            .unwrap_or_default();
+        let clear_times: Vec<usize> = week
+            .iter()
+            .filter(|a| a.completed)
+            .map(|a| a.activity_duration_seconds)
+            .collect();
+
+        points.push(TrendPoint {
+            week_start,
+            attempts: week.len(),
+            clears: clear_times.len(),
+            average_clear_seconds: average(&clear_times),
+        });
+
+        week_start = week_start + Duration::weeks(1);
+    }
+
+    points
+}
+
+fn clear_stats(attempts: &[&CompletedActivity]) -> ClearStats {
+    let mut clear_times: Vec<usize> = attempts
+        .iter()
+        .filter(|a| a.completed)
+        .map(|a| a.activity_duration_seconds)
+        .collect();
+    clear_times.sort_unstable();
+
+    let completion_rate = if attempts.is_empty() {
+        0.0
+    } else {
+        clear_times.len() as f64 / attempts.len() as f64
+    };
+
+    ClearStats {
+        attempts: attempts.len(),
+        clears: clear_times.len(),
+        completion_rate,
+        average_clear_seconds: average(&clear_times),
+        fastest_clear_seconds: clear_times.first().copied(),
+        median_clear_seconds: median(&clear_times),
+        p90_clear_seconds: percentile(&clear_times, 0.9),
+        played_seconds: attempts.iter().map(|a| a.activity_duration_seconds).sum(),
+        trend: trend(attempts),
+    }
+}
+
+/// The numbers behind every clear count and average in groundsub, so the
+/// windows, the overlay, the CLI and the integrations all agree.
+pub fn compute_stats(history: &[CompletedActivity], filter: &HistoryFilter) -> HistoryStats {
+    let attempts = consolidate(history.iter().filter(|a| filter.matches(a)));
+
+    let mut per_activity: BTreeMap<usize, Vec<&CompletedActivity>> = BTreeMap::new();
+
+    for &activity in attempts.iter() {
+        per_activity
+            .entry(activity.activity_hash)
+            .or_default()
+            .push(activity);
+    }
+
+    let mut activities: Vec<ActivityStats> = per_activity
+        .into_iter()
+        .map(|(activity_hash, attempts)| ActivityStats {
+            activity_hash,
+            stats: clear_stats(&attempts),
+        })
+        .collect();
+
+    activities.sort_by(|a, b| b.stats.clears.cmp(&a.stats.clears));
+
+    HistoryStats {
+        overall: clear_stats(&attempts),
+        activities,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::{api::responses::format_duration, consts::RAID_ACTIVITY_MODE};
+
+    const RAID: usize = 1374392663;
+    const DUNGEON: usize = 2032534090;
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, day, hour, 0, 0).unwrap()
+    }
+
+    fn activity(
+        instance_id: &str,
+        activity_hash: usize,
+        period: DateTime<Utc>,
+        seconds: usize,
+        completed: bool,
+    ) -> CompletedActivity {
+        CompletedActivity {
+            period,
+            instance_id: instance_id.to_string(),
+            activity_hash,
+            modes: vec![RAID_ACTIVITY_MODE],
+            completed,
+            failed: !completed,
+            activity_duration: format_duration(seconds),
+            activity_duration_seconds: seconds,
+            start_seconds: 0,
+            character_class: Some("Hunter".to_string()),
+        }
+    }
+
+    #[test]
+    fn takes_the_median_of_odd_and_even_counts() {
+        assert_eq!(median(&[]), None);
+        assert_eq!(median(&[100, 200, 300]), Some(200));
+        assert_eq!(median(&[100, 200, 300, 400]), Some(250));
+    }
+
+    #[test]
+    fn takes_the_nearest_rank_percentile() {
+        let ten: Vec<usize> = (1..=10).collect();
+
+        assert_eq!(percentile(&[], 0.9), None);
+        assert_eq!(percentile(&[100], 0.9), Some(100));
+        assert_eq!(percentile(&[100, 200, 300], 0.9), Some(300));
+        assert_eq!(percentile(&[100, 200, 300, 400], 0.9), Some(400));
+        assert_eq!(percentile(&ten, 0.9), Some(9));
+        assert_eq!(percentile(&ten, 0.0), Some(1));
+    }
+
+    #[test]
+    fn computes_clear_stats() {
+        let history = vec![
+            activity("1", RAID, at(12, 18), 1800, true),
+            activity("2", RAID, at(12, 19), 2400, false),
+            activity("3", RAID, at(12, 20), 3000, true),
+            activity("4", RAID, at(12, 21), 1200, true),
+        ];
+
+        let stats = compute_stats(&history, &HistoryFilter::default()).overall;
+
+        assert_eq!(stats.attempts, 4);
+        assert_eq!(stats.clears, 3);
+        assert_eq!(stats.completion_rate, 0.75);
+        assert_eq!(stats.average_clear_seconds, 2000);
+        assert_eq!(stats.fastest_clear_seconds, Some(1200));
+        assert_eq!(stats.median_clear_seconds, Some(1800));
+        assert_eq!(stats.p90_clear_seconds, Some(3000));
+        assert_eq!(stats.played_seconds, 8400);
+    }
+
+    #[test]
+    fn reports_no_clear_times_without_clears() {
+        let history = vec![activity("1", RAID, at(12, 18), 1800, false)];
+
+        let stats = compute_stats(&history, &HistoryFilter::default()).overall;
+
+        assert_eq!(stats.attempts, 1);
+        assert_eq!(stats.completion_rate, 0.0);
+        assert_eq!(stats.average_clear_seconds, 0);
+        assert_eq!(stats.fastest_clear_seconds, None);
+        assert_eq!(stats.median_clear_seconds, None);
+        assert_eq!(stats.p90_clear_seconds, None);
+    }
+
+    #[test]
+    fn counts_an_instance_once_across_characters() {
+        let mut warlock = activity("1", RAID, at(12, 18), 1800, true);
+        warlock.character_class = Some("Warlock".to_string());
+
+        let history = vec![
+            activity("1", RAID, at(12, 18), 1500, false),
+            warlock,
+            activity("2", RAID, at(12, 20), 2000, false),
+        ];
+
+        let consolidated = consolidate(history.iter());
+        assert_eq!(consolidated.len(), 2);
+        assert_eq!(consolidated[1].character_class.as_deref(), Some("Warlock"));
+
+        let stats = compute_stats(&history, &HistoryFilter::default()).overall;
+        assert_eq!(stats.attempts, 2);
+        assert_eq!(stats.clears, 1);
+        assert_eq!(stats.fastest_clear_seconds, Some(1800));
+    }
+
+    #[test]
+    fn buckets_the_trend_by_weekly_reset() {
+        let history = vec![
+            activity("1", RAID, at(3, 17), 1800, true),
+            activity("2", RAID, at(10, 16), 2400, true),
+            activity("3", RAID, at(17, 18), 3000, false),
+            activity("4", RAID, at(24, 18), 1200, true),
+        ];
+
+        let trend = compute_stats(&history, &HistoryFilter::default())
+            .overall
+            .trend;
+
+        assert_eq!(trend.len(), 4);
+        assert_eq!(trend[0].week_start, at(3, 17));
+        assert_eq!(trend[0].attempts, 2);
+        assert_eq!(trend[0].clears, 2);
+        assert_eq!(trend[0].average_clear_seconds, 2100);
+        assert_eq!(trend[1].week_start, at(10, 17));
+        assert_eq!(trend[1].attempts, 0);
+        assert_eq!(trend[2].week_start, at(17, 17));
+        assert_eq!(trend[2].attempts, 1);
+        assert_eq!(trend[2].clears, 0);
+        assert_eq!(trend[3].week_start, at(24, 17));
+        assert_eq!(trend[3].clears, 1);
+    }
+
+    #[test]
+    fn splits_stats_per_activity_by_clears() {
+        let history = vec![
+            activity("1", RAID, at(12, 18), 1800, true),
+            activity("2", DUNGEON, at(12, 19), 1200, true),
+            activity("3", DUNGEON, at(12, 20), 1000, true),
+        ];
+
+        let stats = compute_stats(&history, &HistoryFilter::default());
+
+        assert_eq!(stats.activities.len(), 2);
+        assert_eq!(stats.activities[0].activity_hash, DUNGEON);
+        assert_eq!(stats.activities[0].stats.clears, 2);
+        assert_eq!(stats.activities[1].activity_hash, RAID);
+        assert_eq!(stats.activities[1].stats.clears, 1);
+    }
+}
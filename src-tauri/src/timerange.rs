@@ -0,0 +1,357 @@
+use anyhow::{anyhow, Result};
+use chrono::{
+    DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday,
+};
+
+use crate::consts::DAILY_RESET_HOUR_UTC;
+
+pub fn last_daily_reset(now: DateTime<Utc>) -> DateTime<Utc> {
+    let today = daily_reset_on(now.naive_utc().date());
+
+    if today > now {
+        today - Duration::days(1)
+    } else {
+        today
+    }
+}
+
+pub fn last_weekly_reset(now: DateTime<Utc>) -> DateTime<Utc> {
+    let mut reset = last_daily_reset(now);
+
+    while reset.weekday() != Weekday::Tue {
+        reset = reset - Duration::days(1);
+    }
+
+    reset
+}
+
+fn daily_reset_on(date: NaiveDate) -> DateTime<Utc> {
+    Utc.from_utc_datetime(&date.and_hms_opt(DAILY_RESET_HOUR_UTC, 0, 0).unwrap())
+}
+
+/// Midnight of the date in the time zone. On days where the clocks go forward
+/// over midnight there is no midnight, so the day starts once the gap ends,
+/// and when they go back over it the first of the two midnights is used.
+fn start_of_day<Tz: TimeZone>(date: NaiveDate, tz: &Tz) -> DateTime<Utc> {
+    let midnight = date.and_hms_opt(0, 0, 0).unwrap();
+
+    (0..=24 * 4)
+        .map(|quarter| midnight + Duration::minutes(quarter * 15))
+        .find_map(|time| tz.from_local_datetime(&time).earliest())
+        .map(|start| start.with_timezone(&Utc))
+        .unwrap_or_else(|| Utc.from_utc_datetime(&midnight))
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate> {
+    let value = value.trim();
+
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .or_else(|_| DateTime::parse_from_rfc3339(value).map(|d| d.naive_local().date()))
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M").map(|d| d.date()))
+        .map_err(|_| anyhow!("Invalid date {}", value))
+}
+
+/// The ranges of the time range filter. In Bungie time they follow the daily
+/// reset at 17:00 UTC and the weekly reset on Tuesday, in real time they count
+/// back from now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeRange {
+    Today,
+    ThisWeek,
+    /// The current week and the three before it, "This Month" in the filters.
+    LastFourResets,
+    Since(NaiveDate),
+}
+
+impl TimeRange {
+    /// Takes the timespan names of the filters, `1`, `7`, `30` and `custom`.
+    /// A custom range without a start date covers all of the history, like in
+    /// the details window, so there is no range for it.
+    pub fn parse(timespan: &str, custom_start_date: &str) -> Result<Option<Self>> {
+        match timespan {
+            "1" | "today" => Ok(Some(Self::Today)),
+            "7" | "week" => Ok(Some(Self::ThisWeek)),
+            "30" | "month" => Ok(Some(Self::LastFourResets)),
+            "custom" if custom_start_date.trim().is_empty() => Ok(None),
+            "custom" => Ok(Some(Self::Since(parse_date(custom_start_date)?))),
+            "all" | "" => Ok(None),
+            _ => Err(anyhow!("Unknown timespan {}", timespan)),
+        }
+    }
+
+    /// When the range starts. Custom dates start with that day's reset in
+    /// Bungie time, or at midnight in the given time zone in real time.
+    pub fn start<Tz: TimeZone>(
+        &self,
+        now: DateTime<Utc>,
+        real_time: bool,
+        tz: &Tz,
+    ) -> DateTime<Utc> {
+        match (*self, real_time) {
+            (Self::Today, false) => last_daily_reset(now),
+            (Self::ThisWeek, false) => last_weekly_reset(now),
+            (Self::LastFourResets, false) => last_weekly_reset(now) - Duration::weeks(3),
+            (Self::Since(date), false) => last_daily_reset(daily_reset_on(date).min(now)),
+            (Self::Today, true) => now - Duration::days(1),
+            (Self::ThisWeek, true) => now - Duration::days(7),
+            (Self::LastFourResets, true) => now - Duration::days(30),
+            (Self::Since(date), true) => start_of_day(date, tz),
+        }
+    }
+}
+
+/// Resolves a timespan of the filters against the local time zone.
+pub fn range_start(
+    timespan: &str,
+    custom_start_date: &str,
+    real_time: bool,
+    now: DateTime<Utc>,
+) -> Result<Option<DateTime<Utc>>> {
+    Ok(TimeRange::parse(timespan, custom_start_date)?.map(|r| r.start(now, real_time, &Local)))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{FixedOffset, LocalResult};
+
+    use super::*;
+
+    /// A zone whose offset changes once, at `switch` UTC. Enough to move the
+    /// clocks forward or back over midnight without a time zone database.
+    #[derive(Clone, Debug)]
+    struct ShiftingZone {
+        switch: NaiveDateTime,
+        before: FixedOffset,
+        after: FixedOffset,
+    }
+
+    impl ShiftingZone {
+        /// Clocks change at `local`, read in the offset before the change.
+        fn new(local: NaiveDateTime, before_hours: i32, after_hours: i32) -> Self {
+            let before = FixedOffset::east_opt(before_hours * 3600).unwrap();
+
+            Self {
+                switch: local - Duration::seconds(before.local_minus_utc() as i64),
+                before,
+                after: FixedOffset::east_opt(after_hours * 3600).unwrap(),
+            }
+        }
+
+        fn utc(local: &NaiveDateTime, offset: &FixedOffset) -> NaiveDateTime {
+            *local - Duration::seconds(offset.local_minus_utc() as i64)
+        }
+    }
+
+    impl TimeZone for ShiftingZone {
+        type Offset = FixedOffset;
+
+        fn from_offset(offset: &FixedOffset) -> Self {
+            Self {
+                switch: NaiveDateTime::MIN,
+                before: *offset,
+                after: *offset,
+            }
+        }
+
+        fn offset_from_local_date(&self, local: &NaiveDate) -> LocalResult<FixedOffset> {
+            self.offset_from_local_datetime(&local.and_hms_opt(0, 0, 0).unwrap())
+        }
+
+        fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> LocalResult<FixedOffset> {
+            let before = Self::utc(local, &self.before) < self.switch;
+            let after = Self::utc(local, &self.after) >= self.switch;
+
+            match (before, after) {
+                (true, true) => LocalResult::Ambiguous(self.before, self.after),
+                (true, false) => LocalResult::Single(self.before),
+                (false, true) => LocalResult::Single(self.after),
+                (false, false) => LocalResult::None,
+            }
+        }
+
+        fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+            self.offset_from_utc_datetime(&utc.and_hms_opt(0, 0, 0).unwrap())
+        }
+
+        fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+            if *utc < self.switch {
+                self.before
+            } else {
+                self.after
+            }
+        }
+    }
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, month, day).unwrap()
+    }
+
+    fn at(month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, month, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    fn local(month: u32, day: u32, hour: u32) -> NaiveDateTime {
+        date(month, day).and_hms_opt(hour, 0, 0).unwrap()
+    }
+
+    fn bungie_start(range: TimeRange, now: DateTime<Utc>) -> DateTime<Utc> {
+        range.start(now, false, &Utc)
+    }
+
+    fn ms() -> Duration {
+        Duration::milliseconds(1)
+    }
+
+    #[test]
+    fn today_starts_at_the_daily_reset() {
+        // 2026-03-10 is a Tuesday.
+        assert_eq!(
+            bungie_start(TimeRange::Today, at(3, 10, 17, 0) - ms()),
+            at(3, 9, 17, 0)
+        );
+        assert_eq!(
+            bungie_start(TimeRange::Today, at(3, 10, 17, 0)),
+            at(3, 10, 17, 0)
+        );
+        assert_eq!(
+            bungie_start(TimeRange::Today, at(3, 11, 0, 0)),
+            at(3, 10, 17, 0)
+        );
+    }
+
+    #[test]
+    fn this_week_starts_at_the_weekly_reset() {
+        assert_eq!(
+            bungie_start(TimeRange::ThisWeek, at(3, 10, 17, 0) - ms()),
+            at(3, 3, 17, 0)
+        );
+        assert_eq!(
+            bungie_start(TimeRange::ThisWeek, at(3, 10, 17, 0)),
+            at(3, 10, 17, 0)
+        );
+        assert_eq!(
+            bungie_start(TimeRange::ThisWeek, at(3, 16, 23, 59)),
+            at(3, 10, 17, 0)
+        );
+        assert_eq!(
+            bungie_start(TimeRange::ThisWeek, at(3, 17, 16, 59)),
+            at(3, 10, 17, 0)
+        );
+    }
+
+    #[test]
+    fn last_four_resets_covers_four_weeks() {
+        assert_eq!(
+            bungie_start(TimeRange::LastFourResets, at(3, 10, 17, 0) - ms()),
+            at(2, 10, 17, 0)
+        );
+        assert_eq!(
+            bungie_start(TimeRange::LastFourResets, at(3, 10, 17, 0)),
+            at(2, 17, 17, 0)
+        );
+    }
+
+    #[test]
+    fn since_starts_at_that_days_reset() {
+        let now = at(3, 10, 20, 0);
+
+        assert_eq!(
+            bungie_start(TimeRange::Since(date(3, 5)), now),
+            at(3, 5, 17, 0)
+        );
+        assert_eq!(
+            bungie_start(TimeRange::Since(date(3, 10)), now),
+            at(3, 10, 17, 0)
+        );
+        assert_eq!(
+            bungie_start(TimeRange::Since(date(3, 12)), now),
+            at(3, 10, 17, 0)
+        );
+
+        let before_reset = at(3, 10, 17, 0) - ms();
+        assert_eq!(
+            bungie_start(TimeRange::Since(date(3, 10)), before_reset),
+            at(3, 9, 17, 0)
+        );
+    }
+
+    #[test]
+    fn real_time_counts_back_from_now() {
+        let now = at(3, 10, 16, 30);
+
+        assert_eq!(TimeRange::Today.start(now, true, &Utc), at(3, 9, 16, 30));
+        assert_eq!(TimeRange::ThisWeek.start(now, true, &Utc), at(3, 3, 16, 30));
+        assert_eq!(
+            TimeRange::LastFourResets.start(now, true, &Utc),
+            at(2, 8, 16, 30)
+        );
+        assert_eq!(
+            TimeRange::Since(date(3, 5)).start(now, true, &Utc),
+            at(3, 5, 0, 0)
+        );
+    }
+
+    #[test]
+    fn parses_the_filter_timespans() {
+        assert_eq!(TimeRange::parse("1", "").unwrap(), Some(TimeRange::Today));
+        assert_eq!(
+            TimeRange::parse("7", "").unwrap(),
+            Some(TimeRange::ThisWeek)
+        );
+        assert_eq!(
+            TimeRange::parse("30", "").unwrap(),
+            Some(TimeRange::LastFourResets)
+        );
+        assert_eq!(
+            TimeRange::parse("custom", "2026-03-05").unwrap(),
+            Some(TimeRange::Since(date(3, 5)))
+        );
+        assert_eq!(TimeRange::parse("custom", " ").unwrap(), None);
+        assert_eq!(TimeRange::parse("all", "").unwrap(), None);
+        assert!(TimeRange::parse("custom", "yesterday").is_err());
+        assert!(TimeRange::parse("90", "").is_err());
+    }
+
+    #[test]
+    fn range_start_uses_bungie_time() {
+        let now = at(3, 10, 17, 0);
+
+        assert_eq!(
+            range_start("7", "", false, now).unwrap(),
+            Some(at(3, 10, 17, 0))
+        );
+        assert_eq!(range_start("custom", "", false, now).unwrap(), None);
+    }
+
+    #[test]
+    fn start_of_day_skips_a_missing_midnight() {
+        // Clocks go from 00:00 at UTC-4 straight to 01:00 at UTC-3.
+        let zone = ShiftingZone::new(local(9, 6, 0), -4, -3);
+
+        assert_eq!(start_of_day(date(9, 5), &zone), at(9, 5, 4, 0));
+        assert_eq!(start_of_day(date(9, 6), &zone), at(9, 6, 4, 0));
+        assert_eq!(start_of_day(date(9, 7), &zone), at(9, 7, 3, 0));
+    }
+
+    #[test]
+    fn start_of_day_takes_the_first_of_two_midnights() {
+        // Clocks go from 01:00 at UTC-3 back to 00:00 at UTC-4.
+        let zone = ShiftingZone::new(local(4, 5, 1), -3, -4);
+
+        assert_eq!(start_of_day(date(4, 4), &zone), at(4, 4, 3, 0));
+        assert_eq!(start_of_day(date(4, 5), &zone), at(4, 5, 3, 0));
+        assert_eq!(start_of_day(date(4, 6), &zone), at(4, 6, 4, 0));
+    }
+
+    #[test]
+    fn since_in_real_time_follows_the_zone() {
+        let zone = ShiftingZone::new(local(9, 6, 0), -4, -3);
+        let now = at(9, 10, 12, 0);
+
+        assert_eq!(
+            TimeRange::Since(date(9, 6)).start(now, true, &zone),
+            at(9, 6, 4, 0)
+        );
+    }
+}
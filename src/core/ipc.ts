@@ -1,5 +1,5 @@
 import { invoke } from "@tauri-apps/api/tauri";
-import type { ActivityInfo, BungieProfile, PlayerDataStatus, Preferences, Profile, ProfileInfo, Profiles, MediaInfo } from "./types";
+import type { ActivityInfo, ApiHealth, AttemptReport, BungieProfile, CharacterInfo, CsvMapping, HistoryFilter, HistoryStats, PersonalBest, PlayerDataStatus, PlaySession, Preferences, Profile, ProfileInfo, Profiles, MediaInfo } from "./types";
 
 export function openPreferences(): Promise<void> {
     return invoke("open_preferences");
@@ -33,6 +33,10 @@ export function getActivityInfo(activityHash: number): Promise<ActivityInfo> {
     return invoke("get_activity_info", { activityHash });
 }
 
+export function getCharacters(profile: Profile): Promise<CharacterInfo[]> {
+    return invoke("get_characters", { profile });
+}
+
 export function searchProfile(displayName: string, displayNameCode: number): Promise<BungieProfile[]> {
     return invoke("search_profile", { displayName, displayNameCode });
 }
@@ -53,6 +57,46 @@ export function clearAndRestartTimer(): Promise<void> {
     return invoke("clear_and_restart_timer");
 }
 
+export function splitTimer(): Promise<void> {
+    return invoke("split_timer");
+}
+
+export function exportHistory(path: string, format: 'csv' | 'json', filter: HistoryFilter): Promise<number> {
+    return invoke("export_history", { path, format, filter });
+}
+
+export function getStats(filter: HistoryFilter): Promise<HistoryStats> {
+    return invoke("get_stats", { filter });
+}
+
+export function getRangeStart(timespan: string, customStartDate: string, useRealTime: boolean): Promise<string | null> {
+    return invoke("get_range_start", { timespan, customStartDate, useRealTime });
+}
+
+export function getAttempts(filter: HistoryFilter): Promise<AttemptReport> {
+    return invoke("get_attempts", { filter });
+}
+
+export function getSessions(): Promise<PlaySession[]> {
+    return invoke("get_sessions");
+}
+
+export function getPersonalBests(): Promise<PersonalBest[]> {
+    return invoke("get_personal_bests");
+}
+
+export function importHistory(path: string, format: 'csv' | 'json', mapping: CsvMapping | null = null): Promise<number> {
+    return invoke("import_history", { path, format, mapping });
+}
+
 export function getCurrentMedia(): Promise<MediaInfo> {
     return invoke("get_current_media");
 }
+
+export function getApiHealth(): Promise<ApiHealth> {
+    return invoke("get_api_health");
+}
+
+export function discordPresenceAvailable(): Promise<boolean> {
+    return invoke("discord_presence_available");
+}
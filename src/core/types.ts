@@ -39,6 +39,10 @@ export type Preferences = {
     useRealTime: boolean;
     displayAverageClearTimeDetails: boolean;
     displayDifferenceIndicator: boolean;
+    displayPersonalBestNotifications: boolean;
+    personalBestPerClass: boolean;
+    personalBestFullRunsOnly: boolean;
+    sessionGapMinutes: number;
     primaryBackground: string;
     secondaryBackground: string;
     primaryHighlight: string;
@@ -46,6 +50,7 @@ export type Preferences = {
     incompleteColor: string;
     completedColor: string;
     filterActivityType: string;
+    filterActivityHashes: number[];
     filterTimespan: string;
     timerMode: 'default' | 'persistent';
     raidLinkProvider: 'raid.report' | 'raidhub.io';
@@ -57,6 +62,34 @@ export type Preferences = {
     customStartDate: string;
     displayNowPlaying: boolean;
     overlayBackgroundOpacity: number;
+    enableLocalApi: boolean;
+    localApiPort: number;
+    localApiToken: string;
+    enableFileOutput: boolean;
+    fileOutputDir: string;
+    enableDiscordPresence: boolean;
+    discordShowTimer: boolean;
+    discordShowClears: boolean;
+    discordShowImage: boolean;
+    enableLivesplit: boolean;
+    livesplitHost: string;
+    livesplitPort: number;
+    enableWebhooks: boolean;
+    webhooks: Webhook[];
+    enableMqtt: boolean;
+    mqttHost: string;
+    mqttPort: number;
+    mqttUsername: string;
+    mqttPassword: string;
+    mqttTopicPrefix: string;
+};
+
+export type Webhook = {
+    url: string;
+    preset: 'discord' | 'generic' | 'custom';
+    template: string;
+    onClear: boolean;
+    onPersonalBest: boolean;
 };
 
 export type PlayerDataStatus = {
@@ -69,6 +102,16 @@ export type PlayerData = {
     currentActivity: CurrentActivity;
     activityHistory: CompletedActivity[];
     profileInfo: ProfileInfo;
+    characters: CharacterInfo[];
+};
+
+export type CharacterInfo = {
+    characterId: string;
+    className: string;
+    raceName: string;
+    light: number;
+    emblemPath: string | null;
+    dateLastPlayed: string;
 };
 
 export type CurrentActivity = {
@@ -87,13 +130,125 @@ export type CompletedActivity = {
     period: string;
     instanceId: string;
     completed: boolean;
+    failed: boolean;
     activityDuration: string;
     activityDurationSeconds: number;
+    startSeconds: number;
     activityHash: number;
     modes: number[];
     characterClass?: string;
 };
 
+export type HistoryFilter = {
+    since?: string | null;
+    until?: string | null;
+    activityType?: string | null;
+    activityHashes?: number[] | null;
+    characterClass?: string | null;
+    runType?: 'full' | 'checkpoint' | null;
+    timespan?: string | null;
+    customStartDate?: string | null;
+    useRealTime?: boolean;
+};
+
+export type TrendPoint = {
+    weekStart: string;
+    attempts: number;
+    clears: number;
+    averageClearSeconds: number;
+};
+
+export type ClearStats = {
+    attempts: number;
+    clears: number;
+    completionRate: number;
+    averageClearSeconds: number;
+    fastestClearSeconds: number | null;
+    medianClearSeconds: number | null;
+    p90ClearSeconds: number | null;
+    playedSeconds: number;
+    trend: TrendPoint[];
+};
+
+export type ActivityStats = ClearStats & {
+    activityHash: number;
+};
+
+export type HistoryStats = ClearStats & {
+    activities: ActivityStats[];
+};
+
+export type PersonalBest = {
+    activityHash: number;
+    characterClass: string | null;
+    fullRunsOnly: boolean;
+    instanceId: string;
+    period: string;
+    durationSeconds: number;
+    previousSeconds: number | null;
+};
+
+export type SessionActivity = {
+    activityHash: number;
+    attempts: number;
+    clears: number;
+    activitySeconds: number;
+};
+
+export type PlaySession = {
+    start: string;
+    end: string;
+    ongoing: boolean;
+    totalSeconds: number;
+    activitySeconds: number;
+    orbitSeconds: number;
+    attempts: number;
+    clears: number;
+    activities: SessionActivity[];
+};
+
+export type AttemptOutcome = 'cleared' | 'failed' | 'abandoned' | 'unknown';
+
+export type Attempt = {
+    activityHash: number;
+    instanceId: string | null;
+    start: string;
+    end: string;
+    durationSeconds: number;
+    outcome: AttemptOutcome;
+};
+
+export type AttemptStats = {
+    attempts: number;
+    clears: number;
+    failed: number;
+    abandoned: number;
+    unknown: number;
+    attemptsPerClear: number | null;
+    unfinishedSeconds: number;
+};
+
+export type ActivityAttempts = AttemptStats & {
+    activityHash: number;
+};
+
+export type AttemptReport = AttemptStats & {
+    activities: ActivityAttempts[];
+    attempts: Attempt[];
+};
+
+export type CsvMapping = {
+    instanceId?: string | null;
+    activityHash?: string | null;
+    characterClass?: string | null;
+    start?: string | null;
+    durationSeconds?: string | null;
+    completed?: string | null;
+    failed?: string | null;
+    modes?: string | null;
+    startSeconds?: string | null;
+};
+
 export interface TimerState {
     timeText: string;
     msText: string;
@@ -111,3 +266,20 @@ export type MediaInfo = {
     appId: string;
     hasMedia: boolean;
 };
+
+export type GlobalAlert = {
+    key: string;
+    html: string;
+    text: string;
+    link: string | null;
+    level: number;
+    timestamp: string;
+};
+
+export type ApiHealth = {
+    status: 'healthy' | 'alerts' | 'disabled' | 'unreachable';
+    destiny2Enabled: boolean;
+    alerts: GlobalAlert[];
+    error: string | null;
+    lastChecked: string | null;
+};
@@ -1,5 +1,6 @@
-import { ACTIVITY_TYPES, KNOWN_RAIDS, KNOWN_DUNGEONS } from "./consts";
-import type { CompletedActivity } from "./types";
+import { ACTIVITY_TYPES, KNOWN_RAIDS, KNOWN_DUNGEONS, GROUPED_RAIDS, GROUPED_DUNGEONS } from "./consts";
+import { getRangeStart } from "./ipc";
+import type { CompletedActivity, HistoryFilter } from "./types";
 
 export function formatTime(millis: number): string {
     let seconds = Math.floor(millis / 1000);
@@ -40,15 +41,6 @@ export function determineActivityType(modes: number[]): string | undefined {
     }
 }
 
-export function calculateAverageClearTime(activities: CompletedActivity[]): number {
-    const completedActivities = activities.filter(a => a.completed);
-    if (completedActivities.length === 0) return 0;
-
-    const totalTime = completedActivities.reduce((sum, activity) =>
-        sum + activity.activityDurationSeconds, 0);
-    return totalTime / completedActivities.length;
-}
-
 export function formatTimeWithUnit(seconds: number): string {
     let hours = Math.floor(seconds / 3600);
     let minutes = Math.floor((seconds % 3600) / 60);
@@ -86,3 +78,24 @@ export function resolveActivityName(activityHash: number, fallbackName: string):
 
     return fallbackName;
 }
+
+export async function timespanCutoff(timespan: string, customStartDate: string, useRealTime: boolean): Promise<Date | null> {
+    const start = await getRangeStart(timespan, customStartDate, useRealTime);
+    return start ? new Date(start) : null;
+}
+
+export function historyFilter(activityType: string, timespan: string, customStartDate: string, useRealTime: boolean): HistoryFilter {
+    const filter: HistoryFilter = { timespan, customStartDate, useRealTime };
+
+    if (activityType.startsWith('grouped-raid-')) {
+        filter.activityHashes = (GROUPED_RAIDS as Record<string, { name: string; hashes: number[] }>)[activityType.replace('grouped-raid-', '')]?.hashes ?? [];
+    } else if (activityType.startsWith('grouped-dungeon-')) {
+        filter.activityHashes = (GROUPED_DUNGEONS as Record<string, { name: string; hashes: number[] }>)[activityType.replace('grouped-dungeon-', '')]?.hashes ?? [];
+    } else if (activityType.startsWith('raid-') || activityType.startsWith('dungeon-')) {
+        filter.activityHashes = [parseInt(activityType.split('-')[1])];
+    } else if (activityType !== 'all') {
+        filter.activityType = activityType;
+    }
+
+    return filter;
+}
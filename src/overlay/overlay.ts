@@ -2,10 +2,10 @@ import "../core/global.css"
 import "./overlay.css"
 import { appWindow, WebviewWindow } from "@tauri-apps/api/window";
 import { createPopup as _createPopup, type Popup } from "./popups";
-import type { TauriEvent, Preferences, CurrentActivity, PlayerDataStatus } from "../core/types";
+import type { TauriEvent, Preferences, CurrentActivity, PlayerDataStatus, PersonalBest, ApiHealth } from "../core/types";
 import { emit, listen } from "@tauri-apps/api/event";
-import { countClears, determineActivityType, calculateAverageClearTime, formatTimeWithUnit } from "../core/util";
-import { getPlayerdata, getPreferences, getCurrentMedia } from "../core/ipc";
+import { determineActivityType, formatDifference, formatTimeWithUnit, historyFilter, timespanCutoff } from "../core/util";
+import { getPlayerdata, getPreferences, getCurrentMedia, getStats, getApiHealth } from "../core/ipc";
 import { THEME_UPDATE_EVENT } from "../core/theme";
 import { type TimerState, type MediaInfo } from "../core/types";
 import { GROUPED_RAIDS, GROUPED_DUNGEONS, KNOWN_RAIDS, KNOWN_DUNGEONS, EXCLUDED_ACTIVITIES } from "../core/consts";
@@ -41,6 +41,8 @@ const nowPlayingSongElem = document.querySelector<HTMLElement>("#now-playing-son
 const nowPlayingTitleElem = document.querySelector<HTMLElement>("#now-playing-title")!;
 const nowPlayingArtistElem = document.querySelector<HTMLElement>("#now-playing-artist")!;
 const nowPlayingIconElem = document.querySelector<SVGElement>("#now-playing > svg")!;
+const apiHealthElem = document.querySelector<HTMLElement>("#api-health")!;
+const apiHealthTextElem = document.querySelector<HTMLElement>("#api-health-text")!;
 
 let currentActivity: CurrentActivity | null;
 let lastRaidId: string | undefined;
@@ -71,6 +73,9 @@ const FILTER_CACHE_DURATION = 5000;
 
 let currentMediaInfo: MediaInfo | null = null;
 
+// Personal best events arrive before the playerdata update with the clear.
+const personalBests = new Map<string, PersonalBest>();
+
 function updateTimespanText() {
     if (!timespanTextElem) return;
     
@@ -205,8 +210,12 @@ async function init() {
         updateNowPlaying(mediaInfo);
     }).catch(() => {});
 
+    getApiHealth().then(updateApiHealth).catch(() => {});
+
+    appWindow.listen("health_update", (e: TauriEvent<ApiHealth>) => updateApiHealth(e.payload));
     appWindow.listen("preferences_update", (p: TauriEvent<Preferences>) => applyPreferences(p.payload));
     appWindow.listen("playerdata_update", (e: TauriEvent<PlayerDataStatus>) => refresh(e.payload));
+    appWindow.listen("personal_best", (e: TauriEvent<PersonalBest>) => personalBests.set(e.payload.instanceId, e.payload));
 }
 
 function checkClearCountUpdate() {
@@ -241,19 +250,21 @@ function performClearCountCheck() {
 
 function updateClearCountFromData(playerDataStatus: PlayerDataStatus) {
     if (!playerDataStatus?.lastUpdate) return;
-    
-    const filteredActivities = filterActivities(playerDataStatus.lastUpdate.activityHistory, currentTimespan, currentActivityType);
-    const currentClearCount = countClears(filteredActivities);
-    
-    if (currentClearCount !== lastClearCount) {
-        dailyElem.innerText = String(currentClearCount);
-        lastClearCount = currentClearCount;
-    }
 
-    if (prefs.displayAverageClearTimeOverlay) {
-        const averageTime = calculateAverageClearTime(filteredActivities);
-        averageTimeTextElem.innerHTML = formatAverageTime(averageTime);
-    }
+    updateStats();
+}
+
+function updateStats() {
+    getStats(historyFilter(currentActivityType, currentTimespan, currentCustomStartDate, prefs.useRealTime)).then(stats => {
+        if (stats.clears !== lastClearCount) {
+            dailyElem.innerText = String(stats.clears);
+            lastClearCount = stats.clears;
+        }
+
+        if (prefs.displayAverageClearTimeOverlay) {
+            averageTimeTextElem.innerHTML = formatAverageTime(stats.averageClearSeconds);
+        }
+    }).catch(() => {});
 }
 
 function clearFilterCache() {
@@ -293,12 +304,12 @@ function checkTimerVisibility() {
     updateSeparators();
 }
 
-function filterActivities(activities: any[], timespan: string, activityType: string) {
+function filterActivities(activities: any[], cutoff: Date | null, activityType: string) {
     if (!activities) return [];
     
     let filtered = activities.filter(activity => !EXCLUDED_ACTIVITIES.includes(activity.activityHash));
     
-    const cacheKey = `${timespan}_${activityType}_${prefs?.useRealTime}_${currentCustomStartDate}_${filtered.length}`;
+    const cacheKey = `${cutoff?.getTime()}_${activityType}_${filtered.length}`;
     const now = Date.now();
     
     if (cachedFilteredActivities.length > 0 && 
@@ -307,58 +318,8 @@ function filterActivities(activities: any[], timespan: string, activityType: str
         return cachedFilteredActivities;
     }
     
-    filtered = [...filtered];
-    const activityDate = (period: string) => new Date(period);
-    
-    if (prefs.useRealTime) {
-        const oneDayMs = 24 * 60 * 60 * 1000;
-        const cutoffs = {
-            '1': new Date(now - oneDayMs),
-            '7': new Date(now - 7 * oneDayMs),
-            '30': new Date(now - 30 * oneDayMs)
-        };
-        
-        if (timespan === '1' || timespan === '7' || timespan === '30') {
-            const cutoff = cutoffs[timespan as keyof typeof cutoffs];
-            filtered = filtered.filter(activity => activityDate(activity.period) >= cutoff);
-        } else if (timespan === 'custom' && currentCustomStartDate) {
-            const cutoff = new Date(currentCustomStartDate);
-            filtered = filtered.filter(activity => activityDate(activity.period) >= cutoff);
-        }
-    } else {
-        const nowDate = new Date(now);
-        
-        if (timespan === '1') {
-            const today = new Date(nowDate);
-            today.setUTCHours(17, 0, 0, 0);
-            if (today > nowDate) {
-                today.setUTCDate(today.getUTCDate() - 1);
-            }
-            filtered = filtered.filter(activity => activityDate(activity.period) >= today);
-        } else if (timespan === '7') {
-            const day = nowDate.getUTCDay();
-            const daysSinceTuesday = (day + 5) % 7;
-            const lastTuesday = new Date(nowDate);
-            lastTuesday.setUTCDate(nowDate.getUTCDate() - daysSinceTuesday);
-            lastTuesday.setUTCHours(17, 0, 0, 0);
-            filtered = filtered.filter(activity => activityDate(activity.period) >= lastTuesday);
-        } else if (timespan === '30') {
-            const day = nowDate.getUTCDay();
-            const daysSinceTuesday = (day + 5) % 7;
-            const lastTuesday = new Date(nowDate);
-            lastTuesday.setUTCDate(nowDate.getUTCDate() - daysSinceTuesday);
-            const targetDate = new Date(lastTuesday);
-            targetDate.setUTCDate(lastTuesday.getUTCDate() - 21);
-            targetDate.setUTCHours(17, 0, 0, 0);
-            filtered = filtered.filter(activity => activityDate(activity.period) >= targetDate);
-        } else if (timespan === 'custom' && currentCustomStartDate) {
-            const selectedDate = new Date(currentCustomStartDate);
-            selectedDate.setUTCHours(17, 0, 0, 0);
-            if (selectedDate > nowDate) {
-                selectedDate.setUTCDate(selectedDate.getUTCDate() - 1);
-            }
-            filtered = filtered.filter(activity => activityDate(activity.period) >= selectedDate);
-        }
+    if (cutoff) {
+        filtered = filtered.filter(activity => new Date(activity.period) >= cutoff);
     }
     
     if (activityType === 'all') return filtered;
@@ -410,7 +371,7 @@ function filterActivities(activities: any[], timespan: string, activityType: str
     return filtered;
 }
 
-function refresh(playerDataStatus: PlayerDataStatus) {
+async function refresh(playerDataStatus: PlayerDataStatus) {
     let playerData = playerDataStatus?.lastUpdate;
 
     if (playerDataStatus?.error) {
@@ -447,13 +408,9 @@ function refresh(playerDataStatus: PlayerDataStatus) {
     cachedPlayerData = playerDataStatus;
     lastPlayerDataCheck = Date.now();
     
-    const filteredActivities = filterActivities(playerData.activityHistory, currentTimespan, currentActivityType);
-    dailyElem.innerText = String(countClears(filteredActivities));
-
-    if (prefs.displayAverageClearTimeOverlay) {
-        const averageTime = calculateAverageClearTime(filteredActivities);
-        averageTimeTextElem.innerHTML = formatAverageTime(averageTime);
-    }
+    const cutoff = await timespanCutoff(currentTimespan, currentCustomStartDate, prefs.useRealTime).catch(() => null);
+    const filteredActivities = filterActivities(playerData.activityHistory, cutoff, currentActivityType);
+    updateStats();
 
     let latestRaid = filteredActivities[0];
     
@@ -472,7 +429,17 @@ function refresh(playerDataStatus: PlayerDataStatus) {
             }
             
             const title = activityName ? activityName : typeFormatted;
-            createPopup({ title, subtext: `API Time: <strong>${latestRaid.activityDuration}</strong>` });
+            const personalBest = personalBests.get(latestRaid.instanceId);
+
+            if (personalBest && prefs.displayPersonalBestNotifications) {
+                const improvement = personalBest.previousSeconds !== null
+                    ? ` (${formatDifference(personalBest.durationSeconds - personalBest.previousSeconds)})`
+                    : '';
+                createPopup({ title: "New Personal Best", subtext: `${title}: <strong>${latestRaid.activityDuration}</strong>${improvement}` });
+            } else {
+                createPopup({ title, subtext: `API Time: <strong>${latestRaid.activityDuration}</strong>` });
+            }
+            personalBests.delete(latestRaid.instanceId);
         }
     }
 
@@ -510,15 +477,8 @@ function applyPreferences(p: Preferences) {
         }
     }
 
-    if (averageTimeDisplayChanged && p.displayAverageClearTimeOverlay) {
-        (async () => {
-            const playerData = await getPlayerdata();
-            if (playerData && cachedPlayerData?.lastUpdate) {
-                const filteredActivities = filterActivities(cachedPlayerData.lastUpdate.activityHistory, currentTimespan, currentActivityType);
-                const averageTime = calculateAverageClearTime(filteredActivities);
-                averageTimeTextElem.innerHTML = formatAverageTime(averageTime);
-            }
-        })();
+    if (averageTimeDisplayChanged && p.displayAverageClearTimeOverlay && cachedPlayerData?.lastUpdate) {
+        updateStats();
     }
     
     if (msElem) {
@@ -613,6 +573,32 @@ function updateSeparators() {
     });
 }
 
+function updateApiHealth(health: ApiHealth) {
+    switch (health.status) {
+        case 'disabled':
+            apiHealthTextElem.textContent = 'API maintenance';
+            break;
+        case 'unreachable':
+            apiHealthTextElem.textContent = 'API unreachable';
+            break;
+        case 'alerts':
+            apiHealthTextElem.textContent = 'API alerts';
+            break;
+        default:
+            apiHealthTextElem.textContent = '';
+    }
+
+    apiHealthElem.title = health.alerts.map(alert => alert.text).join('\n');
+
+    if (health.status === 'healthy') {
+        apiHealthElem.classList.add("hidden");
+    } else {
+        apiHealthElem.classList.remove("hidden");
+    }
+
+    updateSeparators();
+}
+
 function updateNowPlaying(mediaInfo: MediaInfo) {
     currentMediaInfo = mediaInfo;
     